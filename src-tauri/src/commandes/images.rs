@@ -2,19 +2,26 @@
  * Module de conversion d'images pour MacGyver
  * 
  * Fonctionnalités :
- * - Conversion entre formats (JPG, PNG, WebP, BMP, TIFF, GIF)
+ * - Conversion entre formats (JPG, PNG, WebP, BMP, TIFF, GIF, ICO, et AVIF en sortie)
  * - Redimensionnement d'images
  * - Optimisation de qualité
  * - Traitement par lots
  */
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use image::ImageFormat;
-use log::{info, error};
+use log::{info, warn, error};
 use crate::erreurs::{ErreurApplication, ResultatApplication};
 use crate::utilitaires::progression::creer_progression_image;
-use crate::utilitaires::fichiers::{verifier_fichier_existe, creer_dossier_recursif, formater_taille_fichier};
+use crate::utilitaires::jpeg::{lire_coefficients_jpeg, ecrire_jpeg_dct};
+use crate::utilitaires::fichiers::{verifier_fichier_existe, creer_dossier_recursif, formater_taille_fichier, lister_fichiers_par_extension};
+
+/// Extensions prises en compte lors du parcours d'un dossier d'images
+const EXTENSIONS_IMAGES: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "tiff", "tif", "gif"];
 
 // === Types de données ===
 
@@ -26,6 +33,75 @@ pub struct InfoImage {
     pub largeur: u32,
     pub hauteur: u32,
     pub taille_octets: u64,
+    /// Format annoncé par l'extension du fichier (absent si l'extension est inconnue)
+    pub format_extension: Option<String>,
+    /// L'extension ne correspond pas au format réel du contenu
+    pub extension_incorrecte: bool,
+    /// Extension conseillée pour le format réel
+    pub extension_suggeree: String,
+    pub metadonnees: MetadonneesImage,
+    /// Nombre d'images (plus d'une pour un GIF ou un WebP animé)
+    pub nombre_trames: u32,
+    /// Durée d'affichage de chaque image d'une animation, en millisecondes
+    pub delais_trames_ms: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MetadonneesImage {
+    pub contient_exif: bool,
+    pub contient_xmp: bool,
+    pub contient_iptc: bool,
+    /// Marque et modèle de l'appareil
+    pub appareil: Option<String>,
+    pub objectif: Option<String>,
+    /// Temps de pose, ex : "1/125 s"
+    pub temps_pose: Option<String>,
+    /// Ouverture, ex : "f/2.8"
+    pub ouverture: Option<String>,
+    pub iso: Option<u32>,
+    /// Focale, ex : "35 mm"
+    pub focale: Option<String>,
+    /// Date de prise de vue au format "AAAA-MM-JJ HH:MM:SS"
+    pub date_prise_vue: Option<String>,
+    pub gps: Option<CoordonneesGps>,
+    /// Orientation EXIF (1 à 8, 1 = normale)
+    pub orientation: Option<u16>,
+    pub auteur: Option<String>,
+    pub copyright: Option<String>,
+    pub logiciel: Option<String>,
+    /// Tous les champs EXIF lus, pour un affichage détaillé
+    pub champs: Vec<ChampMetadonnee>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CoordonneesGps {
+    /// Latitude en degrés décimaux (négative au sud)
+    pub latitude: f64,
+    /// Longitude en degrés décimaux (négative à l'ouest)
+    pub longitude: f64,
+    /// Altitude en mètres
+    pub altitude: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChampMetadonnee {
+    pub groupe: String,
+    pub nom: String,
+    pub valeur: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatNettoyageMetadonnees {
+    pub succes: bool,
+    pub fichier_origine: String,
+    pub fichier_sortie: String,
+    /// Éléments retirés (blocs EXIF/XMP/IPTC, GPS, champs personnels…)
+    pub elements_supprimes: Vec<String>,
+    /// Les pixels ont dû être réencodés (TIFF en mode "tout")
+    pub pixels_reencodes: bool,
+    pub taille_avant: u64,
+    pub taille_apres: u64,
+    pub message: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -35,11 +111,98 @@ pub struct OptionsConversion {
     pub largeur_max: Option<u32>,
     pub hauteur_max: Option<u32>,
     pub conserver_ratio: bool,
+    /// Transformations appliquées dans l'ordre, avant le redimensionnement
+    pub transformations: Option<Vec<EtapeTransformation>>,
+    /// Réglages de couleur et filtres appliqués dans l'ordre, après le redimensionnement
+    pub reglages: Option<Vec<ReglageImage>>,
+    /// Taille maximale du fichier produit : la qualité puis les dimensions sont réduites jusqu'à l'atteindre
+    pub taille_cible_octets: Option<u64>,
+    /// Filigrane apposé en dernier, après les réglages
+    pub filigrane: Option<OptionsFiligrane>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsFiligrane {
+    pub contenu: ContenuFiligrane,
+    /// Position parmi les ancrages de recadrage ("centre", "bas_droite"…) ou "mosaique"
+    pub position: String,
+    /// Marge aux bords de l'image, ou espacement des motifs en mosaïque (pixels)
+    pub marge: u32,
+    /// Opacité de 0 (invisible) à 1 (opaque)
+    pub opacite: f32,
+    /// Largeur du filigrane rapportée à celle de l'image (ex. 0.25)
+    pub echelle: f32,
+    /// Rotation en degrés, sens horaire
+    pub rotation_degres: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContenuFiligrane {
+    /// Texte sur une ligne, rendu avec la police intégrée (DejaVu Sans Bold)
+    Texte { texte: String, couleur: String },
+    /// Image de logo, de préférence un PNG transparent
+    Logo { chemin: String },
+}
+
+/// Réglage de couleur ou filtre (sérialisé avec un champ "type")
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReglageImage {
+    /// Décalage de luminosité (-255 à 255)
+    Luminosite { valeur: i32 },
+    /// Contraste en pourcentage (négatif pour réduire)
+    Contraste { valeur: f32 },
+    /// Facteur de saturation (0 = gris, 1 = inchangé, 2 = double)
+    Saturation { facteur: f32 },
+    /// Rotation de la teinte en degrés
+    Teinte { degres: i32 },
+    /// Correction gamma (> 1 éclaircit les tons moyens)
+    Gamma { valeur: f32 },
+    NiveauxDeGris,
+    Sepia,
+    Inversion,
+    /// Flou gaussien d'écart type `sigma` pixels
+    FlouGaussien { sigma: f32 },
+    /// Masque flou : `sigma` du flou, `seuil` de différence minimale à renforcer
+    Nettete { sigma: f32, seuil: i32 },
+    /// Étirement d'histogramme par canal, en ignorant `ecretage_pourcent` des pixels extrêmes
+    NiveauxAutomatiques { ecretage_pourcent: f32 },
+}
+
+/// Étape du pipeline de transformation (sérialisée avec un champ "type")
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EtapeTransformation {
+    /// Recadrage sur un rectangle en pixels
+    Recadrage { x: u32, y: u32, largeur: u32, hauteur: u32 },
+    /// Recadrage au plus grand rectangle du ratio donné
+    RecadrageRatio {
+        ratio_largeur: u32,
+        ratio_hauteur: u32,
+        /// "centre", "haut", "bas", "gauche", "droite", "haut_gauche", "haut_droite", "bas_gauche" ou "bas_droite"
+        ancrage: String,
+    },
+    /// Rotation dans le sens horaire ; les coins découverts prennent la couleur de fond
+    Rotation {
+        angle_degres: f32,
+        /// "#RRGGBB", "#RRGGBBAA" ou "transparent"
+        couleur_fond: String,
+    },
+    MiroirHorizontal,
+    MiroirVertical,
+    /// Marges ajoutées autour de l'image
+    Marge { haut: u32, droite: u32, bas: u32, gauche: u32, couleur: String },
+    /// Cadre dessiné sur le pourtour de l'image, sans changer ses dimensions
+    Bordure { epaisseur: u32, couleur: String },
+    /// Coins arrondis transparents (nécessite un format de sortie avec transparence)
+    CoinsArrondis { rayon: u32 },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OptionsSuppressionFond {
     /// Couleur de fond à supprimer (format hex: "#FFFFFF")
+    /// Vide : couleur dominante du pourtour de l'image
     pub couleur_fond: String,
     /// Tolérance pour la suppression (0-255)
     pub tolerance: u8,
@@ -47,6 +210,63 @@ pub struct OptionsSuppressionFond {
     pub adoucir_bords: bool,
     /// Rayon d'adoucissement
     pub rayon_adoucissement: u8,
+    /// Étendue de la suppression : "global" (par défaut, toute l'image),
+    /// "bords" (fond relié au pourtour), "points" (fond relié aux points de départ)
+    /// ou "chromatique" (incrustation sur fond vert ou bleu)
+    pub mode: Option<String>,
+    /// Points cliqués par l'utilisateur, pour le mode "points"
+    pub points_depart: Option<Vec<PointImage>>,
+    /// Espace de mesure de la distance des couleurs : "rgb" (par défaut), "hsv" ou "lab"
+    pub espace_couleur: Option<String>,
+    /// Fondu du contour vers l'intérieur du sujet, en pixels
+    pub rayon_fondu: Option<u8>,
+    /// Retirer la couleur du fond mélangée aux pixels du contour
+    pub decontaminer_couleurs: Option<bool>,
+    /// Réglages du mode "chromatique"
+    pub cle_chromatique: Option<OptionsCleChromatique>,
+    /// Format de sortie : "png" (par défaut), "webp", "avif", "tiff" ou "gif" ;
+    /// les formats sans transparence exigent un fond de composition
+    pub format_sortie: Option<String>,
+    /// Qualité des formats avec perte (100 par défaut : WebP sans perte)
+    pub qualite: Option<u8>,
+    /// Fond sur lequel poser le sujet détouré
+    pub fond_composition: Option<FondComposition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FondComposition {
+    /// Couleur unie (#RRGGBB ou #RRGGBBAA)
+    Couleur { couleur: String },
+    /// Image redimensionnée et recadrée pour couvrir tout le sujet
+    Image { chemin: String },
+    /// Damier gris et blanc, pour prévisualiser la transparence
+    Damier { taille_case: u32 },
+}
+
+/**
+ * Incrustation (chroma key) : la couleur clé est `couleur_fond`, ou la couleur
+ * dominante du pourtour si elle est vide
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsCleChromatique {
+    /// Écart de teinte toléré autour de la couleur clé, en degrés (ex. 25)
+    pub tolerance_teinte: f32,
+    /// Écart de saturation toléré, de 0 à 1 (ex. 0.4)
+    pub tolerance_saturation: f32,
+    /// Écart de valeur (luminosité HSV) toléré, de 0 à 1 (ex. 0.5)
+    pub tolerance_valeur: f32,
+    /// Seuil extérieur, en multiple des tolérances (ex. 1.5) : entre les
+    /// tolérances et ce seuil, les pixels deviennent semi-transparents
+    pub seuil_exterieur: f32,
+    /// Neutraliser le reflet vert ou bleu du fond sur le sujet
+    pub supprimer_debordement: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct PointImage {
+    pub x: u32,
+    pub y: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,6 +280,320 @@ pub struct ResultatConversion {
     pub taille_apres: u64,
     pub reduction_pourcent: f32,
     pub message: String,
+    /// Qualité d'encodage retenue (formats avec perte uniquement)
+    pub qualite_utilisee: Option<u8>,
+    pub largeur_sortie: u32,
+    pub hauteur_sortie: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsConversionLot {
+    /// Fichiers et/ou dossiers à convertir
+    pub entrees: Vec<String>,
+    /// Parcourir les sous-dossiers des dossiers donnés
+    pub recursif: bool,
+    pub dossier_sortie: String,
+    /// Modèle du nom de sortie, ex : "{nom}_{largeur}x{hauteur}.{ext}" ({nom} obligatoire) ;
+    /// les sous-dossiers parcourus sont reproduits dans le dossier de sortie
+    pub modele_nom: String,
+    /// Écraser les fichiers de sortie existants (sinon ils sont ignorés)
+    pub ecraser_existants: bool,
+    /// Nombre maximal de conversions simultanées (4 par défaut)
+    pub concurrence_max: Option<usize>,
+    pub options: OptionsConversion,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResultatConversionLot {
+    /// Résultat de chaque fichier, y compris les échecs et les fichiers ignorés
+    pub resultats: Vec<ResultatConversion>,
+    pub fichiers_convertis: u32,
+    pub fichiers_ignores: u32,
+    pub fichiers_en_echec: u32,
+    /// Tailles cumulées des fichiers convertis
+    pub taille_totale_avant: u64,
+    pub taille_totale_apres: u64,
+    pub reduction_pourcent: f32,
+    pub temps_total_ms: u64,
+}
+
+/// Fichier d'un lot, avec l'emplacement et le nom de base de sa sortie
+struct FichierLot {
+    source: PathBuf,
+    /// Sous-dossier relatif au dossier d'entrée, reproduit dans le dossier de sortie
+    sous_dossier: PathBuf,
+    /// Nom de base unique dans son sous-dossier, remplace {nom} dans le modèle
+    nom: String,
+}
+
+/// Issue de la conversion d'un fichier dans un lot
+enum IssueConversionLot {
+    Convertie(ResultatConversion),
+    Ignoree(ResultatConversion),
+    Echec(ResultatConversion),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorrectionExtension {
+    pub chemin_origine: String,
+    /// Chemin après correction (identique à l'origine si l'extension était correcte)
+    pub chemin_corrige: String,
+    pub format: String,
+    pub renomme: bool,
+    pub erreur: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsOptimisation {
+    /// Effort de recompression PNG, de 0 (rapide) à 6 (maximal) ; 2 par défaut
+    pub niveau_png: Option<u8>,
+    /// Réduire un PNG à 256 couleurs (avec perte)
+    pub quantifier: bool,
+    /// Tramage Floyd-Steinberg lors de la quantification
+    pub tramage: bool,
+    /// Réencoder le JPEG (avec perte) au lieu de le transcoder sans perte
+    pub reencoder: bool,
+    /// Qualité du réencodage JPEG ; absente, la qualité estimée de l'original est reprise
+    pub qualite: Option<u8>,
+    /// JPEG progressif (affichage par passes successives)
+    pub progressif: bool,
+    /// Sous-échantillonnage de la chrominance lors du réencodage JPEG :
+    /// "4:4:4", "4:2:2" ou "4:2:0" (par défaut)
+    pub sous_echantillonnage: Option<String>,
+    /// Conserver EXIF, ICC et autres blocs de métadonnées
+    pub conserver_metadonnees: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsIcones {
+    /// Nom complet de l'application, pour le manifeste
+    pub nom_application: String,
+    /// Nom court affiché sous l'icône (nom complet par défaut)
+    pub nom_court: Option<String>,
+    /// Couleur de thème du manifeste (#RRGGBB)
+    pub couleur_theme: Option<String>,
+    /// Couleur de fond des icônes Apple, qui n'admettent pas la transparence,
+    /// et de l'écran de démarrage (blanc par défaut)
+    pub couleur_fond: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FichierIcone {
+    pub chemin: String,
+    /// Tailles contenues (plusieurs pour le .ico)
+    pub tailles: Vec<u32>,
+    pub taille_octets: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatIcones {
+    pub succes: bool,
+    pub dossier_sortie: String,
+    pub fichiers: Vec<FichierIcone>,
+    /// Contenu du manifest.json écrit dans le dossier
+    pub manifeste: String,
+    /// Balises à placer dans le <head> des pages
+    pub balises_html: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrameAnimation {
+    pub chemin: String,
+    /// Durée d'affichage en millisecondes (par pas de 10 ms en GIF)
+    pub delai_ms: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsAnimation {
+    /// Qualité WebP (100 par défaut : sans perte) ; sans effet en GIF
+    pub qualite: Option<u8>,
+    /// Nombre de répétitions, 0 ou absent pour une boucle infinie
+    pub boucles: Option<u16>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatTrames {
+    pub fichiers: Vec<String>,
+    pub delais_ms: Vec<u32>,
+    pub largeur: u32,
+    pub hauteur: u32,
+}
+
+/// Image d'une animation, à la taille du canevas
+struct Trame {
+    image: image::RgbaImage,
+    delai_ms: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsDoublons {
+    /// Parcourir les sous-dossiers des dossiers donnés
+    pub recursif: bool,
+    /// Empreinte utilisée pour comparer : "ahash", "dhash" ou "phash" (par défaut)
+    pub algorithme: Option<String>,
+    /// Distance de Hamming maximale, sur 64 bits, entre deux copies (5 par défaut)
+    pub seuil_distance: Option<u32>,
+    /// Nombre maximal d'images décodées simultanément (4 par défaut)
+    pub concurrence_max: Option<usize>,
+    /// Fichier de cache des empreintes (dossier de cache de l'utilisateur par défaut)
+    pub chemin_cache: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImageDoublon {
+    pub chemin: String,
+    pub largeur: u32,
+    pub hauteur: u32,
+    pub taille_octets: u64,
+    pub format: String,
+    /// Empreinte choisie, en hexadécimal
+    pub empreinte: String,
+    /// Distance de Hamming à la première image du groupe
+    pub distance: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupeDoublons {
+    /// Images du groupe, la meilleure copie (plus grande résolution, puis plus gros fichier) en premier
+    pub images: Vec<ImageDoublon>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResultatDoublons {
+    pub groupes: Vec<GroupeDoublons>,
+    pub images_analysees: u32,
+    /// Images dont l'empreinte venait du cache
+    pub images_en_cache: u32,
+    /// Fichiers illisibles, avec la raison
+    pub echecs: Vec<String>,
+    pub temps_total_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouleurDominante {
+    /// Couleur au format "#rrggbb"
+    pub hex: String,
+    pub rgb: [u8; 3],
+    /// Part des pixels opaques proches de cette couleur
+    pub pourcentage: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistogrammesImage {
+    /// 256 compteurs par canal
+    pub rouge: Vec<u32>,
+    pub vert: Vec<u32>,
+    pub bleu: Vec<u32>,
+    pub alpha: Vec<u32>,
+    pub luminosite: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalyseCouleurs {
+    /// Couleurs dominantes, de la plus présente à la moins présente
+    pub palette: Vec<CouleurDominante>,
+    pub histogrammes: HistogrammesImage,
+    /// Luminosité moyenne des pixels, de 0 à 255
+    pub luminosite_moyenne: f32,
+    /// Au moins un pixel n'est pas totalement opaque
+    pub alpha_utilise: bool,
+    pub pixels_transparents_pourcent: f32,
+    /// Tous les pixels sont gris, aux écarts de compression près
+    pub niveaux_de_gris: bool,
+    /// Couleur dominante du pourtour, celle que retient la suppression de fond automatique
+    pub couleur_fond_probable: Option<String>,
+}
+
+/// Disposition des images assemblées sur un même canevas
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DispositionAssemblage {
+    /// Grille de vignettes carrées, avec le nom de chaque fichier en légende si demandé
+    PlancheContact { colonnes: u32, taille_vignette: u32, legendes: bool },
+    /// Lignes d'images ramenées à la même hauteur, proportions conservées
+    Collage { colonnes: u32, hauteur_ligne: u32 },
+    /// Images à leur taille d'origine, avec leurs coordonnées en JSON et en CSS
+    Sprites { colonnes: Option<u32>, prefixe_classe: Option<String> },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsAssemblage {
+    pub disposition: DispositionAssemblage,
+    /// Marge autour et entre les images (10 px par défaut, 0 pour les sprites)
+    pub espacement: Option<u32>,
+    /// Couleur du canevas (blanc par défaut, transparent pour les sprites)
+    pub couleur_fond: Option<String>,
+    pub qualite: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PositionImage {
+    pub chemin: String,
+    /// Nom dérivé du fichier, utilisé pour les classes CSS des sprites
+    pub nom: String,
+    pub x: u32,
+    pub y: u32,
+    pub largeur: u32,
+    pub hauteur: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatAssemblage {
+    pub succes: bool,
+    pub fichier_sortie: String,
+    pub format_sortie: String,
+    pub largeur: u32,
+    pub hauteur: u32,
+    pub taille_octets: u64,
+    /// Emplacement de chaque image sur le canevas
+    pub positions: Vec<PositionImage>,
+    /// Fichiers de coordonnées écrits à côté d'une planche de sprites
+    pub fichier_json: Option<String>,
+    pub fichier_css: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsComparaison {
+    /// Image des différences à écrire (aucune si absent)
+    pub chemin_diff: Option<String>,
+    /// Écart toléré par canal avant de compter un pixel comme différent (0 par défaut)
+    pub tolerance: Option<u8>,
+    /// Si les tailles diffèrent : "redimensionner" la seconde image (par défaut)
+    /// ou "recadrer" les deux sur leur zone commune en haut à gauche
+    pub alignement: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatComparaison {
+    /// Taille de la zone comparée
+    pub largeur: u32,
+    pub hauteur: u32,
+    pub dimensions_identiques: bool,
+    pub pixels_differents: u64,
+    pub pourcentage_differents: f32,
+    /// Rapport signal/bruit de crête en dB, absent pour des images identiques
+    pub psnr: Option<f64>,
+    /// Similarité structurelle de la luminosité, 1 pour des images identiques
+    pub ssim: f64,
+    /// Plus grand écart rencontré sur un canal
+    pub ecart_maximal: u8,
+    pub fichier_diff: Option<String>,
+    pub message: String,
+}
+
+/// Empreintes d'un fichier, conservées en cache tant qu'il n'est pas modifié
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct EmpreintesImage {
+    taille_octets: u64,
+    modifie_le: u64,
+    largeur: u32,
+    hauteur: u32,
+    format: String,
+    ahash: u64,
+    dhash: u64,
+    phash: u64,
 }
 
 // === Commandes Tauri ===
@@ -85,6 +619,9 @@ pub async fn obtenir_info_image(chemin_fichier: String) -> Result<InfoImage, Str
 
 /**
  * Convertit une image vers un autre format
+ * 
+ * Un GIF ou un WebP animé converti en GIF ou en WebP garde toutes ses images ;
+ * vers un format fixe, seule la première est convertie et le message le signale.
  */
 #[tauri::command]
 pub async fn convertir_image(
@@ -107,82 +644,687 @@ pub async fn convertir_image(
 }
 
 /**
- * Obtient les formats d'images supportés
+ * Convertit plusieurs images en parallèle avec les mêmes options
  */
 #[tauri::command]
-pub async fn obtenir_formats_supportes() -> Result<Vec<String>, String> {
-    Ok(vec![
-        "JPEG".to_string(),
-        "PNG".to_string(),
-        "WebP".to_string(),
-        "BMP".to_string(),
-        "TIFF".to_string(),
-        "GIF".to_string(),
-    ])
+pub async fn convertir_images_lot(options_lot: OptionsConversionLot) -> Result<ResultatConversionLot, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("🔄 Conversion par lot de {} entrée(s) vers {}", options_lot.entrees.len(), options_lot.dossier_sortie);
+    
+    if let Err(e) = format_depuis_nom(&options_lot.options.format_sortie) {
+        error!("❌ {}", e);
+        return Err(e.to_string());
+    }
+    
+    // Sans {nom}, deux images de même taille recevraient le même fichier de sortie
+    if !options_lot.modele_nom.contains("{nom}") {
+        error!("❌ Modèle de nom sans {{nom}} : {}", options_lot.modele_nom);
+        return Err("Le modèle de nom doit contenir {nom}".to_string());
+    }
+    
+    let fichiers = lister_images_lot(&options_lot.entrees, options_lot.recursif).await.map_err(|e| {
+        error!("❌ Erreur lors du parcours des entrées : {}", e);
+        format!("Impossible de lister les images : {}", e)
+    })?;
+    
+    let semaphore = Arc::new(Semaphore::new(options_lot.concurrence_max.unwrap_or(4).max(1)));
+    let options_lot = Arc::new(options_lot);
+    let mut taches = Vec::with_capacity(fichiers.len());
+    
+    for fichier in fichiers {
+        let semaphore = Arc::clone(&semaphore);
+        let options_lot = Arc::clone(&options_lot);
+        
+        taches.push(tokio::spawn(async move {
+            let _permis = semaphore.acquire_owned().await;
+            let chemin = fichier.source.to_string_lossy().to_string();
+            tokio::task::spawn_blocking(move || convertir_fichier_lot(&fichier, &options_lot))
+                .await
+                .unwrap_or_else(|e| IssueConversionLot::Echec(ResultatConversion {
+                    succes: false,
+                    fichier_origine: chemin,
+                    fichier_sortie: String::new(),
+                    format_origine: String::new(),
+                    format_sortie: String::new(),
+                    taille_avant: 0,
+                    taille_apres: 0,
+                    reduction_pourcent: 0.0,
+                    message: format!("Tâche interrompue : {}", e),
+                    qualite_utilisee: None,
+                    largeur_sortie: 0,
+                    hauteur_sortie: 0,
+                }))
+        }));
+    }
+    
+    let mut bilan = ResultatConversionLot::default();
+    
+    for tache in taches {
+        cumuler_issue_lot(&mut bilan, tache.await.map_err(|e| format!("Tâche interrompue : {}", e))?);
+    }
+    
+    bilan.temps_total_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!(
+        "✅ Lot terminé : {} converti(s), {} ignoré(s), {} échec(s), {} → {}",
+        bilan.fichiers_convertis,
+        bilan.fichiers_ignores,
+        bilan.fichiers_en_echec,
+        formater_taille_fichier(bilan.taille_totale_avant),
+        formater_taille_fichier(bilan.taille_totale_apres)
+    );
+    
+    Ok(bilan)
 }
 
 /**
- * Supprime le fond d'une image et l'exporte en PNG transparent
+ * Analyse les couleurs d'une image : palette dominante, histogrammes et luminosité
  */
 #[tauri::command]
-pub async fn supprimer_fond_image(
-    chemin_entree: String,
+pub async fn analyser_couleurs_image(
+    chemin_fichier: String,
+    nombre_couleurs: Option<u8>
+) -> Result<AnalyseCouleurs, String> {
+    info!("🎨 Analyse des couleurs : {}", chemin_fichier);
+    
+    let analyse = async {
+        if !verifier_fichier_existe(&chemin_fichier).await {
+            return Err(ErreurApplication::Validation {
+                message: format!("Le fichier n'existe pas : {}", chemin_fichier),
+            });
+        }
+        
+        let img = charger_image(Path::new(&chemin_fichier))?.to_rgba8();
+        Ok(analyser_couleurs(&img, nombre_couleurs.unwrap_or(8).clamp(1, 32) as usize))
+    };
+    
+    match analyse.await {
+        Ok(resultat) => {
+            info!("✅ {} couleur(s) dominante(s) trouvée(s)", resultat.palette.len());
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur d'analyse des couleurs : {}", e);
+            Err(format!("Échec de l'analyse des couleurs : {}", e))
+        }
+    }
+}
+
+/**
+ * Assemble plusieurs images sur un même canevas
+ * 
+ * Planche contact, collage ou planche de sprites ; le format est déduit de
+ * l'extension du chemin de sortie.
+ */
+#[tauri::command]
+pub async fn assembler_images(
+    chemins: Vec<String>,
     chemin_sortie: String,
-    options: OptionsSuppressionFond
-) -> Result<ResultatConversion, String> {
-    info!("🎨 Suppression de fond : {} -> {}", chemin_entree, chemin_sortie);
+    options: OptionsAssemblage
+) -> Result<ResultatAssemblage, String> {
+    info!("🧩 Assemblage de {} image(s) : {}", chemins.len(), chemin_sortie);
     
-    match supprimer_fond_interne(&chemin_entree, &chemin_sortie, &options).await {
+    match assembler_images_interne(&chemins, &chemin_sortie, &options).await {
         Ok(resultat) => {
-            info!("✅ Suppression de fond réussie");
+            info!("✅ {}", resultat.message);
             Ok(resultat)
         }
         Err(e) => {
-            error!("❌ Erreur de suppression de fond : {}", e);
-            Err(format!("Échec de la suppression de fond : {}", e))
+            error!("❌ Erreur d'assemblage : {}", e);
+            Err(format!("Échec de l'assemblage des images : {}", e))
         }
     }
 }
 
-// === Fonctions internes ===
+/**
+ * Compare deux images pixel à pixel
+ * 
+ * Donne la part de pixels modifiés, le PSNR et le SSIM, et peut écrire une image
+ * où les pixels modifiés ressortent en rouge sur la première image estompée.
+ */
+#[tauri::command]
+pub async fn comparer_images(
+    chemin_a: String,
+    chemin_b: String,
+    options: OptionsComparaison
+) -> Result<ResultatComparaison, String> {
+    info!("🔬 Comparaison : {} / {}", chemin_a, chemin_b);
+    
+    match comparer_images_interne(&chemin_a, &chemin_b, &options).await {
+        Ok(resultat) => {
+            info!("✅ {}", resultat.message);
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de comparaison : {}", e);
+            Err(format!("Échec de la comparaison des images : {}", e))
+        }
+    }
+}
 
-async fn analyser_image(chemin: &str) -> ResultatApplication<InfoImage> {
-    let path = Path::new(chemin);
+/**
+ * Recherche les copies et quasi-copies parmi des images
+ * 
+ * Compare des empreintes perceptuelles : une image réenregistrée, recompressée
+ * ou redimensionnée reste à faible distance de Hamming de l'originale.
+ */
+#[tauri::command]
+pub async fn rechercher_doublons_images(
+    entrees: Vec<String>,
+    options: OptionsDoublons
+) -> Result<ResultatDoublons, String> {
+    info!("🔍 Recherche de doublons dans {} entrée(s)", entrees.len());
     
-    // Vérifier que le fichier existe
-    if !path.exists() {
-        return Err(ErreurApplication::Donnees {
-            message: "Le fichier image n'existe pas".to_string(),
-        });
+    match rechercher_doublons_interne(&entrees, &options).await {
+        Ok(resultat) => {
+            info!(
+                "✅ {} groupe(s) de doublons parmi {} image(s) ({} depuis le cache)",
+                resultat.groupes.len(),
+                resultat.images_analysees,
+                resultat.images_en_cache
+            );
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de recherche de doublons : {}", e);
+            Err(format!("Échec de la recherche de doublons : {}", e))
+        }
     }
+}
+
+/**
+ * Applique l'orientation EXIF aux pixels de toutes les images données
+ * 
+ * Les images redressées sont écrites dans `dossier_sortie` (sous-dossiers
+ * reproduits) ; les originaux ne sont remplacés que si `ecraser_originaux`
+ * est activé, et jamais sans dossier de sortie ni cet accord, y compris
+ * quand le dossier de sortie retombe sur les originaux. Les images
+ * déjà droites sont ignorées. Pour les JPEG, le bloc EXIF d'origine est
+ * conservé avec l'orientation remise à 1.
+ */
+#[tauri::command]
+pub async fn redresser_images_lot(
+    entrees: Vec<String>,
+    recursif: bool,
+    qualite: Option<u8>,
+    dossier_sortie: Option<String>,
+    ecraser_originaux: Option<bool>
+) -> Result<ResultatConversionLot, String> {
+    let debut_traitement = std::time::Instant::now();
     
-    // Obtenir les métadonnées du fichier
-    let metadata = tokio::fs::metadata(path).await.map_err(|e| {
-        ErreurApplication::Systeme {
-            message: format!("Impossible de lire les métadonnées : {}", e),
+    info!("🔃 Redressement automatique de {} entrée(s)", entrees.len());
+    
+    // Le réencodage JPEG est avec perte : remplacer les originaux doit être demandé
+    let ecraser = ecraser_originaux.unwrap_or(false);
+    let dossier_sortie = match (dossier_sortie, ecraser) {
+        (Some(dossier), _) => Some(PathBuf::from(dossier)),
+        (None, true) => None,
+        (None, false) => {
+            error!("❌ Ni dossier de sortie ni remplacement des originaux");
+            return Err("Indiquez un dossier de sortie, ou confirmez le remplacement des originaux".to_string());
         }
+    };
+    
+    let fichiers = lister_images_lot(&entrees, recursif).await.map_err(|e| {
+        error!("❌ Erreur lors du parcours des entrées : {}", e);
+        format!("Impossible de lister les images : {}", e)
     })?;
     
-    // Charger l'image
-    let img = image::open(path).map_err(|e| {
-        ErreurApplication::Donnees {
-            message: format!("Impossible de charger l'image : {}", e),
+    // Un dossier de sortie confondu avec la source (ou l'un de ses sous-dossiers) remplacerait les originaux
+    if let Some(dossier) = dossier_sortie.as_deref().filter(|_| !ecraser) {
+        if let Some(fichier) = fichiers.iter().find(|fichier| meme_fichier(&fichier.source, &chemin_redressement(fichier, Some(dossier)))) {
+            error!("❌ Le dossier de sortie recouvre les originaux : {}", fichier.source.display());
+            return Err(format!(
+                "Le dossier de sortie contient les originaux ({} serait remplacé) : choisissez un autre dossier ou confirmez le remplacement des originaux",
+                fichier.source.display()
+            ));
         }
-    })?;
+    }
     
-    // Détecter le format
-    let format = detecter_format_image(path)?;
+    let mut bilan = tokio::task::spawn_blocking(move || {
+        let mut bilan = ResultatConversionLot::default();
+        for fichier in &fichiers {
+            cumuler_issue_lot(&mut bilan, redresser_fichier(fichier, dossier_sortie.as_deref(), qualite.unwrap_or(92)));
+        }
+        bilan
+    })
+    .await
+    .map_err(|e| format!("Tâche interrompue : {}", e))?;
     
-    Ok(InfoImage {
-        nom: path.file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
+    bilan.temps_total_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!("✅ {} image(s) redressée(s), {} déjà droite(s)", bilan.fichiers_convertis, bilan.fichiers_ignores);
+    
+    Ok(bilan)
+}
+
+/**
+ * Applique une suite de transformations à une image
+ * 
+ * Le format de sortie est déduit de l'extension du chemin de sortie.
+ */
+#[tauri::command]
+pub async fn transformer_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    etapes: Vec<EtapeTransformation>,
+    qualite: Option<u8>
+) -> Result<ResultatConversion, String> {
+    info!("✂️ Transformation ({} étape(s)) : {} -> {}", etapes.len(), chemin_entree, chemin_sortie);
+    
+    let format_sortie = ImageFormat::from_path(&chemin_sortie)
+        .map(|format| nom_format(format).to_string())
+        .map_err(|_| format!("Format de sortie non reconnu : {}", chemin_sortie))?;
+    
+    let options = OptionsConversion {
+        format_sortie,
+        qualite,
+        largeur_max: None,
+        hauteur_max: None,
+        conserver_ratio: true,
+        transformations: Some(etapes),
+        reglages: None,
+        taille_cible_octets: None,
+        filigrane: None,
+    };
+    
+    match convertir_image_interne(&chemin_entree, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Transformation réussie");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de transformation : {}", e);
+            Err(format!("Échec de la transformation : {}", e))
+        }
+    }
+}
+
+/**
+ * Applique des réglages de couleur et des filtres à une image
+ * 
+ * Le format de sortie est déduit de l'extension du chemin de sortie.
+ */
+#[tauri::command]
+pub async fn ajuster_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    reglages: Vec<ReglageImage>,
+    qualite: Option<u8>
+) -> Result<ResultatConversion, String> {
+    info!("🎛️ Ajustement ({} réglage(s)) : {} -> {}", reglages.len(), chemin_entree, chemin_sortie);
+    
+    let format_sortie = ImageFormat::from_path(&chemin_sortie)
+        .map(|format| nom_format(format).to_string())
+        .map_err(|_| format!("Format de sortie non reconnu : {}", chemin_sortie))?;
+    
+    let options = OptionsConversion {
+        format_sortie,
+        qualite,
+        largeur_max: None,
+        hauteur_max: None,
+        conserver_ratio: true,
+        transformations: None,
+        reglages: Some(reglages),
+        taille_cible_octets: None,
+        filigrane: None,
+    };
+    
+    match convertir_image_interne(&chemin_entree, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Ajustement réussi");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur d'ajustement : {}", e);
+            Err(format!("Échec de l'ajustement : {}", e))
+        }
+    }
+}
+
+/**
+ * Obtient les formats de sortie supportés
+ * 
+ * L'AVIF n'est proposé qu'en sortie : aucun décodeur AVIF n'est intégré.
+ */
+#[tauri::command]
+pub async fn obtenir_formats_supportes() -> Result<Vec<String>, String> {
+    Ok(vec![
+        "JPEG".to_string(),
+        "PNG".to_string(),
+        "WebP".to_string(),
+        "AVIF".to_string(),
+        "BMP".to_string(),
+        "TIFF".to_string(),
+        "GIF".to_string(),
+        "ICO".to_string(),
+    ])
+}
+
+/**
+ * Génère le jeu d'icônes d'un site ou d'une application à partir d'une image carrée
+ * 
+ * Produit favicon.ico (16, 32, 48 et 256 px), les PNG du manifeste web,
+ * les icônes Apple et un manifest.json. Une source non carrée est recadrée au centre.
+ */
+#[tauri::command]
+pub async fn generer_icones(
+    chemin_source: String,
+    dossier_sortie: String,
+    options: OptionsIcones
+) -> Result<ResultatIcones, String> {
+    info!("🧩 Génération des icônes : {} -> {}", chemin_source, dossier_sortie);
+    
+    match generer_icones_interne(&chemin_source, &dossier_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ {} fichier(s) d'icônes générés", resultat.fichiers.len());
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de génération des icônes : {}", e);
+            Err(format!("Échec de la génération des icônes : {}", e))
+        }
+    }
+}
+
+/**
+ * Corrige l'extension des images dont le contenu ne correspond pas à l'extension
+ * 
+ * Avec `appliquer` à `false`, les renommages sont seulement simulés pour prévisualisation.
+ * Un fichier n'est jamais renommé vers un chemin déjà existant.
+ */
+#[tauri::command]
+pub async fn corriger_extensions_images(
+    chemins: Vec<String>,
+    appliquer: bool
+) -> Result<Vec<CorrectionExtension>, String> {
+    info!("🏷️ Correction des extensions de {} image(s) (application : {})", chemins.len(), appliquer);
+    
+    let mut corrections = Vec::with_capacity(chemins.len());
+    
+    for chemin in chemins {
+        let correction = corriger_extension_interne(&chemin, appliquer).await;
+        
+        if let Some(erreur) = &correction.erreur {
+            error!("❌ {} : {}", chemin, erreur);
+        }
+        
+        corrections.push(correction);
+    }
+    
+    let renommees = corrections.iter().filter(|correction| correction.renomme).count();
+    info!("✅ {} extension(s) corrigée(s)", renommees);
+    
+    Ok(corrections)
+}
+
+/**
+ * Supprime les métadonnées d'une image (JPEG, PNG, WebP ou TIFF)
+ * 
+ * Modes :
+ * - "tout" : EXIF, XMP, IPTC, commentaires et aperçus intégrés ; un EXIF
+ *   minimal ne portant que l'orientation est conservé
+ * - "gps_et_personnel" : position GPS, auteur, numéros de série et blocs XMP/IPTC,
+ *   en conservant les réglages de prise de vue et l'orientation
+ * 
+ * Le profil ICC est toujours conservé. Les pixels ne sont pas réencodés, sauf
+ * pour un TIFF en mode "tout" : l'image est alors réécrite par `image`, qui
+ * ne recopie pas le profil ICC ; les TIFF multipages sont refusés dans ce mode.
+ */
+#[tauri::command]
+pub async fn nettoyer_metadonnees_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    mode: String
+) -> Result<ResultatNettoyageMetadonnees, String> {
+    info!("🧹 Nettoyage des métadonnées ({}) : {} -> {}", mode, chemin_entree, chemin_sortie);
+    
+    match nettoyer_metadonnees_interne(&chemin_entree, &chemin_sortie, &mode).await {
+        Ok(resultat) => {
+            info!("✅ {} élément(s) de métadonnées supprimé(s)", resultat.elements_supprimes.len());
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de nettoyage des métadonnées : {}", e);
+            Err(format!("Échec du nettoyage des métadonnées : {}", e))
+        }
+    }
+}
+
+/**
+ * Appose un filigrane (texte ou logo) sur une image
+ * 
+ * Le format de sortie est déduit de l'extension du chemin de sortie. Pour un lot,
+ * le filigrane se passe dans les options de `convertir_images_lot`.
+ */
+#[tauri::command]
+pub async fn ajouter_filigrane_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    filigrane: OptionsFiligrane,
+    qualite: Option<u8>
+) -> Result<ResultatConversion, String> {
+    info!("💧 Filigrane : {} -> {}", chemin_entree, chemin_sortie);
+    
+    let format_sortie = ImageFormat::from_path(&chemin_sortie)
+        .map(|format| nom_format(format).to_string())
+        .map_err(|_| format!("Format de sortie non reconnu : {}", chemin_sortie))?;
+    
+    let options = OptionsConversion {
+        format_sortie,
+        qualite,
+        largeur_max: None,
+        hauteur_max: None,
+        conserver_ratio: true,
+        transformations: None,
+        reglages: None,
+        taille_cible_octets: None,
+        filigrane: Some(filigrane),
+    };
+    
+    match convertir_image_interne(&chemin_entree, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Filigrane appliqué");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de filigrane : {}", e);
+            Err(format!("Échec du filigrane : {}", e))
+        }
+    }
+}
+
+/**
+ * Extrait chaque image d'un GIF ou d'un WebP animé en PNG
+ * 
+ * Les fichiers sont nommés `{nom}_0001.png`, `{nom}_0002.png`…
+ */
+#[tauri::command]
+pub async fn extraire_trames_animation(
+    chemin_entree: String,
+    dossier_sortie: String
+) -> Result<ResultatTrames, String> {
+    info!("🎞️ Extraction des images : {} -> {}", chemin_entree, dossier_sortie);
+    
+    match extraire_trames_interne(&chemin_entree, &dossier_sortie).await {
+        Ok(resultat) => {
+            info!("✅ {} image(s) extraite(s)", resultat.fichiers.len());
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur d'extraction des images : {}", e);
+            Err(format!("Échec de l'extraction des images : {}", e))
+        }
+    }
+}
+
+/**
+ * Crée un GIF ou un WebP animé à partir d'une suite d'images
+ * 
+ * Le format est déduit de l'extension du chemin de sortie. Le canevas prend
+ * la taille de la première image ; les suivantes y sont ajustées et centrées.
+ */
+#[tauri::command]
+pub async fn creer_animation(
+    trames: Vec<TrameAnimation>,
+    chemin_sortie: String,
+    options: OptionsAnimation
+) -> Result<ResultatConversion, String> {
+    info!("🎞️ Création d'une animation de {} image(s) : {}", trames.len(), chemin_sortie);
+    
+    match creer_animation_interne(&trames, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Animation créée");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de création d'animation : {}", e);
+            Err(format!("Échec de la création de l'animation : {}", e))
+        }
+    }
+}
+
+/**
+ * Convertit une animation entre GIF et WebP en conservant images et durées
+ */
+#[tauri::command]
+pub async fn convertir_animation(
+    chemin_entree: String,
+    chemin_sortie: String,
+    options: OptionsAnimation
+) -> Result<ResultatConversion, String> {
+    info!("🎞️ Conversion d'animation : {} -> {}", chemin_entree, chemin_sortie);
+    
+    let conversion = async {
+        if !verifier_fichier_existe(&chemin_entree).await {
+            return Err(ErreurApplication::Validation {
+                message: format!("Le fichier source n'existe pas : {}", chemin_entree),
+            });
+        }
+        
+        let path_entree = Path::new(&chemin_entree);
+        let trames = lire_trames(path_entree)?;
+        let taille_avant = tokio::fs::metadata(path_entree).await.map(|metadata| metadata.len()).unwrap_or(0);
+        
+        ecrire_animation(&trames, &chemin_sortie, &options, &chemin_entree, detecter_format_image(path_entree)?, taille_avant).await
+    };
+    
+    match conversion.await {
+        Ok(resultat) => {
+            info!("✅ Animation convertie");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de conversion d'animation : {}", e);
+            Err(format!("Échec de la conversion de l'animation : {}", e))
+        }
+    }
+}
+
+/**
+ * Optimise une image PNG ou JPEG pour le web
+ * 
+ * - PNG : recompression sans perte et réduction de palette, avec
+ *   quantification optionnelle à 256 couleurs
+ * - JPEG : transcodification sans perte des coefficients DCT avec des tables
+ *   de Huffman optimales, progressive au besoin ; le réencodage, avec perte,
+ *   n'a lieu que sur demande (`reencoder`)
+ * 
+ * Le fichier d'origine est recopié tel quel si l'optimisation ne le réduit pas.
+ */
+#[tauri::command]
+pub async fn optimiser_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    options: OptionsOptimisation
+) -> Result<ResultatConversion, String> {
+    info!("🗜️ Optimisation de l'image : {} -> {}", chemin_entree, chemin_sortie);
+    
+    match optimiser_image_interne(&chemin_entree, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Optimisation terminée : -{:.1}% de taille", resultat.reduction_pourcent);
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur d'optimisation : {}", e);
+            Err(format!("Échec de l'optimisation : {}", e))
+        }
+    }
+}
+
+/**
+ * Supprime le fond d'une image et l'exporte avec transparence (PNG par défaut),
+ * ou posée sur un fond de composition
+ */
+#[tauri::command]
+pub async fn supprimer_fond_image(
+    chemin_entree: String,
+    chemin_sortie: String,
+    options: OptionsSuppressionFond
+) -> Result<ResultatConversion, String> {
+    info!("🎨 Suppression de fond : {} -> {}", chemin_entree, chemin_sortie);
+    
+    match supprimer_fond_interne(&chemin_entree, &chemin_sortie, &options).await {
+        Ok(resultat) => {
+            info!("✅ Suppression de fond réussie");
+            Ok(resultat)
+        }
+        Err(e) => {
+            error!("❌ Erreur de suppression de fond : {}", e);
+            Err(format!("Échec de la suppression de fond : {}", e))
+        }
+    }
+}
+
+// === Fonctions internes ===
+
+async fn analyser_image(chemin: &str) -> ResultatApplication<InfoImage> {
+    let path = Path::new(chemin);
+    
+    // Vérifier que le fichier existe
+    if !path.exists() {
+        return Err(ErreurApplication::Donnees {
+            message: "Le fichier image n'existe pas".to_string(),
+        });
+    }
+    
+    // Obtenir les métadonnées du fichier
+    let metadata = tokio::fs::metadata(path).await.map_err(|e| {
+        ErreurApplication::Systeme {
+            message: format!("Impossible de lire les métadonnées : {}", e),
+        }
+    })?;
+    
+    // Charger l'image
+    let img = charger_image(path)?;
+    
+    // Détecter le format réel et le comparer à celui annoncé par l'extension
+    let format_reel = detecter_format_fichier(path)?;
+    let format_extension = ImageFormat::from_path(path).ok();
+    
+    // Une structure d'animation illisible n'empêche pas l'analyse de la première image
+    let delais_trames_ms = std::fs::read(path)
+        .ok()
+        .and_then(|donnees| lire_delais_trames(&donnees, format_reel))
+        .unwrap_or_else(|| vec![0]);
+    
+    Ok(InfoImage {
+        nom: path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
             .to_string(),
         chemin: chemin.to_string(),
-        format: format.to_string(),
+        format: nom_format(format_reel).to_string(),
         largeur: img.width(),
         hauteur: img.height(),
         taille_octets: metadata.len(),
+        format_extension: format_extension.map(|format| nom_format(format).to_string()),
+        extension_incorrecte: format_extension != Some(format_reel),
+        extension_suggeree: format_reel.extensions_str()[0].to_string(),
+        metadonnees: lire_metadonnees_image(path, format_reel),
+        nombre_trames: delais_trames_ms.len() as u32,
+        delais_trames_ms: if delais_trames_ms.len() > 1 { delais_trames_ms } else { Vec::new() },
     })
 }
 
@@ -217,46 +1359,64 @@ async fn convertir_image_interne(
         })?
         .len();
     
+    // Une animation n'est pas réduite en silence à sa première image : vers GIF
+    // ou WebP, elle est convertie en entier, sinon le résultat le signale
+    let nombre_trames = compter_trames(path_entree);
+    if nombre_trames > 1 && matches!(format_depuis_nom(&options.format_sortie), Ok(ImageFormat::Gif | ImageFormat::WebP)) {
+        let retouchee = options.transformations.as_deref().is_some_and(|etapes| !etapes.is_empty())
+            || options.reglages.as_deref().is_some_and(|reglages| !reglages.is_empty())
+            || options.largeur_max.is_some()
+            || options.hauteur_max.is_some()
+            || options.taille_cible_octets.is_some()
+            || options.filigrane.is_some();
+        if retouchee {
+            progression.terminer_avec_erreur("Animation non prise en charge");
+            return Err(ErreurApplication::Validation {
+                message: format!(
+                    "Animation de {} images : retouches et redimensionnement ne s'appliquent qu'aux images fixes",
+                    nombre_trames
+                ),
+            });
+        }
+        
+        progression.mettre_a_jour(40, Some("Conversion de l'animation...")).await;
+        let options_animation = OptionsAnimation { qualite: options.qualite, boucles: None };
+        let conversion = async {
+            let trames = lire_trames(path_entree)?;
+            ecrire_animation(&trames, chemin_sortie, &options_animation, chemin_entree, detecter_format_image(path_entree)?, taille_avant).await
+        };
+        return match conversion.await {
+            Ok(resultat) => {
+                progression.terminer_avec_succes(&resultat.message);
+                Ok(resultat)
+            }
+            Err(e) => {
+                progression.terminer_avec_erreur("Erreur de conversion de l'animation");
+                Err(e)
+            }
+        };
+    }
+    
     // Étape 2: Chargement de l'image (40%)
     progression.mettre_a_jour(40, Some("Chargement de l'image...")).await;
     
-    let mut img = image::open(path_entree).map_err(|e| {
+    let img = charger_image(path_entree).inspect_err(|_| {
         progression.terminer_avec_erreur("Impossible de charger l'image");
-        ErreurApplication::Donnees {
-            message: format!("Impossible de charger l'image : {}", e),
-        }
     })?;
     
-    // Étape 3: Redimensionnement si nécessaire (60%)
-    if let (Some(largeur_max), Some(hauteur_max)) = (options.largeur_max, options.hauteur_max) {
-        progression.mettre_a_jour(60, Some("Redimensionnement...")).await;
-        
-        if options.conserver_ratio {
-            img = img.thumbnail(largeur_max, hauteur_max);
-        } else {
-            img = img.resize_exact(largeur_max, hauteur_max, image::imageops::FilterType::Lanczos3);
-        }
-    } else {
-        progression.mettre_a_jour(60, Some("Pas de redimensionnement nécessaire")).await;
-    }
+    // Étape 3: Transformations, redimensionnement, réglages et filigrane (60%),
+    // dans le même ordre que la conversion par lots
+    progression.mettre_a_jour(60, Some("Traitement de l'image...")).await;
+    let img = traiter_image(img, options).inspect_err(|_| {
+        progression.terminer_avec_erreur("Traitement impossible");
+    })?;
     
     // Étape 4: Préparation de la conversion (70%)
     progression.mettre_a_jour(70, Some("Préparation de la conversion...")).await;
     
-    let format_sortie = match options.format_sortie.to_lowercase().as_str() {
-        "jpeg" | "jpg" => ImageFormat::Jpeg,
-        "png" => ImageFormat::Png,
-        "webp" => ImageFormat::WebP,
-        "bmp" => ImageFormat::Bmp,
-        "tiff" => ImageFormat::Tiff,
-        "gif" => ImageFormat::Gif,
-        _ => {
-            progression.terminer_avec_erreur("Format non supporté");
-            return Err(ErreurApplication::Validation {
-                message: format!("Format non supporté : {}", options.format_sortie),
-            });
-        }
-    };
+    let format_sortie = format_depuis_nom(&options.format_sortie).inspect_err(|_| {
+        progression.terminer_avec_erreur("Format non supporté");
+    })?;
     
     // Créer le dossier de sortie si nécessaire avec nos utilitaires
     if let Some(parent) = path_sortie.parent() {
@@ -272,33 +1432,9 @@ async fn convertir_image_interne(
     // Étape 5: Sauvegarde de l'image (90%)
     progression.mettre_a_jour(90, Some("Sauvegarde...")).await;
     
-    match format_sortie {
-        ImageFormat::Jpeg => {
-            let qualite = options.qualite.unwrap_or(85);
-            let mut output = std::fs::File::create(path_sortie).map_err(|e| {
-                progression.terminer_avec_erreur("Erreur de création du fichier");
-                ErreurApplication::Systeme {
-                    message: format!("Impossible de créer le fichier : {}", e),
-                }
-            })?;
-            
-            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, qualite);
-            img.write_with_encoder(encoder).map_err(|e| {
-                progression.terminer_avec_erreur("Erreur d'encodage JPEG");
-                ErreurApplication::Systeme {
-                    message: format!("Erreur d'encodage JPEG : {}", e),
-                }
-            })?;
-        }
-        _ => {
-            img.save_with_format(path_sortie, format_sortie).map_err(|e| {
-                progression.terminer_avec_erreur("Erreur de sauvegarde");
-                ErreurApplication::Systeme {
-                    message: format!("Erreur de sauvegarde : {}", e),
-                }
-            })?;
-        }
-    }
+    let encodage = sauvegarder_image(&img, path_sortie, format_sortie, options).inspect_err(|_| {
+        progression.terminer_avec_erreur("Erreur de sauvegarde");
+    })?;
     
     // Étape 6: Finalisation (100%)
     progression.mettre_a_jour(100, Some("Finalisation...")).await;
@@ -340,14 +1476,523 @@ async fn convertir_image_interne(
         taille_apres,
         reduction_pourcent,
         message: format!(
-            "Conversion réussie : {} -> {} ({:.1}% de réduction)",
+            "Conversion réussie : {} -> {} ({:.1}% de réduction){}",
             format_origine,
             options.format_sortie,
-            reduction_pourcent
+            reduction_pourcent,
+            avertissement_animation(nombre_trames)
         ),
+        qualite_utilisee: encodage.qualite,
+        largeur_sortie: encodage.largeur,
+        hauteur_sortie: encodage.hauteur,
     })
 }
 
+fn format_depuis_nom(nom: &str) -> ResultatApplication<ImageFormat> {
+    match nom.to_lowercase().as_str() {
+        "jpeg" | "jpg" => Ok(ImageFormat::Jpeg),
+        "png" => Ok(ImageFormat::Png),
+        "webp" => Ok(ImageFormat::WebP),
+        "avif" => Ok(ImageFormat::Avif),
+        "bmp" => Ok(ImageFormat::Bmp),
+        "tiff" => Ok(ImageFormat::Tiff),
+        "gif" => Ok(ImageFormat::Gif),
+        "ico" => Ok(ImageFormat::Ico),
+        _ => Err(ErreurApplication::Validation {
+            message: format!("Format non supporté : {}", nom),
+        }),
+    }
+}
+
+/**
+ * Applique à une image chargée l'ensemble des traitements d'une conversion
+ */
+fn traiter_image(img: image::DynamicImage, options: &OptionsConversion) -> ResultatApplication<image::DynamicImage> {
+    let img = appliquer_transformations(img, options.transformations.as_deref().unwrap_or_default())?;
+    let img = redimensionner_image(img, options);
+    let img = appliquer_reglages(img, options.reglages.as_deref().unwrap_or_default());
+    
+    match &options.filigrane {
+        Some(filigrane) => appliquer_filigrane(img, filigrane),
+        None => Ok(img),
+    }
+}
+
+/// Police intégrée au binaire : le filigrane texte fonctionne sans polices système
+const POLICE_FILIGRANE: &[u8] = include_bytes!("../../polices/DejaVuSans-Bold.ttf");
+
+fn appliquer_filigrane(img: image::DynamicImage, filigrane: &OptionsFiligrane) -> ResultatApplication<image::DynamicImage> {
+    let mut resultat = img.to_rgba8();
+    let (largeur, hauteur) = resultat.dimensions();
+    let largeur_motif = ((largeur as f32 * filigrane.echelle.clamp(0.01, 1.0)).round() as u32).max(1);
+    
+    let mut motif = match &filigrane.contenu {
+        ContenuFiligrane::Texte { texte, couleur } => rendre_texte(texte, largeur_motif, parser_couleur_rgba(couleur)?)?,
+        ContenuFiligrane::Logo { chemin } => {
+            if !Path::new(chemin).exists() {
+                return Err(ErreurApplication::Validation {
+                    message: format!("Le logo n'existe pas : {}", chemin),
+                });
+            }
+            let logo = charger_image(Path::new(chemin))?;
+            let hauteur_logo = ((logo.height() as u64 * largeur_motif as u64 / logo.width().max(1) as u64) as u32).max(1);
+            logo.resize_exact(largeur_motif, hauteur_logo, image::imageops::FilterType::Lanczos3).to_rgba8()
+        }
+    };
+    
+    if filigrane.rotation_degres.rem_euclid(360.0) != 0.0 {
+        motif = pivoter_image(&motif, filigrane.rotation_degres, [0, 0, 0, 0]);
+    }
+    
+    let opacite = filigrane.opacite.clamp(0.0, 1.0);
+    for pixel in motif.pixels_mut() {
+        pixel.0[3] = (pixel.0[3] as f32 * opacite).round() as u8;
+    }
+    
+    let (largeur_motif, hauteur_motif) = motif.dimensions();
+    
+    if filigrane.position == "mosaique" {
+        let pas_x = (largeur_motif + filigrane.marge) as usize;
+        let pas_y = (hauteur_motif + filigrane.marge) as usize;
+        for y in (0..hauteur).step_by(pas_y.max(1)) {
+            for x in (0..largeur).step_by(pas_x.max(1)) {
+                superposer(&mut resultat, &motif, x as i64, y as i64);
+            }
+        }
+    } else {
+        let (facteur_x, facteur_y) = facteurs_ancrage(&filigrane.position)?;
+        let marge = filigrane.marge as i64;
+        let x = marge + ((largeur as i64 - largeur_motif as i64 - 2 * marge) as f32 * facteur_x).round() as i64;
+        let y = marge + ((hauteur as i64 - hauteur_motif as i64 - 2 * marge) as f32 * facteur_y).round() as i64;
+        superposer(&mut resultat, &motif, x, y);
+    }
+    
+    Ok(image::DynamicImage::ImageRgba8(resultat))
+}
+
+/**
+ * Rend une ligne de texte sur fond transparent, à la largeur demandée
+ */
+fn rendre_texte(texte: &str, largeur: u32, couleur: [u8; 4]) -> ResultatApplication<image::RgbaImage> {
+    use ab_glyph::{Font, ScaleFont};
+    
+    if texte.trim().is_empty() {
+        return Err(ErreurApplication::Validation {
+            message: "Le texte du filigrane est vide".to_string(),
+        });
+    }
+    
+    let police = ab_glyph::FontRef::try_from_slice(POLICE_FILIGRANE).map_err(|e| ErreurApplication::Systeme {
+        message: format!("Police de filigrane invalide : {}", e),
+    })?;
+    
+    // Largeur mesurée à une taille de référence, puis taille ajustée à la largeur voulue
+    let mesurer = |taille: f32| {
+        let police_mise_a_echelle = police.as_scaled(taille);
+        let mut precedent = None;
+        let mut avance = 0.0;
+        for caractere in texte.chars() {
+            let glyphe = police_mise_a_echelle.glyph_id(caractere);
+            if let Some(precedent) = precedent {
+                avance += police_mise_a_echelle.kern(precedent, glyphe);
+            }
+            avance += police_mise_a_echelle.h_advance(glyphe);
+            precedent = Some(glyphe);
+        }
+        avance
+    };
+    let taille = 100.0 * largeur as f32 / mesurer(100.0).max(1.0);
+    let police_mise_a_echelle = police.as_scaled(taille);
+    
+    let hauteur = (police_mise_a_echelle.ascent() - police_mise_a_echelle.descent()).ceil().max(1.0) as u32;
+    let mut rendu = image::RgbaImage::new(largeur, hauteur);
+    
+    let mut position_x = 0.0;
+    let mut precedent = None;
+    for caractere in texte.chars() {
+        let identifiant = police_mise_a_echelle.glyph_id(caractere);
+        if let Some(precedent) = precedent {
+            position_x += police_mise_a_echelle.kern(precedent, identifiant);
+        }
+        let glyphe = identifiant.with_scale_and_position(taille, ab_glyph::point(position_x, police_mise_a_echelle.ascent()));
+        position_x += police_mise_a_echelle.h_advance(identifiant);
+        precedent = Some(identifiant);
+        
+        if let Some(contour) = police.outline_glyph(glyphe) {
+            let limites = contour.px_bounds();
+            contour.draw(|x, y, couverture| {
+                let px = limites.min.x as i64 + x as i64;
+                let py = limites.min.y as i64 + y as i64;
+                if px >= 0 && py >= 0 && (px as u32) < largeur && (py as u32) < hauteur {
+                    let pixel = rendu.get_pixel_mut(px as u32, py as u32);
+                    let alpha = (couverture.clamp(0.0, 1.0) * couleur[3] as f32).round() as u8;
+                    if alpha > pixel.0[3] {
+                        *pixel = image::Rgba([couleur[0], couleur[1], couleur[2], alpha]);
+                    }
+                }
+            });
+        }
+    }
+    
+    Ok(rendu)
+}
+
+fn redimensionner_image(img: image::DynamicImage, options: &OptionsConversion) -> image::DynamicImage {
+    match (options.largeur_max, options.hauteur_max) {
+        (Some(largeur_max), Some(hauteur_max)) if options.conserver_ratio => img.thumbnail(largeur_max, hauteur_max),
+        (Some(largeur_max), Some(hauteur_max)) => {
+            img.resize_exact(largeur_max, hauteur_max, image::imageops::FilterType::Lanczos3)
+        }
+        _ => img,
+    }
+}
+
+/// Image encodée en mémoire, prête à être écrite
+struct ImageEncodee {
+    octets: Vec<u8>,
+    qualite: Option<u8>,
+    largeur: u32,
+    hauteur: u32,
+}
+
+/// Qualité en dessous de laquelle la recherche de taille cible préfère réduire les dimensions
+const QUALITE_MINIMALE_CIBLE: u8 = 30;
+
+fn sauvegarder_image(
+    img: &image::DynamicImage,
+    path_sortie: &Path,
+    format_sortie: ImageFormat,
+    options: &OptionsConversion
+) -> ResultatApplication<ImageEncodee> {
+    let encodage = encoder_sortie(img, format_sortie, options)?;
+    
+    std::fs::write(path_sortie, &encodage.octets).map_err(|e| {
+        ErreurApplication::Systeme {
+            message: format!("Impossible d'écrire le fichier : {}", e),
+        }
+    })?;
+    
+    Ok(encodage)
+}
+
+fn qualite_reglable(format: ImageFormat) -> bool {
+    matches!(format, ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Avif)
+}
+
+fn encoder_image(img: &image::DynamicImage, format: ImageFormat, qualite: u8) -> ResultatApplication<Vec<u8>> {
+    let mut octets = Vec::new();
+    
+    let resultat = match format {
+        // Le JPEG ne gère pas la transparence : on passe en RGB
+        ImageFormat::Jpeg => image::DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(&mut octets, qualite)),
+        // L'encodeur WebP d'image est sans perte uniquement : libwebp prend le relais
+        ImageFormat::WebP => return encoder_webp(img, qualite),
+        ImageFormat::Avif => return encoder_avif(img, qualite),
+        _ => img.write_to(&mut std::io::Cursor::new(&mut octets), format),
+    };
+    
+    resultat.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Erreur d'encodage {} : {}", nom_format(format), e),
+    })?;
+    
+    Ok(octets)
+}
+
+/// Largeur et hauteur maximales d'une image WebP
+const DIMENSION_MAX_WEBP: u32 = 16383;
+
+/**
+ * Encode en WebP avec perte (qualité 100 : sans perte)
+ */
+fn encoder_webp(img: &image::DynamicImage, qualite: u8) -> ResultatApplication<Vec<u8>> {
+    let (largeur, hauteur) = (img.width(), img.height());
+    if largeur > DIMENSION_MAX_WEBP || hauteur > DIMENSION_MAX_WEBP {
+        return Err(ErreurApplication::Validation {
+            message: format!(
+                "Image trop grande pour le WebP : {}x{} ({} pixels de côté au maximum)",
+                largeur, hauteur, DIMENSION_MAX_WEBP
+            ),
+        });
+    }
+    
+    let pixels;
+    let encodeur = if img.color().has_alpha() {
+        pixels = img.to_rgba8().into_raw();
+        webp::Encoder::from_rgba(&pixels, largeur, hauteur)
+    } else {
+        pixels = img.to_rgb8().into_raw();
+        webp::Encoder::from_rgb(&pixels, largeur, hauteur)
+    };
+    
+    // En sans perte, la « qualité » de libwebp règle l'effort de compression
+    let (sans_perte, reglage) = if qualite >= 100 { (true, 75.0) } else { (false, qualite as f32) };
+    encodeur
+        .encode_simple(sans_perte, reglage)
+        .map(|memoire| memoire.to_vec())
+        .map_err(|e| ErreurApplication::Systeme {
+            message: format!("Erreur d'encodage WebP : {:?}", e),
+        })
+}
+
+fn encoder_avif(img: &image::DynamicImage, qualite: u8) -> ResultatApplication<Vec<u8>> {
+    let rgba = img.to_rgba8();
+    let pixels: Vec<ravif::RGBA8> = rgba
+        .pixels()
+        .map(|pixel| ravif::RGBA8::new(pixel[0], pixel[1], pixel[2], pixel[3]))
+        .collect();
+    
+    // Vitesse 6 : bon compromis entre temps d'encodage et taille du fichier
+    ravif::Encoder::new()
+        .with_quality(qualite.clamp(1, 100) as f32)
+        .with_speed(6)
+        .encode_rgba(ravif::Img::new(&pixels[..], rgba.width() as usize, rgba.height() as usize))
+        .map(|encodee| encodee.avif_file)
+        .map_err(|e| ErreurApplication::Systeme {
+            message: format!("Erreur d'encodage AVIF : {}", e),
+        })
+}
+
+/**
+ * Encode l'image selon les options, en respectant la taille cible éventuelle
+ * 
+ * La qualité est cherchée par dichotomie (la plus haute qui tient dans la cible) ;
+ * si même la qualité minimale dépasse, les dimensions sont réduites de 20 % et
+ * la recherche recommence.
+ */
+fn encoder_sortie(img: &image::DynamicImage, format: ImageFormat, options: &OptionsConversion) -> ResultatApplication<ImageEncodee> {
+    let qualite_max = options.qualite.unwrap_or(85);
+    
+    let Some(cible) = options.taille_cible_octets else {
+        return Ok(ImageEncodee {
+            octets: encoder_image(img, format, qualite_max)?,
+            qualite: qualite_reglable(format).then_some(qualite_max),
+            largeur: img.width(),
+            hauteur: img.height(),
+        });
+    };
+    
+    let mut echelle = 1.0f32;
+    
+    loop {
+        let largeur = ((img.width() as f32 * echelle).round() as u32).max(1);
+        let hauteur = ((img.height() as f32 * echelle).round() as u32).max(1);
+        let candidate = if echelle < 1.0 {
+            std::borrow::Cow::Owned(img.resize_exact(largeur, hauteur, image::imageops::FilterType::Lanczos3))
+        } else {
+            std::borrow::Cow::Borrowed(img)
+        };
+        
+        let mut meilleur = None;
+        
+        if qualite_reglable(format) {
+            let (mut bas, mut haut) = (QUALITE_MINIMALE_CIBLE.min(qualite_max) as i32, qualite_max as i32);
+            while bas <= haut {
+                let milieu = (bas + haut) / 2;
+                let octets = encoder_image(&candidate, format, milieu as u8)?;
+                if octets.len() as u64 <= cible {
+                    meilleur = Some((Some(milieu as u8), octets));
+                    bas = milieu + 1;
+                } else {
+                    haut = milieu - 1;
+                }
+            }
+        } else {
+            let octets = encoder_image(&candidate, format, qualite_max)?;
+            if octets.len() as u64 <= cible {
+                meilleur = Some((None, octets));
+            }
+        }
+        
+        if let Some((qualite, octets)) = meilleur {
+            return Ok(ImageEncodee { octets, qualite, largeur, hauteur });
+        }
+        
+        if largeur <= 16 && hauteur <= 16 {
+            return Err(ErreurApplication::Validation {
+                message: format!(
+                    "Impossible d'atteindre {} en {}",
+                    formater_taille_fichier(cible),
+                    nom_format(format)
+                ),
+            });
+        }
+        
+        echelle *= 0.8;
+    }
+}
+
+/**
+ * Remplit le modèle de nom de sortie d'un traitement par lot
+ * 
+ * Variables disponibles : `{nom}` (nom d'origine sans extension),
+ * `{largeur}`, `{hauteur}` (dimensions de sortie) et `{ext}`.
+ */
+fn appliquer_modele_nom(modele: &str, nom: &str, largeur: u32, hauteur: u32, extension: &str) -> String {
+    modele
+        .replace("{nom}", nom)
+        .replace("{largeur}", &largeur.to_string())
+        .replace("{hauteur}", &hauteur.to_string())
+        .replace("{ext}", extension)
+}
+
+/**
+ * Liste les images des entrées et leur attribue un nom de sortie unique
+ * 
+ * Les sous-dossiers parcourus sont reproduits en sortie. Dans un même
+ * sous-dossier, les noms de base identiques (a.png et a.jpg, ou deux fichiers
+ * donnés depuis des dossiers différents) reçoivent un suffixe _2, _3…
+ * La comparaison ignore la casse, comme les systèmes de fichiers Windows et macOS.
+ */
+async fn lister_images_lot(entrees: &[String], recursif: bool) -> ResultatApplication<Vec<FichierLot>> {
+    let mut sources = Vec::new();
+    
+    for entree in entrees {
+        if Path::new(entree).is_dir() {
+            for fichier in lister_fichiers_par_extension(entree, EXTENSIONS_IMAGES, recursif).await? {
+                let sous_dossier = fichier
+                    .strip_prefix(entree)
+                    .ok()
+                    .and_then(Path::parent)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default();
+                sources.push((fichier, sous_dossier));
+            }
+        } else {
+            sources.push((PathBuf::from(entree), PathBuf::new()));
+        }
+    }
+    
+    sources.sort();
+    sources.dedup_by(|a, b| a.0 == b.0);
+    
+    let mut noms_pris = std::collections::HashSet::new();
+    let fichiers = sources
+        .into_iter()
+        .map(|(source, sous_dossier)| {
+            let base = source.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let mut nom = base.clone();
+            let mut numero = 1;
+            while !noms_pris.insert((sous_dossier.clone(), nom.to_lowercase())) {
+                numero += 1;
+                nom = format!("{}_{}", base, numero);
+            }
+            FichierLot { source, sous_dossier, nom }
+        })
+        .collect();
+    
+    Ok(fichiers)
+}
+
+/**
+ * Convertit un fichier d'un lot (exécuté hors du runtime asynchrone)
+ */
+fn convertir_fichier_lot(fichier: &FichierLot, options_lot: &OptionsConversionLot) -> IssueConversionLot {
+    let path_entree = fichier.source.as_path();
+    let chemin_entree = path_entree.to_string_lossy().to_string();
+    let resultat_vide = |fichier_sortie: String, message: String| ResultatConversion {
+        succes: false,
+        fichier_origine: chemin_entree.clone(),
+        fichier_sortie,
+        format_origine: String::new(),
+        format_sortie: options_lot.options.format_sortie.clone(),
+        taille_avant: 0,
+        taille_apres: 0,
+        reduction_pourcent: 0.0,
+        message,
+        qualite_utilisee: None,
+        largeur_sortie: 0,
+        hauteur_sortie: 0,
+    };
+    
+    let conversion = || -> ResultatApplication<IssueConversionLot> {
+        let format_sortie = format_depuis_nom(&options_lot.options.format_sortie)?;
+        let format_origine = detecter_format_image(path_entree)?;
+        let taille_avant = std::fs::metadata(path_entree)
+            .map_err(|e| ErreurApplication::Systeme {
+                message: format!("Impossible de lire le fichier d'origine : {}", e),
+            })?
+            .len();
+        
+        let img = traiter_image(charger_image(path_entree)?, &options_lot.options)?;
+        let nombre_trames = compter_trames(path_entree);
+        
+        // L'encodage précède le nommage : une taille cible peut réduire les dimensions
+        let encodage = encoder_sortie(&img, format_sortie, &options_lot.options)?;
+        
+        let nom_sortie = appliquer_modele_nom(
+            &options_lot.modele_nom,
+            &fichier.nom,
+            encodage.largeur,
+            encodage.hauteur,
+            format_sortie.extensions_str()[0],
+        );
+        let path_sortie = Path::new(&options_lot.dossier_sortie).join(&fichier.sous_dossier).join(nom_sortie);
+        let chemin_sortie = path_sortie.to_string_lossy().to_string();
+        
+        if let Some(parent) = path_sortie.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ErreurApplication::Systeme {
+                message: format!("Impossible de créer le dossier : {}", e),
+            })?;
+        }
+        
+        // Sans écrasement, la création exclusive rend le test d'existence atomique
+        let ouverture = if options_lot.ecraser_existants {
+            std::fs::File::create(&path_sortie)
+        } else {
+            std::fs::OpenOptions::new().write(true).create_new(true).open(&path_sortie)
+        };
+        let mut fichier_sortie = match ouverture {
+            Ok(fichier_sortie) => fichier_sortie,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Ok(IssueConversionLot::Ignoree(resultat_vide(
+                    chemin_sortie,
+                    "Ignoré : le fichier de sortie existe déjà".to_string(),
+                )));
+            }
+            Err(e) => {
+                return Err(ErreurApplication::Systeme {
+                    message: format!("Impossible de créer le fichier : {}", e),
+                })
+            }
+        };
+        
+        std::io::Write::write_all(&mut fichier_sortie, &encodage.octets).map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible d'écrire le fichier : {}", e),
+        })?;
+        
+        let taille_apres = encodage.octets.len() as u64;
+        let reduction_pourcent = if taille_avant > 0 {
+            ((taille_avant as f32 - taille_apres as f32) / taille_avant as f32) * 100.0
+        } else {
+            0.0
+        };
+        
+        Ok(IssueConversionLot::Convertie(ResultatConversion {
+            succes: true,
+            fichier_origine: chemin_entree.clone(),
+            fichier_sortie: chemin_sortie,
+            format_origine: format_origine.to_string(),
+            format_sortie: options_lot.options.format_sortie.clone(),
+            taille_avant,
+            taille_apres,
+            reduction_pourcent,
+            message: format!(
+                "Conversion réussie : {} -> {} ({:.1}% de réduction){}",
+                format_origine,
+                options_lot.options.format_sortie,
+                reduction_pourcent,
+                avertissement_animation(nombre_trames)
+            ),
+            qualite_utilisee: encodage.qualite,
+            largeur_sortie: encodage.largeur,
+            hauteur_sortie: encodage.hauteur,
+        }))
+    };
+    
+    conversion().unwrap_or_else(|e| IssueConversionLot::Echec(resultat_vide(String::new(), e.to_string())))
+}
+
 async fn supprimer_fond_interne(
     chemin_entree: &str,
     chemin_sortie: &str,
@@ -369,6 +2014,19 @@ async fn supprimer_fond_interne(
         });
     }
     
+    let format_sortie = format_depuis_nom(options.format_sortie.as_deref().unwrap_or("png")).inspect_err(|_| {
+        progression.terminer_avec_erreur("Format de sortie non supporté");
+    })?;
+    if options.fond_composition.is_none() && !gere_transparence(format_sortie) {
+        progression.terminer_avec_erreur("Format sans transparence");
+        return Err(ErreurApplication::Validation {
+            message: format!(
+                "Le format {} ne gère pas la transparence : choisissez un fond de composition",
+                nom_format(format_sortie)
+            ),
+        });
+    }
+    
     let taille_avant = tokio::fs::metadata(path_entree).await
         .map_err(|e| {
             progression.terminer_avec_erreur("Erreur de lecture des métadonnées");
@@ -381,11 +2039,8 @@ async fn supprimer_fond_interne(
     // Étape 2: Chargement de l'image (40%)
     progression.mettre_a_jour(40, Some("Chargement de l'image...")).await;
     
-    let img = image::open(path_entree).map_err(|e| {
+    let img = charger_image(path_entree).inspect_err(|_| {
         progression.terminer_avec_erreur("Impossible de charger l'image");
-        ErreurApplication::Donnees {
-            message: format!("Impossible de charger l'image : {}", e),
-        }
     })?;
     
     // Convertir en RGBA pour gérer la transparence
@@ -394,23 +2049,94 @@ async fn supprimer_fond_interne(
     // Étape 3: Parsing de la couleur de fond (50%)
     progression.mettre_a_jour(50, Some("Analyse de la couleur de fond...")).await;
     
-    let couleur_cible = parser_couleur_hex(&options.couleur_fond)?;
+    let espace = EspaceCouleur::depuis_nom(options.espace_couleur.as_deref())?;
+    let couleur_detectee = options.couleur_fond.trim().is_empty();
+    let couleur_cible = if couleur_detectee {
+        detecter_couleur_bordure(&img_rgba).ok_or_else(|| {
+            progression.terminer_avec_erreur("Couleur de fond introuvable");
+            ErreurApplication::Validation {
+                message: "Impossible de détecter la couleur de fond : le pourtour est transparent".to_string(),
+            }
+        })?
+    } else {
+        parser_couleur_hex(&options.couleur_fond)?
+    };
     
     // Étape 4: Suppression du fond (80%)
     progression.mettre_a_jour(80, Some("Suppression du fond...")).await;
     
-    supprimer_couleur_fond(&mut img_rgba, couleur_cible, options.tolerance);
+    match options.mode.as_deref().unwrap_or("global") {
+        "global" => supprimer_couleur_fond(&mut img_rgba, couleur_cible, options.tolerance, espace),
+        "bords" => {
+            let departs = pixels_pourtour(img_rgba.width(), img_rgba.height());
+            remplir_fond_depuis(&mut img_rgba, &departs, couleur_cible, options.tolerance, espace);
+        }
+        "points" => {
+            let points = options.points_depart.as_deref().unwrap_or_default();
+            if points.is_empty() {
+                progression.terminer_avec_erreur("Aucun point de départ");
+                return Err(ErreurApplication::Validation {
+                    message: "Le mode \"points\" nécessite au moins un point de départ".to_string(),
+                });
+            }
+            if let Some(point) = points.iter().find(|point| point.x >= img_rgba.width() || point.y >= img_rgba.height()) {
+                progression.terminer_avec_erreur("Point de départ invalide");
+                return Err(ErreurApplication::Validation {
+                    message: format!("Le point ({}, {}) est hors de l'image", point.x, point.y),
+                });
+            }
+            
+            for point in points {
+                // Sans couleur imposée, chaque point désigne sa propre couleur de fond
+                let cible = if couleur_detectee {
+                    let [r, g, b, _] = img_rgba.get_pixel(point.x, point.y).0;
+                    [r, g, b]
+                } else {
+                    couleur_cible
+                };
+                remplir_fond_depuis(&mut img_rgba, &[(point.x, point.y)], cible, options.tolerance, espace);
+            }
+        }
+        "chromatique" => {
+            let cle = options.cle_chromatique.as_ref().ok_or_else(|| {
+                progression.terminer_avec_erreur("Réglages d'incrustation manquants");
+                ErreurApplication::Validation {
+                    message: "Le mode \"chromatique\" nécessite les réglages cle_chromatique".to_string(),
+                }
+            })?;
+            incruster_cle_chromatique(&mut img_rgba, couleur_cible, cle);
+        }
+        autre => {
+            progression.terminer_avec_erreur("Mode inconnu");
+            return Err(ErreurApplication::Validation {
+                message: format!("Mode de suppression inconnu : {}", autre),
+            });
+        }
+    }
+    
+    // Étape 5: Traitement des bords si demandé (90%)
+    progression.mettre_a_jour(90, Some("Traitement des bords...")).await;
     
-    // Étape 5: Adoucissement des bords si demandé (90%)
+    // Avant tout flou : l'alpha issu de la suppression mesure encore le mélange avec le fond
+    if options.decontaminer_couleurs.unwrap_or(false) {
+        decontaminer_couleurs(&mut img_rgba, couleur_cible);
+    }
     if options.adoucir_bords {
-        progression.mettre_a_jour(90, Some("Adoucissement des bords...")).await;
         adoucir_bords_transparence(&mut img_rgba, options.rayon_adoucissement);
-    } else {
-        progression.mettre_a_jour(90, Some("Finalisation...")).await;
+    }
+    if let Some(rayon) = options.rayon_fondu.filter(|rayon| *rayon > 0) {
+        fondre_contour(&mut img_rgba, rayon);
+    }
+    
+    if let Some(fond) = &options.fond_composition {
+        progression.mettre_a_jour(95, Some("Composition sur le fond...")).await;
+        img_rgba = composer_sur_fond(&img_rgba, fond).inspect_err(|_| {
+            progression.terminer_avec_erreur("Erreur de composition");
+        })?;
     }
     
-    // Étape 6: Sauvegarde en PNG (100%)
-    progression.mettre_a_jour(100, Some("Sauvegarde en PNG...")).await;
+    // Étape 6: Sauvegarde (100%)
+    progression.mettre_a_jour(100, Some(&format!("Sauvegarde en {}...", nom_format(format_sortie)))).await;
     
     // Créer le dossier de sortie si nécessaire
     if let Some(parent) = path_sortie.parent() {
@@ -423,11 +2149,15 @@ async fn supprimer_fond_interne(
         })?;
     }
     
-    // Sauvegarder en PNG (seul format supportant la transparence)
-    img_rgba.save_with_format(path_sortie, image::ImageFormat::Png).map_err(|e| {
-        progression.terminer_avec_erreur("Erreur de sauvegarde PNG");
+    let (largeur_sortie, hauteur_sortie) = img_rgba.dimensions();
+    let qualite = options.qualite.unwrap_or(100).clamp(1, 100);
+    let octets = encoder_image(&image::DynamicImage::ImageRgba8(img_rgba), format_sortie, qualite).inspect_err(|_| {
+        progression.terminer_avec_erreur("Erreur de sauvegarde");
+    })?;
+    tokio::fs::write(path_sortie, octets).await.map_err(|e| {
+        progression.terminer_avec_erreur("Erreur de sauvegarde");
         ErreurApplication::Systeme {
-            message: format!("Erreur de sauvegarde PNG : {}", e),
+            message: format!("Impossible d'écrire le fichier : {}", e),
         }
     })?;
     
@@ -448,9 +2178,16 @@ async fn supprimer_fond_interne(
     
     let format_origine = detecter_format_image(path_entree)?;
     
+    let description_sortie = if options.fond_composition.is_some() {
+        format!("{} sur fond de composition", nom_format(format_sortie))
+    } else {
+        format!("{} transparent", nom_format(format_sortie))
+    };
+    
     let message_final = format!(
-        "Fond supprimé: {} → PNG transparent", 
-        formater_taille_fichier(taille_avant)
+        "Fond supprimé: {} → {}", 
+        formater_taille_fichier(taille_avant),
+        description_sortie
     );
     progression.terminer_avec_succes(&message_final);
     
@@ -459,128 +2196,3528 @@ async fn supprimer_fond_interne(
         fichier_origine: chemin_entree.to_string(),
         fichier_sortie: chemin_sortie.to_string(),
         format_origine: format_origine.to_string(),
-        format_sortie: "PNG".to_string(),
+        format_sortie: nom_format(format_sortie).to_string(),
         taille_avant,
         taille_apres,
         reduction_pourcent,
-        message: format!(
-            "Suppression de fond réussie : {} -> PNG transparent",
-            format_origine
-        ),
+        message: if couleur_detectee {
+            format!(
+                "Suppression de fond réussie : {} -> {} (fond détecté : #{:02X}{:02X}{:02X})",
+                format_origine, description_sortie, couleur_cible[0], couleur_cible[1], couleur_cible[2]
+            )
+        } else {
+            format!(
+                "Suppression de fond réussie : {} -> {}",
+                format_origine,
+                description_sortie
+            )
+        },
+        qualite_utilisee: qualite_reglable(format_sortie).then_some(qualite),
+        largeur_sortie,
+        hauteur_sortie,
     })
 }
 
-fn parser_couleur_hex(hex: &str) -> ResultatApplication<[u8; 3]> {
-    let hex = hex.trim_start_matches('#');
+fn appliquer_transformations(
+    img: image::DynamicImage,
+    etapes: &[EtapeTransformation]
+) -> ResultatApplication<image::DynamicImage> {
+    let mut img = img;
     
-    if hex.len() != 6 {
+    for etape in etapes {
+        img = match etape {
+            EtapeTransformation::Recadrage { x, y, largeur, hauteur } => {
+                // Sommes en u64 : des coordonnées proches de u32::MAX ne doivent pas déborder
+                let deborde = *x as u64 + *largeur as u64 > img.width() as u64
+                    || *y as u64 + *hauteur as u64 > img.height() as u64;
+                if *largeur == 0 || *hauteur == 0 || deborde {
+                    return Err(ErreurApplication::Validation {
+                        message: format!(
+                            "Rectangle de recadrage {}x{}+{}+{} hors de l'image {}x{}",
+                            largeur, hauteur, x, y, img.width(), img.height()
+                        ),
+                    });
+                }
+                img.crop_imm(*x, *y, *largeur, *hauteur)
+            }
+            EtapeTransformation::RecadrageRatio { ratio_largeur, ratio_hauteur, ancrage } => {
+                let (x, y, largeur, hauteur) =
+                    calculer_recadrage_ratio(img.width(), img.height(), *ratio_largeur, *ratio_hauteur, ancrage)?;
+                img.crop_imm(x, y, largeur, hauteur)
+            }
+            EtapeTransformation::Rotation { angle_degres, couleur_fond } => {
+                // Les quarts de tour sont exacts et ne nécessitent pas d'interpolation
+                let angle = angle_degres.rem_euclid(360.0);
+                if angle == 0.0 {
+                    img
+                } else if angle == 90.0 {
+                    img.rotate90()
+                } else if angle == 180.0 {
+                    img.rotate180()
+                } else if angle == 270.0 {
+                    img.rotate270()
+                } else {
+                    image::DynamicImage::ImageRgba8(pivoter_image(&img.to_rgba8(), angle, parser_couleur_rgba(couleur_fond)?))
+                }
+            }
+            EtapeTransformation::MiroirHorizontal => img.fliph(),
+            EtapeTransformation::MiroirVertical => img.flipv(),
+            EtapeTransformation::Marge { haut, droite, bas, gauche, couleur } => {
+                let couleur = image::Rgba(parser_couleur_rgba(couleur)?);
+                let dimensions = img.width().checked_add(*gauche).and_then(|largeur| largeur.checked_add(*droite))
+                    .zip(img.height().checked_add(*haut).and_then(|hauteur| hauteur.checked_add(*bas)));
+                let Some((largeur, hauteur)) = dimensions else {
+                    return Err(ErreurApplication::Validation {
+                        message: format!("Marges trop grandes pour l'image {}x{}", img.width(), img.height()),
+                    });
+                };
+                let mut resultat = image::RgbaImage::from_pixel(largeur, hauteur, couleur);
+                image::imageops::replace(&mut resultat, &img.to_rgba8(), *gauche as i64, *haut as i64);
+                image::DynamicImage::ImageRgba8(resultat)
+            }
+            EtapeTransformation::Bordure { epaisseur, couleur } => {
+                let couleur = image::Rgba(parser_couleur_rgba(couleur)?);
+                let mut resultat = img.to_rgba8();
+                let (largeur, hauteur) = resultat.dimensions();
+                for (x, y, pixel) in resultat.enumerate_pixels_mut() {
+                    if x < *epaisseur || y < *epaisseur || largeur - x <= *epaisseur || hauteur - y <= *epaisseur {
+                        *pixel = couleur;
+                    }
+                }
+                image::DynamicImage::ImageRgba8(resultat)
+            }
+            EtapeTransformation::CoinsArrondis { rayon } => {
+                let mut resultat = img.to_rgba8();
+                arrondir_coins(&mut resultat, *rayon);
+                image::DynamicImage::ImageRgba8(resultat)
+            }
+        };
+    }
+    
+    Ok(img)
+}
+
+/**
+ * Calcule le plus grand rectangle au ratio demandé, placé selon l'ancrage
+ * 
+ * # Retour
+ * * `(x, y, largeur, hauteur)` du rectangle de recadrage
+ */
+fn calculer_recadrage_ratio(
+    largeur: u32,
+    hauteur: u32,
+    ratio_largeur: u32,
+    ratio_hauteur: u32,
+    ancrage: &str
+) -> ResultatApplication<(u32, u32, u32, u32)> {
+    if ratio_largeur == 0 || ratio_hauteur == 0 {
         return Err(ErreurApplication::Validation {
-            message: "La couleur doit être au format #RRGGBB".to_string(),
+            message: "Le ratio de recadrage doit être strictement positif".to_string(),
         });
     }
     
-    let r = u8::from_str_radix(&hex[0..2], 16).map_err(|_| {
-        ErreurApplication::Validation {
-            message: "Couleur hexadécimale invalide".to_string(),
-        }
-    })?;
+    let (facteur_x, facteur_y) = facteurs_ancrage(ancrage)?;
     
-    let g = u8::from_str_radix(&hex[2..4], 16).map_err(|_| {
-        ErreurApplication::Validation {
-            message: "Couleur hexadécimale invalide".to_string(),
-        }
-    })?;
+    // Comparaison en entiers pour éviter les arrondis sur les ratios exacts
+    let (nouvelle_largeur, nouvelle_hauteur) =
+        if largeur as u64 * ratio_hauteur as u64 > hauteur as u64 * ratio_largeur as u64 {
+            ((hauteur as u64 * ratio_largeur as u64 / ratio_hauteur as u64).max(1) as u32, hauteur)
+        } else {
+            (largeur, (largeur as u64 * ratio_hauteur as u64 / ratio_largeur as u64).max(1) as u32)
+        };
     
-    let b = u8::from_str_radix(&hex[4..6], 16).map_err(|_| {
-        ErreurApplication::Validation {
-            message: "Couleur hexadécimale invalide".to_string(),
+    let x = ((largeur - nouvelle_largeur) as f32 * facteur_x).round() as u32;
+    let y = ((hauteur - nouvelle_hauteur) as f32 * facteur_y).round() as u32;
+    
+    Ok((x, y, nouvelle_largeur, nouvelle_hauteur))
+}
+
+/**
+ * Position relative (0 à 1 sur chaque axe) d'un ancrage nommé
+ */
+fn facteurs_ancrage(ancrage: &str) -> ResultatApplication<(f32, f32)> {
+    match ancrage {
+        "centre" => Ok((0.5, 0.5)),
+        "haut" => Ok((0.5, 0.0)),
+        "bas" => Ok((0.5, 1.0)),
+        "gauche" => Ok((0.0, 0.5)),
+        "droite" => Ok((1.0, 0.5)),
+        "haut_gauche" => Ok((0.0, 0.0)),
+        "haut_droite" => Ok((1.0, 0.0)),
+        "bas_gauche" => Ok((0.0, 1.0)),
+        "bas_droite" => Ok((1.0, 1.0)),
+        _ => Err(ErreurApplication::Validation {
+            message: format!("Ancrage inconnu : {}", ancrage),
+        }),
+    }
+}
+
+/**
+ * Fait pivoter une image d'un angle quelconque (sens horaire) avec interpolation bilinéaire
+ * 
+ * Le canevas est agrandi pour contenir toute l'image pivotée.
+ */
+fn pivoter_image(source: &image::RgbaImage, angle_degres: f32, fond: [u8; 4]) -> image::RgbaImage {
+    let (largeur, hauteur) = (source.width() as f32, source.height() as f32);
+    let (sinus, cosinus) = angle_degres.to_radians().sin_cos();
+    
+    let nouvelle_largeur = (largeur * cosinus.abs() + hauteur * sinus.abs()).round().max(1.0) as u32;
+    let nouvelle_hauteur = (largeur * sinus.abs() + hauteur * cosinus.abs()).round().max(1.0) as u32;
+    let (centre_x, centre_y) = (largeur / 2.0, hauteur / 2.0);
+    let (nouveau_centre_x, nouveau_centre_y) = (nouvelle_largeur as f32 / 2.0, nouvelle_hauteur as f32 / 2.0);
+    
+    image::RgbaImage::from_fn(nouvelle_largeur, nouvelle_hauteur, |x, y| {
+        // Rotation inverse du centre du pixel de destination vers la source
+        let dx = x as f32 + 0.5 - nouveau_centre_x;
+        let dy = y as f32 + 0.5 - nouveau_centre_y;
+        let sx = dx * cosinus + dy * sinus + centre_x - 0.5;
+        let sy = -dx * sinus + dy * cosinus + centre_y - 0.5;
+        
+        if sx < -0.5 || sy < -0.5 || sx > largeur - 0.5 || sy > hauteur - 0.5 {
+            return image::Rgba(fond);
+        }
+        
+        let echantillon = |px: i64, py: i64| -> [f32; 4] {
+            let pixel = source.get_pixel(
+                px.clamp(0, source.width() as i64 - 1) as u32,
+                py.clamp(0, source.height() as i64 - 1) as u32,
+            );
+            pixel.0.map(|canal| canal as f32)
+        };
+        
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let (a, b, c, d) = (echantillon(x0, y0), echantillon(x0 + 1, y0), echantillon(x0, y0 + 1), echantillon(x0 + 1, y0 + 1));
+        
+        let mut resultat = [0u8; 4];
+        for canal in 0..4 {
+            let haut = a[canal] * (1.0 - fx) + b[canal] * fx;
+            let bas = c[canal] * (1.0 - fx) + d[canal] * fx;
+            resultat[canal] = (haut * (1.0 - fy) + bas * fy).round() as u8;
+        }
+        image::Rgba(resultat)
+    })
+}
+
+/**
+ * Rend transparents les coins de l'image, avec un bord anticrénelé
+ */
+fn arrondir_coins(img: &mut image::RgbaImage, rayon: u32) {
+    let (largeur, hauteur) = img.dimensions();
+    let rayon = rayon.min(largeur / 2).min(hauteur / 2);
+    if rayon == 0 {
+        return;
+    }
+    let r = rayon as f32;
+    
+    for y in 0..hauteur {
+        for x in 0..largeur {
+            // Distance au centre du cercle du coin le plus proche, le cas échéant
+            let cx = if x < rayon { r } else if x >= largeur - rayon { (largeur - rayon) as f32 } else { continue };
+            let cy = if y < rayon { r } else if y >= hauteur - rayon { (hauteur - rayon) as f32 } else { continue };
+            let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+            let couverture = (r - distance + 0.5).clamp(0.0, 1.0);
+            
+            let pixel = img.get_pixel_mut(x, y);
+            pixel.0[3] = (pixel.0[3] as f32 * couverture).round() as u8;
+        }
+    }
+}
+
+/**
+ * Lit une couleur "#RRGGBB", "#RRGGBBAA" ou "transparent"
+ */
+fn parser_couleur_rgba(texte: &str) -> ResultatApplication<[u8; 4]> {
+    if texte.eq_ignore_ascii_case("transparent") {
+        return Ok([0, 0, 0, 0]);
+    }
+    
+    let hex = texte.trim_start_matches('#');
+    // Le découpage par octets suppose une chaîne ASCII
+    if !hex.is_ascii() {
+        return Err(ErreurApplication::Validation {
+            message: "Couleur hexadécimale invalide".to_string(),
+        });
+    }
+    if hex.len() == 8 {
+        let alpha = u8::from_str_radix(&hex[6..8], 16).map_err(|_| ErreurApplication::Validation {
+            message: "Couleur hexadécimale invalide".to_string(),
+        })?;
+        let [r, g, b] = parser_couleur_hex(&hex[0..6])?;
+        return Ok([r, g, b, alpha]);
+    }
+    
+    let [r, g, b] = parser_couleur_hex(hex)?;
+    Ok([r, g, b, 255])
+}
+
+fn appliquer_reglages(img: image::DynamicImage, reglages: &[ReglageImage]) -> image::DynamicImage {
+    let mut img = img;
+    
+    for reglage in reglages {
+        img = match reglage {
+            ReglageImage::Luminosite { valeur } => img.brighten(*valeur),
+            ReglageImage::Contraste { valeur } => img.adjust_contrast(*valeur),
+            ReglageImage::Teinte { degres } => img.huerotate(*degres),
+            ReglageImage::Inversion => {
+                img.invert();
+                img
+            }
+            ReglageImage::NiveauxDeGris => img.grayscale(),
+            ReglageImage::FlouGaussien { sigma } => img.blur(*sigma),
+            ReglageImage::Nettete { sigma, seuil } => img.unsharpen(*sigma, *seuil),
+            ReglageImage::Saturation { facteur } => transformer_pixels(img, |[r, g, b]| {
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+                [r, g, b].map(|canal| luma + (canal - luma) * facteur)
+            }),
+            ReglageImage::Sepia => transformer_pixels(img, |[r, g, b]| [
+                0.393 * r + 0.769 * g + 0.189 * b,
+                0.349 * r + 0.686 * g + 0.168 * b,
+                0.272 * r + 0.534 * g + 0.131 * b,
+            ]),
+            ReglageImage::Gamma { valeur } => {
+                let exposant = 1.0 / valeur.max(0.01);
+                transformer_pixels(img, |rgb| rgb.map(|canal| 255.0 * (canal / 255.0).powf(exposant)))
+            }
+            ReglageImage::NiveauxAutomatiques { ecretage_pourcent } => {
+                let mut rgba = img.to_rgba8();
+                etirer_histogramme(&mut rgba, *ecretage_pourcent);
+                image::DynamicImage::ImageRgba8(rgba)
+            }
+        };
+    }
+    
+    img
+}
+
+/**
+ * Applique une fonction aux composantes RGB (0 à 255) de chaque pixel, l'alpha étant conservé
+ */
+fn transformer_pixels(img: image::DynamicImage, fonction: impl Fn([f32; 3]) -> [f32; 3]) -> image::DynamicImage {
+    let mut rgba = img.to_rgba8();
+    
+    for pixel in rgba.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let [r, g, b] = fonction([r as f32, g as f32, b as f32]).map(|canal| canal.round().clamp(0.0, 255.0) as u8);
+        pixel.0 = [r, g, b, a];
+    }
+    
+    image::DynamicImage::ImageRgba8(rgba)
+}
+
+/**
+ * Étire l'histogramme de chaque canal RGB pour occuper toute la plage 0-255
+ */
+fn etirer_histogramme(img: &mut image::RgbaImage, ecretage_pourcent: f32) {
+    let nombre_pixels = (img.width() as u64 * img.height() as u64) as f32;
+    let ecretage = (nombre_pixels * ecretage_pourcent.clamp(0.0, 49.0) / 100.0) as u64;
+    
+    let mut tables = [[0u8; 256]; 3];
+    
+    for (canal, table) in tables.iter_mut().enumerate() {
+        let mut histogramme = [0u64; 256];
+        for pixel in img.pixels() {
+            histogramme[pixel.0[canal] as usize] += 1;
+        }
+        
+        // Premier et dernier niveaux une fois les extrêmes écrêtés
+        let mut cumul = 0;
+        let minimum = (0..256).find(|&niveau| { cumul += histogramme[niveau]; cumul > ecretage }).unwrap_or(0);
+        cumul = 0;
+        let maximum = (0..256).rev().find(|&niveau| { cumul += histogramme[niveau]; cumul > ecretage }).unwrap_or(255);
+        
+        for (niveau, valeur) in table.iter_mut().enumerate() {
+            *valeur = if maximum <= minimum {
+                niveau as u8
+            } else {
+                ((niveau as f32 - minimum as f32) * 255.0 / (maximum - minimum) as f32).round().clamp(0.0, 255.0) as u8
+            };
+        }
+    }
+    
+    for pixel in img.pixels_mut() {
+        for (canal, table) in tables.iter().enumerate() {
+            pixel.0[canal] = table[pixel.0[canal] as usize];
+        }
+    }
+}
+
+fn parser_couleur_hex(hex: &str) -> ResultatApplication<[u8; 3]> {
+    let hex = hex.trim_start_matches('#');
+    
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(ErreurApplication::Validation {
+            message: "La couleur doit être au format #RRGGBB".to_string(),
+        });
+    }
+    
+    let r = u8::from_str_radix(&hex[0..2], 16).map_err(|_| {
+        ErreurApplication::Validation {
+            message: "Couleur hexadécimale invalide".to_string(),
+        }
+    })?;
+    
+    let g = u8::from_str_radix(&hex[2..4], 16).map_err(|_| {
+        ErreurApplication::Validation {
+            message: "Couleur hexadécimale invalide".to_string(),
+        }
+    })?;
+    
+    let b = u8::from_str_radix(&hex[4..6], 16).map_err(|_| {
+        ErreurApplication::Validation {
+            message: "Couleur hexadécimale invalide".to_string(),
+        }
+    })?;
+    
+    Ok([r, g, b])
+}
+
+fn supprimer_couleur_fond(img: &mut image::RgbaImage, couleur_cible: [u8; 3], tolerance: u8, espace: EspaceCouleur) {
+    let cible = espace.coordonnees(couleur_cible);
+    
+    for pixel in img.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        
+        // Calculer la distance entre la couleur du pixel et la couleur cible
+        let distance = espace.distance(cible, [r, g, b]);
+        
+        if let Some(alpha) = alpha_selon_distance(distance, tolerance, a) {
+            pixel.0[3] = alpha;
+        }
+    }
+}
+
+fn gere_transparence(format: ImageFormat) -> bool {
+    matches!(
+        format,
+        ImageFormat::Png | ImageFormat::WebP | ImageFormat::Avif | ImageFormat::Tiff | ImageFormat::Gif
+    )
+}
+
+/**
+ * Pose le sujet détouré sur un fond de mêmes dimensions
+ */
+fn composer_sur_fond(sujet: &image::RgbaImage, fond: &FondComposition) -> ResultatApplication<image::RgbaImage> {
+    let (largeur, hauteur) = sujet.dimensions();
+    
+    let mut resultat = match fond {
+        FondComposition::Couleur { couleur } => {
+            image::RgbaImage::from_pixel(largeur, hauteur, image::Rgba(parser_couleur_rgba(couleur)?))
+        }
+        FondComposition::Image { chemin } => {
+            if !Path::new(chemin).exists() {
+                return Err(ErreurApplication::Validation {
+                    message: format!("L'image de fond n'existe pas : {}", chemin),
+                });
+            }
+            charger_image(Path::new(chemin))?
+                .resize_to_fill(largeur, hauteur, image::imageops::FilterType::Lanczos3)
+                .to_rgba8()
+        }
+        FondComposition::Damier { taille_case } => {
+            let case = (*taille_case).max(1);
+            image::RgbaImage::from_fn(largeur, hauteur, |x, y| {
+                if (x / case + y / case) % 2 == 0 {
+                    image::Rgba([255, 255, 255, 255])
+                } else {
+                    image::Rgba([204, 204, 204, 255])
+                }
+            })
+        }
+    };
+    
+    superposer(&mut resultat, sujet, 0, 0);
+    Ok(resultat)
+}
+
+/**
+ * Pose `dessus` sur `dessous` à la position donnée, qui peut déborder de l'image
+ * 
+ * Opérateur « over » calculé en flottants : imageops::overlay arrondit
+ * l'alpha d'un fond opaque à 254.
+ */
+fn superposer(dessous: &mut image::RgbaImage, dessus: &image::RgbaImage, x: i64, y: i64) {
+    for (dx, dy, pixel) in dessus.enumerate_pixels() {
+        let (px, py) = (x + dx as i64, y + dy as i64);
+        if px < 0 || py < 0 || px >= dessous.width() as i64 || py >= dessous.height() as i64 {
+            continue;
+        }
+        let dessous = dessous.get_pixel_mut(px as u32, py as u32);
+        
+        let alpha = pixel.0[3] as f32 / 255.0;
+        let alpha_dessous = dessous.0[3] as f32 / 255.0 * (1.0 - alpha);
+        let alpha_sortie = alpha + alpha_dessous;
+        if alpha_sortie <= 0.0 {
+            continue;
+        }
+        
+        let melanger = |canal: u8, canal_dessous: u8| {
+            ((canal as f32 * alpha + canal_dessous as f32 * alpha_dessous) / alpha_sortie).round() as u8
+        };
+        let [r, g, b, _] = pixel.0;
+        let [r_dessous, g_dessous, b_dessous, _] = dessous.0;
+        dessous.0 = [
+            melanger(r, r_dessous),
+            melanger(g, g_dessous),
+            melanger(b, b_dessous),
+            (alpha_sortie * 255.0).round() as u8,
+        ];
+    }
+}
+
+/**
+ * Incrustation : rend transparents les pixels proches de la couleur clé en HSV
+ * 
+ * L'écart d'un pixel est le plus grand des écarts de teinte, saturation et valeur,
+ * chacun rapporté à sa tolérance. Jusqu'à 1 le pixel est du fond, au-delà du seuil
+ * extérieur il est du sujet ; entre les deux l'opacité croît linéairement.
+ */
+fn incruster_cle_chromatique(img: &mut image::RgbaImage, couleur_cle: [u8; 3], cle: &OptionsCleChromatique) {
+    let (teinte_cle, saturation_cle, valeur_cle) = rgb_vers_hsv(couleur_cle);
+    let seuil_exterieur = cle.seuil_exterieur.max(1.0);
+    
+    // Le débordement se lit sur le canal dominant de la clé (vert ou bleu en général)
+    let canal_cle = (0..3).max_by_key(|&canal| couleur_cle[canal]).unwrap_or(1);
+    
+    for pixel in img.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let (teinte, saturation, valeur) = rgb_vers_hsv([r, g, b]);
+        
+        let ecart_teinte = (teinte - teinte_cle).abs();
+        let ecart = (ecart_teinte.min(360.0 - ecart_teinte) / cle.tolerance_teinte.max(0.001))
+            .max((saturation - saturation_cle).abs() / cle.tolerance_saturation.max(0.001))
+            .max((valeur - valeur_cle).abs() / cle.tolerance_valeur.max(0.001));
+        
+        let opacite = if ecart <= 1.0 {
+            0.0
+        } else if ecart >= seuil_exterieur {
+            1.0
+        } else {
+            (ecart - 1.0) / (seuil_exterieur - 1.0)
+        };
+        pixel.0[3] = (a as f32 * opacite).round() as u8;
+        
+        // Le canal de la clé est plafonné au plus fort des deux autres
+        if cle.supprimer_debordement && pixel.0[3] > 0 {
+            let plafond = (0..3)
+                .filter(|&canal| canal != canal_cle)
+                .map(|canal| pixel.0[canal])
+                .max()
+                .unwrap_or(0);
+            pixel.0[canal_cle] = pixel.0[canal_cle].min(plafond);
+        }
+    }
+}
+
+/**
+ * Transparence d'un pixel selon sa distance à la couleur de fond
+ * 
+ * # Retour
+ * * `Some(0)` dans la tolérance, un alpha partiel dans la zone de transition
+ *   (jusqu'à 1,5 fois la tolérance), `None` si le pixel n'est pas du fond
+ */
+fn alpha_selon_distance(distance: f32, tolerance: u8, alpha: u8) -> Option<u8> {
+    let tolerance = tolerance as f32;
+    
+    if distance <= tolerance {
+        Some(0)
+    } else if distance <= tolerance * 1.5 {
+        // Transition douce pour les pixels proches
+        let facteur_alpha = (distance - tolerance) / (tolerance * 0.5);
+        Some((alpha as f32 * facteur_alpha.min(1.0)) as u8)
+    } else {
+        None
+    }
+}
+
+/**
+ * Rend transparent le fond relié aux points de départ, par remplissage de proche en proche
+ * 
+ * Le remplissage ne traverse que les pixels dans la tolérance : ceux de la zone
+ * de transition deviennent semi-transparents mais l'arrêtent. Un sujet de la même
+ * couleur que le fond, mais séparé de lui par un contour, est ainsi préservé.
+ */
+fn remplir_fond_depuis(
+    img: &mut image::RgbaImage,
+    departs: &[(u32, u32)],
+    couleur_cible: [u8; 3],
+    tolerance: u8,
+    espace: EspaceCouleur
+) {
+    let (largeur, hauteur) = img.dimensions();
+    let cible = espace.coordonnees(couleur_cible);
+    let indice = |x: u32, y: u32| y as usize * largeur as usize + x as usize;
+    
+    let mut visites = vec![false; largeur as usize * hauteur as usize];
+    let mut pile = Vec::with_capacity(departs.len());
+    for &(x, y) in departs {
+        if !visites[indice(x, y)] {
+            visites[indice(x, y)] = true;
+            pile.push((x, y));
+        }
+    }
+    
+    while let Some((x, y)) = pile.pop() {
+        let pixel = img.get_pixel_mut(x, y);
+        let [r, g, b, a] = pixel.0;
+        let distance = espace.distance(cible, [r, g, b]);
+        
+        let Some(alpha) = alpha_selon_distance(distance, tolerance, a) else {
+            continue;
+        };
+        pixel.0[3] = alpha;
+        
+        if distance > tolerance as f32 {
+            continue;
+        }
+        
+        let voisins = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (vx, vy) in voisins {
+            if vx < largeur && vy < hauteur && !visites[indice(vx, vy)] {
+                visites[indice(vx, vy)] = true;
+                pile.push((vx, vy));
+            }
+        }
+    }
+}
+
+fn pixels_pourtour(largeur: u32, hauteur: u32) -> Vec<(u32, u32)> {
+    if largeur == 0 || hauteur == 0 {
+        return Vec::new();
+    }
+    
+    let mut pixels = Vec::with_capacity(2 * (largeur + hauteur) as usize);
+    for x in 0..largeur {
+        pixels.push((x, 0));
+        pixels.push((x, hauteur - 1));
+    }
+    for y in 1..hauteur.saturating_sub(1) {
+        pixels.push((0, y));
+        pixels.push((largeur - 1, y));
+    }
+    
+    pixels
+}
+
+/**
+ * Couleur dominante du pourtour de l'image
+ * 
+ * Les pixels opaques du pourtour sont regroupés par teintes voisines (5 bits par
+ * canal) ; la moyenne du groupe le plus peuplé est retenue.
+ */
+fn detecter_couleur_bordure(img: &image::RgbaImage) -> Option<[u8; 3]> {
+    let mut groupes: std::collections::HashMap<[u8; 3], (u32, [u64; 3])> = std::collections::HashMap::new();
+    
+    for (x, y) in pixels_pourtour(img.width(), img.height()) {
+        let [r, g, b, a] = img.get_pixel(x, y).0;
+        if a < 128 {
+            continue;
+        }
+        
+        let groupe = groupes.entry([r >> 3, g >> 3, b >> 3]).or_insert((0, [0; 3]));
+        groupe.0 += 1;
+        groupe.1[0] += r as u64;
+        groupe.1[1] += g as u64;
+        groupe.1[2] += b as u64;
+    }
+    
+    groupes
+        .into_values()
+        .max_by_key(|(nombre, _)| *nombre)
+        .map(|(nombre, sommes)| sommes.map(|somme| (somme / nombre as u64) as u8))
+}
+
+/// Espace dans lequel est mesurée la distance entre deux couleurs
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EspaceCouleur {
+    /// Distance euclidienne sur les canaux rouge, vert, bleu
+    Rgb,
+    /// Cône teinte / saturation / valeur : les gris sont proches quelle que soit leur teinte
+    Hsv,
+    /// CIE L*a*b* (ΔE 1976), proche de la perception humaine
+    Lab,
+}
+
+impl EspaceCouleur {
+    fn depuis_nom(nom: Option<&str>) -> ResultatApplication<Self> {
+        match nom.unwrap_or("rgb").to_lowercase().as_str() {
+            "rgb" => Ok(EspaceCouleur::Rgb),
+            "hsv" => Ok(EspaceCouleur::Hsv),
+            "lab" => Ok(EspaceCouleur::Lab),
+            autre => Err(ErreurApplication::Validation {
+                message: format!("Espace de couleur inconnu : {}", autre),
+            }),
+        }
+    }
+    
+    /**
+     * Coordonnées d'une couleur dans l'espace, ramenées à l'échelle 0-255
+     * de la tolérance (1 ΔE vaut 2,55 en Lab)
+     */
+    fn coordonnees(self, [r, g, b]: [u8; 3]) -> [f32; 3] {
+        match self {
+            EspaceCouleur::Rgb => [r as f32, g as f32, b as f32],
+            EspaceCouleur::Hsv => {
+                let (teinte, saturation, valeur) = rgb_vers_hsv([r, g, b]);
+                let rayon = saturation * valeur * 255.0;
+                let angle = teinte.to_radians();
+                [rayon * angle.cos(), rayon * angle.sin(), valeur * 255.0]
+            }
+            EspaceCouleur::Lab => {
+                let lineaire = |canal: u8| {
+                    let canal = canal as f32 / 255.0;
+                    if canal <= 0.04045 { canal / 12.92 } else { ((canal + 0.055) / 1.055).powf(2.4) }
+                };
+                let (r, g, b) = (lineaire(r), lineaire(g), lineaire(b));
+                
+                // sRGB -> XYZ (illuminant D65), normalisé par le blanc de référence
+                let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+                let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+                
+                let f = |t: f32| if t > 0.008856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+                let (fx, fy, fz) = (f(x), f(y), f(z));
+                
+                [(116.0 * fy - 16.0) * 2.55, 500.0 * (fx - fy) * 2.55, 200.0 * (fy - fz) * 2.55]
+            }
+        }
+    }
+    
+    fn distance(self, cible: [f32; 3], couleur: [u8; 3]) -> f32 {
+        let coordonnees = self.coordonnees(couleur);
+        cible.iter()
+            .zip(coordonnees.iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/**
+ * Convertit une couleur en teinte (degrés), saturation et valeur (0 à 1)
+ */
+fn rgb_vers_hsv([r, g, b]: [u8; 3]) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let ecart = max - min;
+    
+    let teinte = if ecart == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / ecart).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / ecart + 2.0)
+    } else {
+        60.0 * ((r - g) / ecart + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { ecart / max };
+    
+    (teinte, saturation, max)
+}
+
+/**
+ * Remplace l'alpha des pixels semi-transparents par la moyenne de leur voisinage
+ * 
+ * Moyenne sur une fenêtre (2r+1)² calculée par sommes glissantes séparables :
+ * le coût ne dépend pas du rayon, et seule la couche alpha est copiée.
+ */
+fn adoucir_bords_transparence(img: &mut image::RgbaImage, rayon: u8) {
+    flouter_alpha(img, rayon, |alpha, moyenne| (alpha > 0 && alpha < 255).then_some(moyenne));
+}
+
+/**
+ * Fond le contour du sujet vers l'intérieur
+ * 
+ * L'alpha ne peut que diminuer : les pixels transparents, qui portent encore
+ * la couleur de l'ancien fond, ne réapparaissent pas en liseré.
+ */
+fn fondre_contour(img: &mut image::RgbaImage, rayon: u8) {
+    flouter_alpha(img, rayon, |alpha, moyenne| Some(alpha.min(moyenne)));
+}
+
+/**
+ * Flou moyenneur de la couche alpha, avec une fenêtre (2r+1)² tronquée aux bords
+ * 
+ * Les sommes horizontales de chaque ligne sont cumulées dans des sommes par
+ * colonne qui glissent d'une ligne à l'autre : O(largeur × hauteur) quel que
+ * soit le rayon, et une mémoire de quelques lignes en plus de la copie de l'alpha.
+ * 
+ * # Arguments
+ * * `choisir` - Reçoit l'alpha d'origine et la moyenne du voisinage, retourne
+ *   le nouvel alpha ou `None` pour laisser le pixel inchangé
+ */
+fn flouter_alpha(img: &mut image::RgbaImage, rayon: u8, choisir: impl Fn(u8, u8) -> Option<u8>) {
+    if rayon == 0 || img.width() == 0 || img.height() == 0 {
+        return;
+    }
+    
+    let (largeur, hauteur) = (img.width() as usize, img.height() as usize);
+    let rayon = rayon as usize;
+    let alphas: Vec<u8> = img.pixels().map(|pixel| pixel.0[3]).collect();
+    
+    let sommer_ligne = |y: usize, sommes: &mut [u32]| {
+        let ligne = &alphas[y * largeur..(y + 1) * largeur];
+        let mut somme: u32 = ligne[..=rayon.min(largeur - 1)].iter().map(|&alpha| alpha as u32).sum();
+        
+        for x in 0..largeur {
+            sommes[x] = somme;
+            if x + rayon + 1 < largeur {
+                somme += ligne[x + rayon + 1] as u32;
+            }
+            if x >= rayon {
+                somme -= ligne[x - rayon] as u32;
+            }
+        }
+    };
+    
+    let mut colonnes = vec![0u32; largeur];
+    let mut ligne = vec![0u32; largeur];
+    for y in 0..=rayon.min(hauteur - 1) {
+        sommer_ligne(y, &mut ligne);
+        colonnes.iter_mut().zip(&ligne).for_each(|(colonne, somme)| *colonne += somme);
+    }
+    
+    for y in 0..hauteur {
+        let hauteur_fenetre = (y + rayon).min(hauteur - 1) - y.saturating_sub(rayon) + 1;
+        
+        for x in 0..largeur {
+            let largeur_fenetre = (x + rayon).min(largeur - 1) - x.saturating_sub(rayon) + 1;
+            let moyenne = (colonnes[x] / (largeur_fenetre * hauteur_fenetre) as u32) as u8;
+            
+            if let Some(alpha) = choisir(alphas[y * largeur + x], moyenne) {
+                img.get_pixel_mut(x as u32, y as u32).0[3] = alpha;
+            }
+        }
+        
+        // Faire glisser la fenêtre d'une ligne vers le bas
+        if y + rayon + 1 < hauteur {
+            sommer_ligne(y + rayon + 1, &mut ligne);
+            colonnes.iter_mut().zip(&ligne).for_each(|(colonne, somme)| *colonne += somme);
+        }
+        if y >= rayon {
+            sommer_ligne(y - rayon, &mut ligne);
+            colonnes.iter_mut().zip(&ligne).for_each(|(colonne, somme)| *colonne -= somme);
+        }
+    }
+}
+
+/**
+ * Retire la couleur du fond mélangée aux pixels semi-transparents du contour
+ * 
+ * Un pixel de bord est vu comme le mélange C = α·F + (1 − α)·B du sujet F et
+ * du fond B : on reconstitue F, ce qui efface le halo de l'ancien fond.
+ */
+fn decontaminer_couleurs(img: &mut image::RgbaImage, couleur_fond: [u8; 3]) {
+    for pixel in img.pixels_mut() {
+        let alpha = pixel.0[3];
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+        
+        // Borne basse : les pixels presque transparents amplifieraient le bruit
+        let opacite = (alpha as f32 / 255.0).max(0.1);
+        for (canal, fond) in pixel.0.iter_mut().zip(couleur_fond) {
+            let sujet = (*canal as f32 - (1.0 - opacite) * fond as f32) / opacite;
+            *canal = sujet.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/**
+ * Charge une image en choisissant le décodeur d'après son contenu
+ * 
+ * `image::open` se fie à l'extension : un PNG nommé `.jpg` ou un fichier
+ * sans extension ne pourrait pas être ouvert. L'orientation EXIF est
+ * appliquée aux pixels, car l'image réencodée ne conservera pas la balise.
+ */
+fn charger_image(path: &Path) -> ResultatApplication<image::DynamicImage> {
+    if detecter_format_fichier(path).ok() == Some(ImageFormat::Avif) {
+        return Err(ErreurApplication::Validation {
+            message: "Format AVIF pris en charge en sortie uniquement : il ne peut pas être lu".to_string(),
+        });
+    }
+    
+    let img = image::io::Reader::open(path)
+        .and_then(|lecteur| lecteur.with_guessed_format())
+        .map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible de lire l'image : {}", e),
+        })?
+        .decode()
+        .map_err(|e| ErreurApplication::Donnees {
+            message: format!("Impossible de charger l'image : {}", e),
+        })?;
+    
+    Ok(appliquer_orientation(img, lire_orientation_exif(path).unwrap_or(1)))
+}
+
+fn lire_orientation_exif(path: &Path) -> Option<u16> {
+    let mut lecteur = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+    let exif = exif::Reader::new().read_from_container(&mut lecteur).ok()?;
+    
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|champ| champ.value.get_uint(0))
+        .map(|orientation| orientation as u16)
+}
+
+/**
+ * Applique une orientation EXIF (1 à 8) aux pixels
+ */
+fn appliquer_orientation(img: image::DynamicImage, orientation: u16) -> image::DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/**
+ * Chemin d'écriture d'un fichier redressé : dans `dossier_sortie`, ou l'original lui-même
+ */
+fn chemin_redressement(fichier: &FichierLot, dossier_sortie: Option<&Path>) -> PathBuf {
+    let path = fichier.source.as_path();
+    match dossier_sortie {
+        Some(dossier) => {
+            let extension = path.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default();
+            dossier.join(&fichier.sous_dossier).join(format!("{}{}", fichier.nom, extension))
+        }
+        None => path.to_path_buf(),
+    }
+}
+
+/**
+ * Indique si deux chemins désignent le même fichier existant (liens et `..` résolus)
+ */
+fn meme_fichier(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/**
+ * Redresse un fichier d'un lot, vers `dossier_sortie` ou à la place de l'original
+ * (exécuté hors du runtime asynchrone)
+ */
+fn redresser_fichier(fichier: &FichierLot, dossier_sortie: Option<&Path>, qualite: u8) -> IssueConversionLot {
+    let path = fichier.source.as_path();
+    let chemin = path.to_string_lossy().to_string();
+    let path_sortie = chemin_redressement(fichier, dossier_sortie);
+    let chemin_sortie = path_sortie.to_string_lossy().to_string();
+    let resultat = |succes: bool, format: &str, taille_avant: u64, taille_apres: u64, message: String| ResultatConversion {
+        succes,
+        fichier_origine: chemin.clone(),
+        fichier_sortie: chemin_sortie.clone(),
+        format_origine: format.to_string(),
+        format_sortie: format.to_string(),
+        taille_avant,
+        taille_apres,
+        reduction_pourcent: 0.0,
+        message,
+        qualite_utilisee: None,
+        largeur_sortie: 0,
+        hauteur_sortie: 0,
+    };
+    
+    let redressement = || -> ResultatApplication<IssueConversionLot> {
+        let format = detecter_format_fichier(path)?;
+        let orientation = lire_orientation_exif(path).unwrap_or(1);
+        
+        if orientation <= 1 || orientation > 8 {
+            return Ok(IssueConversionLot::Ignoree(resultat(false, nom_format(format), 0, 0, "Image déjà droite".to_string())));
+        }
+        
+        let origine = std::fs::read(path).map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible de lire le fichier d'origine : {}", e),
+        })?;
+        let img = charger_image(path)?;
+        
+        let mut sortie = encoder_image(&img, format, qualite)?;
+        
+        if format == ImageFormat::Jpeg {
+            if let Some(mut segment) = extraire_segment_exif_jpeg(&origine) {
+                reinitialiser_orientation_tiff(&mut segment[10..]);
+                let insertion = position_apres_app0(&sortie);
+                sortie.splice(insertion..insertion, segment);
+            }
+        }
+        
+        ecrire_fichier_atomique(&path_sortie, &sortie)?;
+        
+        Ok(IssueConversionLot::Convertie(ResultatConversion {
+            qualite_utilisee: qualite_reglable(format).then_some(qualite),
+            largeur_sortie: img.width(),
+            hauteur_sortie: img.height(),
+            ..resultat(
+                true,
+                nom_format(format),
+                origine.len() as u64,
+                sortie.len() as u64,
+                format!("Orientation {} appliquée aux pixels", orientation),
+            )
+        }))
+    };
+    
+    redressement().unwrap_or_else(|e| IssueConversionLot::Echec(resultat(false, "", 0, 0, e.to_string())))
+}
+
+fn cumuler_issue_lot(bilan: &mut ResultatConversionLot, issue: IssueConversionLot) {
+    match issue {
+        IssueConversionLot::Convertie(resultat) => {
+            bilan.fichiers_convertis += 1;
+            bilan.taille_totale_avant += resultat.taille_avant;
+            bilan.taille_totale_apres += resultat.taille_apres;
+            bilan.resultats.push(resultat);
+        }
+        IssueConversionLot::Ignoree(resultat) => {
+            bilan.fichiers_ignores += 1;
+            bilan.resultats.push(resultat);
+        }
+        IssueConversionLot::Echec(resultat) => {
+            error!("❌ {} : {}", resultat.fichier_origine, resultat.message);
+            bilan.fichiers_en_echec += 1;
+            bilan.resultats.push(resultat);
+        }
+    }
+    
+    if bilan.taille_totale_avant > 0 {
+        bilan.reduction_pourcent = ((bilan.taille_totale_avant as f32 - bilan.taille_totale_apres as f32)
+            / bilan.taille_totale_avant as f32) * 100.0;
+    }
+}
+
+/// Écart maximal entre canaux pour qu'un pixel compte comme gris (bruit de compression JPEG)
+const ECART_NIVEAUX_DE_GRIS: u8 = 8;
+
+/// Nombre de pixels au-delà duquel la palette est calculée sur un échantillon
+const PIXELS_ECHANTILLON_PALETTE: usize = 100_000;
+
+fn analyser_couleurs(img: &image::RgbaImage, nombre_couleurs: usize) -> AnalyseCouleurs {
+    let mut histogrammes = HistogrammesImage {
+        rouge: vec![0; 256],
+        vert: vec![0; 256],
+        bleu: vec![0; 256],
+        alpha: vec![0; 256],
+        luminosite: vec![0; 256],
+    };
+    let mut somme_luminosite = 0.0f64;
+    let mut niveaux_de_gris = true;
+    let mut opaques = Vec::new();
+    
+    for pixel in img.pixels() {
+        let [r, g, b, a] = pixel.0;
+        let luminosite = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        
+        histogrammes.rouge[r as usize] += 1;
+        histogrammes.vert[g as usize] += 1;
+        histogrammes.bleu[b as usize] += 1;
+        histogrammes.alpha[a as usize] += 1;
+        histogrammes.luminosite[luminosite.round() as usize] += 1;
+        somme_luminosite += luminosite as f64;
+        
+        // Les pixels presque invisibles gardent souvent une couleur arbitraire
+        if a >= 128 {
+            niveaux_de_gris &= r.max(g).max(b) - r.min(g).min(b) <= ECART_NIVEAUX_DE_GRIS;
+            opaques.push([r, g, b]);
+        }
+    }
+    
+    let total = (img.width() as u64 * img.height() as u64).max(1);
+    let transparents = total - histogrammes.alpha[255] as u64;
+    
+    let pas = opaques.len().div_ceil(PIXELS_ECHANTILLON_PALETTE).max(1);
+    let echantillon: Vec<[u8; 3]> = opaques.into_iter().step_by(pas).collect();
+    let taille_echantillon = echantillon.len().max(1) as f32;
+    let palette = palette_coupe_mediane(echantillon, nombre_couleurs)
+        .into_iter()
+        .map(|(rgb, nombre)| CouleurDominante {
+            hex: format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]),
+            rgb,
+            pourcentage: nombre as f32 / taille_echantillon * 100.0,
+        })
+        .collect();
+    
+    AnalyseCouleurs {
+        palette,
+        luminosite_moyenne: (somme_luminosite / total as f64) as f32,
+        alpha_utilise: transparents > 0,
+        pixels_transparents_pourcent: transparents as f32 / total as f32 * 100.0,
+        niveaux_de_gris,
+        couleur_fond_probable: detecter_couleur_bordure(img).map(|[r, g, b]| format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        histogrammes,
+    }
+}
+
+/**
+ * Réduit des pixels à `nombre` couleurs par coupe médiane
+ * 
+ * La boîte coupée à chaque étape est celle dont l'étendue sur son canal le plus
+ * large, pondérée par son nombre de pixels, est la plus grande. Retourne la
+ * couleur moyenne et le nombre de pixels de chaque boîte, par ordre décroissant.
+ */
+fn palette_coupe_mediane(pixels: Vec<[u8; 3]>, nombre: usize) -> Vec<([u8; 3], u32)> {
+    let canal_le_plus_large = |boite: &[[u8; 3]]| {
+        (0..3)
+            .map(|canal| {
+                let (min, max) = boite
+                    .iter()
+                    .fold((255u8, 0u8), |(min, max), pixel| (min.min(pixel[canal]), max.max(pixel[canal])));
+                (canal, max.saturating_sub(min))
+            })
+            .max_by_key(|&(_, etendue)| etendue)
+            .unwrap_or((0, 0))
+    };
+    
+    let mut boites = vec![pixels];
+    boites.retain(|boite| !boite.is_empty());
+    
+    while boites.len() < nombre {
+        let Some((indice, canal)) = boites
+            .iter()
+            .enumerate()
+            .map(|(indice, boite)| {
+                let (canal, etendue) = canal_le_plus_large(boite);
+                (indice, canal, etendue as u64 * boite.len() as u64)
+            })
+            .filter(|&(_, _, poids)| poids > 0)
+            .max_by_key(|&(_, _, poids)| poids)
+            .map(|(indice, canal, _)| (indice, canal))
+        else {
+            break;
+        };
+        
+        let mut boite = boites.swap_remove(indice);
+        boite.sort_unstable_by_key(|pixel| pixel[canal]);
+        
+        // Les pixels de même valeur que la médiane restent dans la même boîte
+        let mediane = boite[boite.len() / 2][canal];
+        let coupure = match boite.partition_point(|pixel| pixel[canal] < mediane) {
+            0 => boite.partition_point(|pixel| pixel[canal] <= mediane),
+            coupure => coupure,
+        };
+        let seconde = boite.split_off(coupure);
+        boites.push(boite);
+        boites.push(seconde);
+    }
+    
+    let mut palette: Vec<([u8; 3], u32)> = boites
+        .iter()
+        .map(|boite| {
+            let sommes = boite.iter().fold([0u64; 3], |mut sommes, pixel| {
+                for (somme, &valeur) in sommes.iter_mut().zip(pixel) {
+                    *somme += valeur as u64;
+                }
+                sommes
+            });
+            (sommes.map(|somme| (somme / boite.len() as u64) as u8), boite.len() as u32)
+        })
+        .collect();
+    
+    palette.sort_by_key(|&(_, nombre)| std::cmp::Reverse(nombre));
+    palette
+}
+
+async fn rechercher_doublons_interne(entrees: &[String], options: &OptionsDoublons) -> ResultatApplication<ResultatDoublons> {
+    let debut_traitement = std::time::Instant::now();
+    
+    let algorithme = options.algorithme.as_deref().unwrap_or("phash").to_lowercase();
+    let choisir: fn(&EmpreintesImage) -> u64 = match algorithme.as_str() {
+        "ahash" => |empreintes| empreintes.ahash,
+        "dhash" => |empreintes| empreintes.dhash,
+        "phash" => |empreintes| empreintes.phash,
+        autre => {
+            return Err(ErreurApplication::Validation {
+                message: format!("Algorithme d'empreinte inconnu : {}", autre),
+            })
+        }
+    };
+    let seuil = options.seuil_distance.unwrap_or(5);
+    
+    let fichiers: Vec<PathBuf> = lister_images_lot(entrees, options.recursif)
+        .await?
+        .into_iter()
+        .map(|fichier| fichier.source)
+        .collect();
+    let chemin_cache = options
+        .chemin_cache
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::cache_dir().map(|dossier| dossier.join("mon-application").join("empreintes_images.json")));
+    // Un cache absent est normal ; illisible ou corrompu, il est signalé puis reconstruit
+    let cache: HashMap<String, EmpreintesImage> = match &chemin_cache {
+        Some(chemin) => match tokio::fs::read(chemin).await {
+            Ok(contenu) => serde_json::from_slice(&contenu).unwrap_or_else(|e| {
+                warn!("⚠️  Cache des empreintes corrompu, il sera reconstruit ({}) : {}", chemin.display(), e);
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!("⚠️  Cache des empreintes illisible, il sera reconstruit ({}) : {}", chemin.display(), e);
+                HashMap::new()
+            }
+        },
+        None => HashMap::new(),
+    };
+    
+    let semaphore = Arc::new(Semaphore::new(options.concurrence_max.unwrap_or(4).max(1)));
+    let cache = Arc::new(cache);
+    let mut taches = Vec::with_capacity(fichiers.len());
+    
+    for fichier in fichiers {
+        let semaphore = Arc::clone(&semaphore);
+        let cache = Arc::clone(&cache);
+        
+        taches.push(tokio::spawn(async move {
+            let _permis = semaphore.acquire_owned().await;
+            let chemin = fichier.to_string_lossy().to_string();
+            let resultat = tokio::task::spawn_blocking(move || calculer_empreintes_fichier(&fichier, &cache))
+                .await
+                .unwrap_or_else(|e| Err(ErreurApplication::Systeme {
+                    message: format!("Tâche interrompue : {}", e),
+                }));
+            (chemin, resultat)
+        }));
+    }
+    
+    let mut resultat = ResultatDoublons::default();
+    let mut empreintes = Vec::new();
+    let mut nouveau_cache = HashMap::new();
+    
+    for tache in taches {
+        let (chemin, issue) = tache.await.map_err(|e| ErreurApplication::Systeme {
+            message: format!("Tâche interrompue : {}", e),
+        })?;
+        match issue {
+            Ok((empreintes_fichier, depuis_cache)) => {
+                resultat.images_analysees += 1;
+                resultat.images_en_cache += depuis_cache as u32;
+                nouveau_cache.insert(chemin.clone(), empreintes_fichier.clone());
+                empreintes.push((chemin, empreintes_fichier));
+            }
+            Err(e) => resultat.echecs.push(format!("{} : {}", chemin, e)),
+        }
+    }
+    
+    // Le cache garde aussi les images des autres dossiers déjà analysés, tant qu'elles existent
+    if let Some(chemin) = &chemin_cache {
+        let mut cache_complet = (*cache).clone();
+        cache_complet.retain(|fichier, _| Path::new(fichier).exists());
+        cache_complet.extend(nouveau_cache);
+        if let Some(parent) = chemin.parent() {
+            creer_dossier_recursif(&parent.to_string_lossy()).await?;
+        }
+        let contenu = serde_json::to_vec(&cache_complet).map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible de sérialiser le cache : {}", e),
+        })?;
+        if let Err(e) = tokio::fs::write(chemin, contenu).await {
+            error!("❌ Impossible d'écrire le cache des empreintes : {}", e);
+        }
+    }
+    
+    resultat.groupes = grouper_doublons(&empreintes, choisir, seuil);
+    resultat.temps_total_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    Ok(resultat)
+}
+
+/**
+ * Calcule les empreintes d'un fichier, ou les reprend du cache s'il n'a pas changé
+ * 
+ * Retourne aussi `true` quand les empreintes viennent du cache.
+ */
+fn calculer_empreintes_fichier(
+    path: &Path,
+    cache: &HashMap<String, EmpreintesImage>
+) -> ResultatApplication<(EmpreintesImage, bool)> {
+    let metadata = std::fs::metadata(path).map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible de lire le fichier : {}", e),
+    })?;
+    let modifie_le = metadata
+        .modified()
+        .ok()
+        .and_then(|date| date.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duree| duree.as_secs())
+        .unwrap_or(0);
+    
+    if let Some(empreintes) = cache.get(&*path.to_string_lossy()) {
+        if empreintes.taille_octets == metadata.len() && empreintes.modifie_le == modifie_le {
+            return Ok((empreintes.clone(), true));
+        }
+    }
+    
+    let img = charger_image(path)?;
+    let (ahash, dhash, phash) = calculer_empreintes(&img);
+    
+    Ok((
+        EmpreintesImage {
+            taille_octets: metadata.len(),
+            modifie_le,
+            largeur: img.width(),
+            hauteur: img.height(),
+            format: detecter_format_image(path)?.to_string(),
+            ahash,
+            dhash,
+            phash,
+        },
+        false,
+    ))
+}
+
+/**
+ * Calcule les empreintes perceptuelles aHash, dHash et pHash (64 bits chacune)
+ * 
+ * - aHash : pixels d'une miniature 8×8 plus clairs que la moyenne
+ * - dHash : pixels d'une miniature 9×8 plus clairs que leur voisin de droite
+ * - pHash : coefficients basse fréquence de la DCT d'une miniature 32×32 supérieurs à leur médiane
+ */
+fn calculer_empreintes(img: &image::DynamicImage) -> (u64, u64, u64) {
+    use image::imageops::FilterType;
+    
+    // Une première réduction rapide évite de filtrer toute la photo trois fois
+    let gris = image::DynamicImage::ImageLuma8(img.thumbnail(128, 128).to_luma8());
+    let miniature = |largeur: u32, hauteur: u32| gris.resize_exact(largeur, hauteur, FilterType::Triangle).to_luma8().into_raw();
+    let vers_bits = |bits: &mut dyn Iterator<Item = bool>| bits.fold(0u64, |empreinte, bit| (empreinte << 1) | bit as u64);
+    
+    let pixels = miniature(8, 8);
+    let moyenne = pixels.iter().map(|&p| p as u32).sum::<u32>() / 64;
+    let ahash = vers_bits(&mut pixels.iter().map(|&p| p as u32 > moyenne));
+    
+    let pixels = miniature(9, 8);
+    let dhash = vers_bits(&mut pixels.chunks(9).flat_map(|ligne| ligne.windows(2).map(|paire| paire[0] > paire[1])));
+    
+    let pixels: Vec<f32> = miniature(32, 32).into_iter().map(f32::from).collect();
+    let cosinus: Vec<f32> = (0..8 * 32)
+        .map(|i| ((2 * (i % 32) + 1) as f32 * (i / 32) as f32 * std::f32::consts::PI / 64.0).cos())
+        .collect();
+    let mut coefficients = [0.0f32; 64];
+    for (indice, coefficient) in coefficients.iter_mut().enumerate() {
+        let (v, u) = (indice / 8, indice % 8);
+        *coefficient = (0..32 * 32)
+            .map(|p| pixels[p] * cosinus[v * 32 + p / 32] * cosinus[u * 32 + p % 32])
+            .sum();
+    }
+    // Le coefficient continu (luminosité moyenne) fausserait la médiane
+    let mut tries = coefficients[1..].to_vec();
+    tries.sort_by(|a, b| a.total_cmp(b));
+    let mediane = tries[tries.len() / 2];
+    let phash = vers_bits(&mut coefficients.iter().map(|&c| c > mediane));
+    
+    (ahash, dhash, phash)
+}
+
+/**
+ * Regroupe les images dont les empreintes sont à moins de `seuil` bits d'écart
+ * 
+ * Les groupes sont transitifs : A proche de B et B proche de C les réunit tous trois.
+ */
+fn grouper_doublons(
+    empreintes: &[(String, EmpreintesImage)],
+    choisir: fn(&EmpreintesImage) -> u64,
+    seuil: u32
+) -> Vec<GroupeDoublons> {
+    let mut parents: Vec<usize> = (0..empreintes.len()).collect();
+    fn racine(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    
+    for i in 0..empreintes.len() {
+        for j in i + 1..empreintes.len() {
+            if (choisir(&empreintes[i].1) ^ choisir(&empreintes[j].1)).count_ones() <= seuil {
+                let (a, b) = (racine(&mut parents, i), racine(&mut parents, j));
+                parents[a] = b;
+            }
+        }
+    }
+    
+    let mut membres: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..empreintes.len() {
+        membres.entry(racine(&mut parents, i)).or_default().push(i);
+    }
+    
+    let mut groupes: Vec<GroupeDoublons> = membres
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+        .map(|mut indices| {
+            indices.sort_by_key(|&i| {
+                let e = &empreintes[i].1;
+                std::cmp::Reverse((e.largeur as u64 * e.hauteur as u64, e.taille_octets))
+            });
+            let reference = choisir(&empreintes[indices[0]].1);
+            GroupeDoublons {
+                images: indices
+                    .into_iter()
+                    .map(|i| {
+                        let (chemin, e) = &empreintes[i];
+                        ImageDoublon {
+                            chemin: chemin.clone(),
+                            largeur: e.largeur,
+                            hauteur: e.hauteur,
+                            taille_octets: e.taille_octets,
+                            format: e.format.clone(),
+                            empreinte: format!("{:016x}", choisir(e)),
+                            distance: (choisir(e) ^ reference).count_ones(),
+                        }
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    
+    groupes.sort_by(|a, b| b.images.len().cmp(&a.images.len()).then_with(|| a.images[0].chemin.cmp(&b.images[0].chemin)));
+    groupes
+}
+
+/**
+ * Durées des images d'un GIF ou d'un WebP animé, lues dans la structure du fichier
+ * 
+ * Aucune image n'est décodée : seules les extensions de contrôle graphique
+ * (GIF) et les en-têtes ANMF (WebP) sont parcourus. Un GIF tronqué garde
+ * les images lues jusque-là.
+ * 
+ * # Retour
+ * * `Option<Vec<u32>>` - Une durée en millisecondes par image, None hors GIF et WebP
+ */
+fn lire_delais_trames(donnees: &[u8], format: ImageFormat) -> Option<Vec<u32>> {
+    let mut delais = Vec::new();
+    
+    match format {
+        ImageFormat::Gif => {
+            if !donnees.starts_with(b"GIF8") {
+                return None;
+            }
+            let taille_palette = |drapeaux: u8| if drapeaux & 0x80 != 0 { 3 << ((drapeaux & 0x07) + 1) } else { 0 };
+            // Saute une suite de sous-blocs terminée par un bloc vide
+            let sauter_sous_blocs = |mut position: usize| -> Option<usize> {
+                loop {
+                    let taille = *donnees.get(position)? as usize;
+                    position += 1 + taille;
+                    if taille == 0 {
+                        return Some(position);
+                    }
+                }
+            };
+            
+            let mut position = 13 + taille_palette(*donnees.get(10)?);
+            let mut delai = 0;
+            loop {
+                let suivant = match donnees.get(position) {
+                    Some(0x21) => {
+                        if donnees.get(position + 1) == Some(&0xF9) {
+                            if let Some(octets) = donnees.get(position + 4..position + 6) {
+                                delai = u16::from_le_bytes([octets[0], octets[1]]) as u32 * 10;
+                            }
+                        }
+                        sauter_sous_blocs(position + 2)
+                    }
+                    Some(0x2C) => {
+                        delais.push(delai);
+                        delai = 0;
+                        // Descripteur, palette locale, taille des codes LZW puis données
+                        donnees.get(position + 9)
+                            .and_then(|&drapeaux| sauter_sous_blocs(position + 11 + taille_palette(drapeaux)))
+                    }
+                    _ => None,
+                };
+                match suivant {
+                    Some(suivant) => position = suivant,
+                    None => break,
+                }
+            }
+        }
+        ImageFormat::WebP => {
+            if donnees.get(8..12)? != b"WEBP" {
+                return None;
+            }
+            let mut position = 12;
+            while let Some(entete) = donnees.get(position..position + 8) {
+                let taille = u32::from_le_bytes([entete[4], entete[5], entete[6], entete[7]]) as usize;
+                // Durée sur 24 bits, après la position et les dimensions de l'image
+                if &entete[..4] == b"ANMF" {
+                    let duree = donnees.get(position + 20..position + 23)?;
+                    delais.push(u32::from_le_bytes([duree[0], duree[1], duree[2], 0]));
+                }
+                position += 8 + taille + (taille & 1);
+            }
+            if delais.is_empty() {
+                delais.push(0);
+            }
+        }
+        _ => return None,
+    }
+    
+    (!delais.is_empty()).then_some(delais)
+}
+
+/// Nombre d'images d'un fichier (1 hors animation, ou si sa structure est illisible)
+fn compter_trames(path: &Path) -> usize {
+    std::fs::read(path)
+        .ok()
+        .zip(detecter_format_fichier(path).ok())
+        .and_then(|(donnees, format)| lire_delais_trames(&donnees, format))
+        .map_or(1, |delais| delais.len())
+}
+
+/// Mention ajoutée au message d'une conversion qui n'a gardé que la première image d'une animation
+fn avertissement_animation(nombre_trames: usize) -> String {
+    if nombre_trames > 1 {
+        format!(" ; animation de {} images : seule la première a été convertie", nombre_trames)
+    } else {
+        String::new()
+    }
+}
+
+/**
+ * Lit toutes les images d'un GIF ou d'un WebP animé
+ * 
+ * Les autres formats, et les WebP fixes, donnent une seule image de durée nulle.
+ */
+fn lire_trames(path: &Path) -> ResultatApplication<Vec<Trame>> {
+    use image::AnimationDecoder;
+    
+    let ouvrir = || std::fs::File::open(path).map(std::io::BufReader::new).map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible de lire l'image : {}", e),
+    });
+    let erreur_decodage = |e: image::ImageError| ErreurApplication::Systeme {
+        message: format!("Impossible de décoder l'animation : {}", e),
+    };
+    
+    let trames = match detecter_format_fichier(path)? {
+        ImageFormat::Gif => image::codecs::gif::GifDecoder::new(ouvrir()?)
+            .map_err(erreur_decodage)?
+            .into_frames()
+            .collect_frames()
+            .map_err(erreur_decodage)?,
+        ImageFormat::WebP => {
+            let decodeur = image::codecs::webp::WebPDecoder::new(ouvrir()?).map_err(erreur_decodage)?;
+            if decodeur.has_animation() {
+                decodeur.into_frames().collect_frames().map_err(erreur_decodage)?
+            } else {
+                Vec::new()
+            }
+        }
+        _ => Vec::new(),
+    };
+    
+    if trames.is_empty() {
+        return Ok(vec![Trame { image: charger_image(path)?.to_rgba8(), delai_ms: 0 }]);
+    }
+    
+    Ok(trames
+        .into_iter()
+        .map(|trame| {
+            let (numerateur, denominateur) = trame.delay().numer_denom_ms();
+            Trame {
+                delai_ms: numerateur.checked_div(denominateur).unwrap_or(0),
+                image: trame.into_buffer(),
+            }
+        })
+        .collect())
+}
+
+async fn extraire_trames_interne(chemin_entree: &str, dossier_sortie: &str) -> ResultatApplication<ResultatTrames> {
+    if !verifier_fichier_existe(chemin_entree).await {
+        return Err(ErreurApplication::Validation {
+            message: format!("Le fichier source n'existe pas : {}", chemin_entree),
+        });
+    }
+    
+    let path_entree = Path::new(chemin_entree);
+    let trames = lire_trames(path_entree)?;
+    creer_dossier_recursif(dossier_sortie).await?;
+    
+    let nom = path_entree.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let mut resultat = ResultatTrames {
+        fichiers: Vec::with_capacity(trames.len()),
+        delais_ms: Vec::with_capacity(trames.len()),
+        largeur: trames[0].image.width(),
+        hauteur: trames[0].image.height(),
+    };
+    
+    for (indice, trame) in trames.iter().enumerate() {
+        let chemin = Path::new(dossier_sortie).join(format!("{}_{:04}.png", nom, indice + 1));
+        let octets = encoder_image(&image::DynamicImage::ImageRgba8(trame.image.clone()), ImageFormat::Png, 100)?;
+        tokio::fs::write(&chemin, octets).await.map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible d'écrire le fichier : {}", e),
+        })?;
+        
+        resultat.fichiers.push(chemin.to_string_lossy().to_string());
+        resultat.delais_ms.push(trame.delai_ms);
+    }
+    
+    Ok(resultat)
+}
+
+async fn creer_animation_interne(
+    trames: &[TrameAnimation],
+    chemin_sortie: &str,
+    options: &OptionsAnimation
+) -> ResultatApplication<ResultatConversion> {
+    if trames.is_empty() {
+        return Err(ErreurApplication::Validation {
+            message: "Aucune image à animer".to_string(),
+        });
+    }
+    
+    let mut images = Vec::with_capacity(trames.len());
+    let mut taille_avant = 0;
+    
+    for trame in trames {
+        if !verifier_fichier_existe(&trame.chemin).await {
+            return Err(ErreurApplication::Validation {
+                message: format!("Le fichier source n'existe pas : {}", trame.chemin),
+            });
+        }
+        taille_avant += tokio::fs::metadata(&trame.chemin).await.map(|metadata| metadata.len()).unwrap_or(0);
+        
+        let img = charger_image(Path::new(&trame.chemin))?;
+        let image = match images.first() {
+            Some(Trame { image: premiere, .. }) if (img.width(), img.height()) != premiere.dimensions() => {
+                let (largeur, hauteur) = premiere.dimensions();
+                let ajustee = img.resize(largeur, hauteur, image::imageops::FilterType::Lanczos3).to_rgba8();
+                let mut canevas = image::RgbaImage::new(largeur, hauteur);
+                superposer(
+                    &mut canevas,
+                    &ajustee,
+                    ((largeur - ajustee.width()) / 2) as i64,
+                    ((hauteur - ajustee.height()) / 2) as i64,
+                );
+                canevas
+            }
+            _ => img.to_rgba8(),
+        };
+        images.push(Trame { image, delai_ms: trame.delai_ms });
+    }
+    
+    ecrire_animation(&images, chemin_sortie, options, &trames[0].chemin, "Images", taille_avant).await
+}
+
+async fn ecrire_animation(
+    trames: &[Trame],
+    chemin_sortie: &str,
+    options: &OptionsAnimation,
+    fichier_origine: &str,
+    format_origine: &str,
+    taille_avant: u64
+) -> ResultatApplication<ResultatConversion> {
+    let format_sortie = ImageFormat::from_path(chemin_sortie).map_err(|_| ErreurApplication::Validation {
+        message: format!("Format de sortie non reconnu : {}", chemin_sortie),
+    })?;
+    let qualite = options.qualite.unwrap_or(100).clamp(1, 100);
+    
+    let octets = encoder_animation(trames, format_sortie, qualite, options.boucles.unwrap_or(0))?;
+    
+    if let Some(parent) = Path::new(chemin_sortie).parent() {
+        creer_dossier_recursif(&parent.to_string_lossy()).await?;
+    }
+    tokio::fs::write(chemin_sortie, &octets).await.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible d'écrire le fichier : {}", e),
+    })?;
+    
+    let taille_apres = octets.len() as u64;
+    let reduction_pourcent = if taille_avant > 0 {
+        ((taille_avant as f32 - taille_apres as f32) / taille_avant as f32) * 100.0
+    } else {
+        0.0
+    };
+    let duree_ms: u32 = trames.iter().map(|trame| trame.delai_ms).sum();
+    
+    Ok(ResultatConversion {
+        succes: true,
+        fichier_origine: fichier_origine.to_string(),
+        fichier_sortie: chemin_sortie.to_string(),
+        format_origine: format_origine.to_string(),
+        format_sortie: nom_format(format_sortie).to_string(),
+        taille_avant,
+        taille_apres,
+        reduction_pourcent,
+        message: format!(
+            "Animation {} de {} image(s), {:.1} s ({:.1}% de réduction)",
+            nom_format(format_sortie),
+            trames.len(),
+            duree_ms as f32 / 1000.0,
+            reduction_pourcent
+        ),
+        qualite_utilisee: (format_sortie == ImageFormat::WebP).then_some(qualite),
+        largeur_sortie: trames[0].image.width(),
+        hauteur_sortie: trames[0].image.height(),
+    })
+}
+
+fn encoder_animation(trames: &[Trame], format: ImageFormat, qualite: u8, boucles: u16) -> ResultatApplication<Vec<u8>> {
+    let erreur_encodage = |e: image::ImageError| ErreurApplication::Systeme {
+        message: format!("Erreur d'encodage de l'animation : {}", e),
+    };
+    
+    match format {
+        ImageFormat::Gif => {
+            let mut sortie = Vec::new();
+            {
+                let mut encodeur = image::codecs::gif::GifEncoder::new_with_speed(&mut sortie, 10);
+                encodeur
+                    .set_repeat(if boucles == 0 {
+                        image::codecs::gif::Repeat::Infinite
+                    } else {
+                        image::codecs::gif::Repeat::Finite(boucles)
+                    })
+                    .map_err(erreur_encodage)?;
+                encodeur
+                    .encode_frames(trames.iter().map(|trame| {
+                        image::Frame::from_parts(trame.image.clone(), 0, 0, image::Delay::from_numer_denom_ms(trame.delai_ms, 1))
+                    }))
+                    .map_err(erreur_encodage)?;
+            }
+            Ok(sortie)
+        }
+        ImageFormat::WebP => encoder_webp_anime(trames, qualite, boucles),
+        autre => Err(ErreurApplication::Validation {
+            message: format!("Animation possible en GIF ou WebP uniquement (format demandé : {})", nom_format(autre)),
+        }),
+    }
+}
+
+/**
+ * Assemble un WebP animé : chunks VP8X et ANIM, puis un chunk ANMF par image
+ * 
+ * Chaque image est encodée seule par libwebp puis placée dans son ANMF. L'encodeur
+ * d'animation de libwebp, lui, ne permet pas de fixer la durée de la dernière image.
+ */
+fn encoder_webp_anime(trames: &[Trame], qualite: u8, boucles: u16) -> ResultatApplication<Vec<u8>> {
+    let (largeur, hauteur) = trames[0].image.dimensions();
+    let octets_24_bits = |valeur: u32| valeur.min(0xFF_FFFF).to_le_bytes()[..3].to_vec();
+    
+    let transparente = trames.iter().any(|trame| trame.image.pixels().any(|pixel| pixel.0[3] < 255));
+    let mut vp8x = vec![if transparente { 0x12 } else { 0x02 }, 0, 0, 0];
+    vp8x.extend(octets_24_bits(largeur - 1));
+    vp8x.extend(octets_24_bits(hauteur - 1));
+    
+    // Couleur de fond transparente, puis nombre de boucles
+    let mut anim = vec![0, 0, 0, 0];
+    anim.extend(boucles.to_le_bytes());
+    
+    let mut corps = Vec::new();
+    ajouter_chunk_riff(&mut corps, b"VP8X", &vp8x);
+    ajouter_chunk_riff(&mut corps, b"ANIM", &anim);
+    
+    for trame in trames {
+        let webp = encoder_webp(&image::DynamicImage::ImageRgba8(trame.image.clone()), qualite)?;
+        
+        // Position (0, 0), dimensions, durée, puis ni fusion ni effacement
+        let mut anmf = vec![0; 6];
+        anmf.extend(octets_24_bits(largeur - 1));
+        anmf.extend(octets_24_bits(hauteur - 1));
+        anmf.extend(octets_24_bits(trame.delai_ms));
+        anmf.push(0x02);
+        
+        // Chunks de l'image seule (ALPH, VP8 ou VP8L), sans son propre VP8X
+        let mut position = 12;
+        while position + 8 <= webp.len() {
+            let taille = u32::from_le_bytes([webp[position + 4], webp[position + 5], webp[position + 6], webp[position + 7]]) as usize;
+            let fin = (position + 8 + taille + taille % 2).min(webp.len());
+            if &webp[position..position + 4] != b"VP8X" {
+                anmf.extend_from_slice(&webp[position..fin]);
+            }
+            position = fin;
+        }
+        
+        ajouter_chunk_riff(&mut corps, b"ANMF", &anmf);
+    }
+    
+    let mut sortie = b"RIFF".to_vec();
+    sortie.extend(((corps.len() + 4) as u32).to_le_bytes());
+    sortie.extend(b"WEBP");
+    sortie.extend(corps);
+    Ok(sortie)
+}
+
+fn ajouter_chunk_riff(sortie: &mut Vec<u8>, type_chunk: &[u8; 4], donnees: &[u8]) {
+    sortie.extend(type_chunk);
+    sortie.extend((donnees.len() as u32).to_le_bytes());
+    sortie.extend(donnees);
+    if donnees.len() % 2 == 1 {
+        sortie.push(0);
+    }
+}
+
+async fn assembler_images_interne(
+    chemins: &[String],
+    chemin_sortie: &str,
+    options: &OptionsAssemblage
+) -> ResultatApplication<ResultatAssemblage> {
+    if chemins.is_empty() {
+        return Err(ErreurApplication::Validation {
+            message: "Aucune image à assembler".to_string(),
+        });
+    }
+    
+    let format_sortie = ImageFormat::from_path(chemin_sortie).map_err(|_| ErreurApplication::Validation {
+        message: format!("Format de sortie non reconnu : {}", chemin_sortie),
+    })?;
+    let sprites = matches!(options.disposition, DispositionAssemblage::Sprites { .. });
+    let espacement = options.espacement.unwrap_or(if sprites { 0 } else { 10 });
+    let fond = parser_couleur_rgba(options.couleur_fond.as_deref().unwrap_or(if sprites { "transparent" } else { "#ffffff" }))?;
+    if fond[3] < 255 && !gere_transparence(format_sortie) {
+        return Err(ErreurApplication::Validation {
+            message: format!("Le format {} ne gère pas la transparence : choisissez une couleur de fond", nom_format(format_sortie)),
+        });
+    }
+    
+    let noms: Vec<String> = chemins
+        .iter()
+        .map(|chemin| Path::new(chemin).file_stem().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    let colonnes = match &options.disposition {
+        DispositionAssemblage::PlancheContact { colonnes, .. } | DispositionAssemblage::Collage { colonnes, .. } => *colonnes,
+        DispositionAssemblage::Sprites { colonnes, .. } => colonnes.unwrap_or_else(|| (chemins.len() as f32).sqrt().ceil() as u32),
+    };
+    
+    // Chaque image devient une tuile, placée ensuite ligne par ligne ; les
+    // sources sont chargées une à une pour n'en garder qu'une en pleine résolution
+    let mut tuiles: Vec<image::RgbaImage> = Vec::with_capacity(chemins.len());
+    for (chemin, nom) in chemins.iter().zip(&noms) {
+        if !verifier_fichier_existe(chemin).await {
+            return Err(ErreurApplication::Validation {
+                message: format!("Le fichier source n'existe pas : {}", chemin),
+            });
+        }
+        let img = charger_image(Path::new(chemin))?;
+        
+        let tuile = match &options.disposition {
+            DispositionAssemblage::PlancheContact { taille_vignette, legendes, .. } => {
+                let cote = (*taille_vignette).max(1);
+                let hauteur_legende = if *legendes { (cote / 8).max(10) } else { 0 };
+                let mut tuile = image::RgbaImage::new(cote, cote + hauteur_legende);
+                let vignette = img.resize(cote, cote, image::imageops::FilterType::Lanczos3).to_rgba8();
+                superposer(&mut tuile, &vignette, ((cote - vignette.width()) / 2) as i64, ((cote - vignette.height()) / 2) as i64);
+                if *legendes && !nom.trim().is_empty() {
+                    let legende = rendre_legende(nom, cote, hauteur_legende, fond)?;
+                    superposer(&mut tuile, &legende, ((cote - legende.width()) / 2) as i64, cote as i64);
+                }
+                tuile
+            }
+            DispositionAssemblage::Collage { hauteur_ligne, .. } => {
+                let hauteur = (*hauteur_ligne).max(1);
+                let largeur = ((img.width() as f32 * hauteur as f32 / img.height() as f32).round() as u32).max(1);
+                img.resize_exact(largeur, hauteur, image::imageops::FilterType::Lanczos3).to_rgba8()
+            }
+            DispositionAssemblage::Sprites { .. } => img.to_rgba8(),
+        };
+        tuiles.push(tuile);
+    }
+    
+    let tailles: Vec<(u32, u32)> = tuiles.iter().map(|tuile| tuile.dimensions()).collect();
+    let (emplacements, largeur, hauteur) = disposer_en_lignes(&tailles, colonnes.max(1), espacement);
+    
+    let mut canevas = image::RgbaImage::from_pixel(largeur, hauteur, image::Rgba(fond));
+    for (tuile, &(x, y)) in tuiles.iter().zip(&emplacements) {
+        superposer(&mut canevas, tuile, x as i64, y as i64);
+    }
+    
+    let octets = encoder_image(&image::DynamicImage::ImageRgba8(canevas), format_sortie, options.qualite.unwrap_or(90).clamp(1, 100))?;
+    if let Some(parent) = Path::new(chemin_sortie).parent() {
+        creer_dossier_recursif(&parent.to_string_lossy()).await?;
+    }
+    tokio::fs::write(chemin_sortie, &octets).await.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible d'écrire le fichier : {}", e),
+    })?;
+    
+    let positions: Vec<PositionImage> = chemins
+        .iter()
+        .zip(noms)
+        .zip(emplacements.iter().zip(&tailles))
+        .map(|((chemin, nom), (&(x, y), &(largeur, hauteur)))| PositionImage { chemin: chemin.clone(), nom, x, y, largeur, hauteur })
+        .collect();
+    
+    let mut resultat = ResultatAssemblage {
+        succes: true,
+        fichier_sortie: chemin_sortie.to_string(),
+        format_sortie: nom_format(format_sortie).to_string(),
+        largeur,
+        hauteur,
+        taille_octets: octets.len() as u64,
+        positions,
+        fichier_json: None,
+        fichier_css: None,
+        message: format!("{} image(s) assemblée(s) sur {}×{} px", chemins.len(), largeur, hauteur),
+    };
+    
+    if let DispositionAssemblage::Sprites { prefixe_classe, .. } = &options.disposition {
+        let path_sortie = Path::new(chemin_sortie);
+        let nom_planche = path_sortie.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let prefixe = prefixe_classe.as_deref().unwrap_or("sprite");
+        let classes = classes_sprites(&resultat.positions, prefixe);
+        let json = serde_json::json!({
+            "image": nom_planche,
+            "largeur": largeur,
+            "hauteur": hauteur,
+            "sprites": resultat.positions.iter().zip(&classes).map(|(position, classe)| serde_json::json!({
+                "nom": position.nom,
+                "classe": classe,
+                "x": position.x,
+                "y": position.y,
+                "largeur": position.largeur,
+                "hauteur": position.hauteur,
+            })).collect::<Vec<_>>(),
+        });
+        let css = generer_css_sprites(&resultat.positions, &classes, &nom_planche, prefixe);
+        
+        for (extension, contenu) in [("json", serde_json::to_string_pretty(&json).unwrap_or_default()), ("css", css)] {
+            let chemin = path_sortie.with_extension(extension);
+            tokio::fs::write(&chemin, contenu).await.map_err(|e| ErreurApplication::Systeme {
+                message: format!("Impossible d'écrire le fichier : {}", e),
+            })?;
+            let chemin = Some(chemin.to_string_lossy().to_string());
+            if extension == "json" {
+                resultat.fichier_json = chemin;
+            } else {
+                resultat.fichier_css = chemin;
+            }
+        }
+    }
+    
+    Ok(resultat)
+}
+
+/**
+ * Place des rectangles ligne par ligne, `colonnes` par ligne
+ * 
+ * Chaque ligne prend la hauteur de son plus grand élément. Retourne le coin
+ * supérieur gauche de chaque rectangle et la taille totale du canevas.
+ */
+fn disposer_en_lignes(tailles: &[(u32, u32)], colonnes: u32, espacement: u32) -> (Vec<(u32, u32)>, u32, u32) {
+    let mut emplacements = Vec::with_capacity(tailles.len());
+    let (mut largeur, mut y) = (0, espacement);
+    
+    for ligne in tailles.chunks(colonnes as usize) {
+        let mut x = espacement;
+        for &(largeur_element, _) in ligne {
+            emplacements.push((x, y));
+            x += largeur_element + espacement;
+        }
+        largeur = largeur.max(x);
+        y += ligne.iter().map(|&(_, hauteur)| hauteur).max().unwrap_or(0) + espacement;
+    }
+    
+    (emplacements, largeur.max(1), y.max(1))
+}
+
+/**
+ * Rend une légende tenant dans `largeur` × `hauteur`, contrastée avec le fond
+ */
+fn rendre_legende(texte: &str, largeur: u32, hauteur: u32, fond: [u8; 4]) -> ResultatApplication<image::RgbaImage> {
+    let luminosite = 0.299 * fond[0] as f32 + 0.587 * fond[1] as f32 + 0.114 * fond[2] as f32;
+    let couleur = if fond[3] >= 128 && luminosite > 128.0 { [0, 0, 0, 255] } else { [255, 255, 255, 255] };
+    
+    // Le texte est ajusté à la largeur ; un nom court serait alors trop haut
+    let rendu = rendre_texte(texte, largeur, couleur)?;
+    if rendu.height() <= hauteur {
+        return Ok(rendu);
+    }
+    rendre_texte(texte, (largeur as u64 * hauteur as u64 / rendu.height() as u64).max(1) as u32, couleur)
+}
+
+/**
+ * Classe CSS de chaque sprite, tirée de son nom et rendue unique
+ * 
+ * Deux noms qui se réduisent à la même classe (« a b » et « a-b », ou un même
+ * nom dans deux dossiers) reçoivent les suffixes -2, -3…
+ */
+fn classes_sprites(positions: &[PositionImage], prefixe: &str) -> Vec<String> {
+    let mut utilisees = std::collections::HashSet::new();
+    
+    positions
+        .iter()
+        .map(|position| {
+            let base: String = position
+                .nom
+                .to_lowercase()
+                .chars()
+                .map(|caractere| if caractere.is_ascii_alphanumeric() { caractere } else { '-' })
+                .collect();
+            let base = format!("{}-{}", prefixe, base);
+            
+            let mut classe = base.clone();
+            let mut suffixe = 1;
+            while !utilisees.insert(classe.clone()) {
+                suffixe += 1;
+                classe = format!("{}-{}", base, suffixe);
+            }
+            classe
+        })
+        .collect()
+}
+
+fn generer_css_sprites(positions: &[PositionImage], classes: &[String], nom_planche: &str, prefixe: &str) -> String {
+    let mut css = format!(
+        ".{} {{\n  display: inline-block;\n  background-image: url(\"{}\");\n  background-repeat: no-repeat;\n}}\n",
+        prefixe, nom_planche
+    );
+    
+    for (position, classe) in positions.iter().zip(classes) {
+        css.push_str(&format!(
+            "\n.{} {{\n  width: {}px;\n  height: {}px;\n  background-position: -{}px -{}px;\n}}\n",
+            classe, position.largeur, position.hauteur, position.x, position.y
+        ));
+    }
+    
+    css
+}
+
+async fn comparer_images_interne(
+    chemin_a: &str,
+    chemin_b: &str,
+    options: &OptionsComparaison
+) -> ResultatApplication<ResultatComparaison> {
+    for chemin in [chemin_a, chemin_b] {
+        if !verifier_fichier_existe(chemin).await {
+            return Err(ErreurApplication::Validation {
+                message: format!("Le fichier n'existe pas : {}", chemin),
+            });
+        }
+    }
+    
+    let mut a = charger_image(Path::new(chemin_a))?;
+    let mut b = charger_image(Path::new(chemin_b))?;
+    let dimensions_identiques = (a.width(), a.height()) == (b.width(), b.height());
+    
+    if !dimensions_identiques {
+        match options.alignement.as_deref().unwrap_or("redimensionner") {
+            "redimensionner" => b = b.resize_exact(a.width(), a.height(), image::imageops::FilterType::Lanczos3),
+            "recadrer" => {
+                let (largeur, hauteur) = (a.width().min(b.width()), a.height().min(b.height()));
+                a = a.crop_imm(0, 0, largeur, hauteur);
+                b = b.crop_imm(0, 0, largeur, hauteur);
+            }
+            autre => {
+                return Err(ErreurApplication::Validation {
+                    message: format!("Alignement inconnu : {}", autre),
+                })
+            }
+        }
+    }
+    
+    let (a, b) = (a.to_rgba8(), b.to_rgba8());
+    let mut resultat = comparer_pixels(&a, &b, options.tolerance.unwrap_or(0));
+    resultat.dimensions_identiques = dimensions_identiques;
+    
+    if let Some(chemin_diff) = &options.chemin_diff {
+        let format = ImageFormat::from_path(chemin_diff).map_err(|_| ErreurApplication::Validation {
+            message: format!("Format de sortie non reconnu : {}", chemin_diff),
+        })?;
+        let diff = generer_image_diff(&a, &b, options.tolerance.unwrap_or(0));
+        if let Some(parent) = Path::new(chemin_diff).parent() {
+            creer_dossier_recursif(&parent.to_string_lossy()).await?;
+        }
+        tokio::fs::write(chemin_diff, encoder_image(&image::DynamicImage::ImageRgba8(diff), format, 100)?)
+            .await
+            .map_err(|e| ErreurApplication::Systeme {
+                message: format!("Impossible d'écrire le fichier : {}", e),
+            })?;
+        resultat.fichier_diff = Some(chemin_diff.clone());
+    }
+    
+    Ok(resultat)
+}
+
+/**
+ * Mesure l'écart entre deux images de même taille
+ * 
+ * Le PSNR porte sur les canaux rouge, vert et bleu ; le SSIM sur la luminosité,
+ * par fenêtres de 8×8 pixels décalées de 4.
+ */
+fn comparer_pixels(a: &image::RgbaImage, b: &image::RgbaImage, tolerance: u8) -> ResultatComparaison {
+    let mut pixels_differents = 0u64;
+    let mut ecart_maximal = 0u8;
+    let mut somme_carres = 0u64;
+    
+    for (pixel_a, pixel_b) in a.pixels().zip(b.pixels()) {
+        let ecarts = [0, 1, 2, 3].map(|canal| pixel_a.0[canal].abs_diff(pixel_b.0[canal]));
+        let ecart = ecarts.into_iter().max().unwrap_or(0);
+        ecart_maximal = ecart_maximal.max(ecart);
+        pixels_differents += (ecart > tolerance) as u64;
+        somme_carres += ecarts[..3].iter().map(|&e| e as u64 * e as u64).sum::<u64>();
+    }
+    
+    let (largeur, hauteur) = a.dimensions();
+    let total = (largeur as u64 * hauteur as u64).max(1);
+    let mse = somme_carres as f64 / (total * 3) as f64;
+    let psnr = (mse > 0.0).then(|| 10.0 * (255.0 * 255.0 / mse).log10());
+    let ssim = calculer_ssim(&luminosites(a), &luminosites(b), largeur as usize, hauteur as usize);
+    let pourcentage_differents = pixels_differents as f32 / total as f32 * 100.0;
+    
+    ResultatComparaison {
+        largeur,
+        hauteur,
+        dimensions_identiques: true,
+        pixels_differents,
+        pourcentage_differents,
+        psnr,
+        ssim,
+        ecart_maximal,
+        fichier_diff: None,
+        message: match psnr {
+            Some(psnr) => format!(
+                "{:.2}% de pixels différents, PSNR {:.1} dB, SSIM {:.4}",
+                pourcentage_differents, psnr, ssim
+            ),
+            None => "Images identiques".to_string(),
+        },
+    }
+}
+
+fn luminosites(img: &image::RgbaImage) -> Vec<f64> {
+    img.pixels()
+        .map(|pixel| 0.299 * pixel.0[0] as f64 + 0.587 * pixel.0[1] as f64 + 0.114 * pixel.0[2] as f64)
+        .collect()
+}
+
+/**
+ * SSIM moyen sur des fenêtres de 8×8 (l'image entière si elle est plus petite)
+ */
+fn calculer_ssim(a: &[f64], b: &[f64], largeur: usize, hauteur: usize) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    
+    let (fenetre_x, fenetre_y) = (largeur.min(8), hauteur.min(8));
+    let positions = |taille: usize, fenetre: usize| (0..=taille - fenetre).step_by(4).collect::<Vec<_>>();
+    let (colonnes, lignes) = (positions(largeur, fenetre_x), positions(hauteur, fenetre_y));
+    let n = (fenetre_x * fenetre_y) as f64;
+    let mut somme = 0.0;
+    
+    for &y0 in &lignes {
+        for &x0 in &colonnes {
+            let (mut somme_a, mut somme_b, mut carres_a, mut carres_b, mut produits) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in y0..y0 + fenetre_y {
+                for i in y * largeur + x0..y * largeur + x0 + fenetre_x {
+                    somme_a += a[i];
+                    somme_b += b[i];
+                    carres_a += a[i] * a[i];
+                    carres_b += b[i] * b[i];
+                    produits += a[i] * b[i];
+                }
+            }
+            let (moyenne_a, moyenne_b) = (somme_a / n, somme_b / n);
+            let variance_a = carres_a / n - moyenne_a * moyenne_a;
+            let variance_b = carres_b / n - moyenne_b * moyenne_b;
+            let covariance = produits / n - moyenne_a * moyenne_b;
+            
+            somme += ((2.0 * moyenne_a * moyenne_b + C1) * (2.0 * covariance + C2))
+                / ((moyenne_a * moyenne_a + moyenne_b * moyenne_b + C1) * (variance_a + variance_b + C2));
+        }
+    }
+    
+    somme / (colonnes.len() * lignes.len()) as f64
+}
+
+/**
+ * Première image en gris estompé, pixels modifiés en rouge d'autant plus opaque que l'écart est grand
+ */
+fn generer_image_diff(a: &image::RgbaImage, b: &image::RgbaImage, tolerance: u8) -> image::RgbaImage {
+    image::RgbaImage::from_fn(a.width(), a.height(), |x, y| {
+        let (pixel_a, pixel_b) = (a.get_pixel(x, y).0, b.get_pixel(x, y).0);
+        let ecart = (0..4).map(|canal| pixel_a[canal].abs_diff(pixel_b[canal])).max().unwrap_or(0);
+        
+        if ecart > tolerance {
+            let intensite = 0.5 + 0.5 * ecart as f32 / 255.0;
+            let fond = 255.0 * (1.0 - intensite);
+            image::Rgba([255, fond as u8, fond as u8, 255])
+        } else {
+            let luminosite = 0.299 * pixel_a[0] as f32 + 0.587 * pixel_a[1] as f32 + 0.114 * pixel_a[2] as f32;
+            let opacite = pixel_a[3] as f32 / 255.0;
+            let gris = (255.0 - (255.0 - luminosite) * 0.25 * opacite) as u8;
+            image::Rgba([gris, gris, gris, 255])
+        }
+    })
+}
+
+/// Tailles incluses dans favicon.ico
+const TAILLES_FAVICON_ICO: [u32; 4] = [16, 32, 48, 256];
+
+/// PNG générés : nom de fichier, taille, fond opaque (icônes Apple)
+const ICONES_PNG: [(&str, u32, bool); 7] = [
+    ("favicon-16x16.png", 16, false),
+    ("favicon-32x32.png", 32, false),
+    ("android-chrome-192x192.png", 192, false),
+    ("android-chrome-512x512.png", 512, false),
+    ("apple-touch-icon.png", 180, true),
+    ("apple-touch-icon-152x152.png", 152, true),
+    ("apple-touch-icon-167x167.png", 167, true),
+];
+
+async fn generer_icones_interne(
+    chemin_source: &str,
+    dossier_sortie: &str,
+    options: &OptionsIcones
+) -> ResultatApplication<ResultatIcones> {
+    if !verifier_fichier_existe(chemin_source).await {
+        return Err(ErreurApplication::Validation {
+            message: format!("Le fichier source n'existe pas : {}", chemin_source),
+        });
+    }
+    
+    let couleur_fond = parser_couleur_hex(options.couleur_fond.as_deref().unwrap_or("#FFFFFF"))?;
+    let couleur_theme = options.couleur_theme.as_deref().unwrap_or("#FFFFFF");
+    parser_couleur_hex(couleur_theme)?;
+    
+    let img = charger_image(Path::new(chemin_source))?;
+    let mut remarques = Vec::new();
+    
+    let source = if img.width() != img.height() {
+        let (x, y, cote, _) = calculer_recadrage_ratio(img.width(), img.height(), 1, 1, "centre")?;
+        remarques.push(format!("source {}x{} recadrée au centre", img.width(), img.height()));
+        img.crop_imm(x, y, cote, cote).to_rgba8()
+    } else {
+        img.to_rgba8()
+    };
+    if source.width() < 512 {
+        remarques.push(format!("source de {} px agrandie jusqu'à 512 px", source.width()));
+    }
+    
+    creer_dossier_recursif(dossier_sortie).await?;
+    let dossier = Path::new(dossier_sortie);
+    let mut fichiers = Vec::new();
+    
+    let mut ecrire = |nom: &str, tailles: Vec<u32>, octets: Vec<u8>| -> ResultatApplication<()> {
+        let chemin = dossier.join(nom);
+        std::fs::write(&chemin, &octets).map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible d'écrire {} : {}", nom, e),
+        })?;
+        fichiers.push(FichierIcone {
+            chemin: chemin.to_string_lossy().to_string(),
+            tailles,
+            taille_octets: octets.len() as u64,
+        });
+        Ok(())
+    };
+    
+    // favicon.ico : chaque taille est stockée en PNG, accepté par tous les navigateurs actuels
+    let images_ico = TAILLES_FAVICON_ICO
+        .iter()
+        .map(|&cote| encoder_image(&redimensionner_icone(&source, cote, None), ImageFormat::Png, 100))
+        .collect::<ResultatApplication<Vec<_>>>()?;
+    let trames = images_ico
+        .iter()
+        .zip(TAILLES_FAVICON_ICO)
+        .map(|(png, cote)| image::codecs::ico::IcoFrame::with_encoded(png.as_slice(), cote, cote, image::ColorType::Rgba8))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ErreurApplication::Systeme {
+            message: format!("Erreur d'encodage ICO : {}", e),
+        })?;
+    let mut ico = Vec::new();
+    image::codecs::ico::IcoEncoder::new(&mut ico)
+        .encode_images(&trames)
+        .map_err(|e| ErreurApplication::Systeme {
+            message: format!("Erreur d'encodage ICO : {}", e),
+        })?;
+    ecrire("favicon.ico", TAILLES_FAVICON_ICO.to_vec(), ico)?;
+    
+    for (nom, cote, opaque) in ICONES_PNG {
+        let icone = redimensionner_icone(&source, cote, opaque.then_some(couleur_fond));
+        ecrire(nom, vec![cote], encoder_image(&icone, ImageFormat::Png, 100)?)?;
+    }
+    
+    let nom_court = options.nom_court.as_deref().unwrap_or(&options.nom_application);
+    let manifeste = serde_json::to_string_pretty(&serde_json::json!({
+        "name": options.nom_application,
+        "short_name": nom_court,
+        "icons": [
+            { "src": "/android-chrome-192x192.png", "sizes": "192x192", "type": "image/png" },
+            { "src": "/android-chrome-512x512.png", "sizes": "512x512", "type": "image/png" }
+        ],
+        "theme_color": couleur_theme,
+        "background_color": format!("#{:02x}{:02x}{:02x}", couleur_fond[0], couleur_fond[1], couleur_fond[2]),
+        "display": "standalone"
+    }))
+    .map_err(|e| ErreurApplication::Systeme {
+        message: format!("Erreur de génération du manifeste : {}", e),
+    })?;
+    ecrire("manifest.json", Vec::new(), manifeste.clone().into_bytes())?;
+    
+    let balises_html = [
+        r#"<link rel="icon" href="/favicon.ico" sizes="16x16 32x32 48x48 256x256">"#.to_string(),
+        r#"<link rel="icon" type="image/png" sizes="32x32" href="/favicon-32x32.png">"#.to_string(),
+        r#"<link rel="icon" type="image/png" sizes="16x16" href="/favicon-16x16.png">"#.to_string(),
+        r#"<link rel="apple-touch-icon" sizes="180x180" href="/apple-touch-icon.png">"#.to_string(),
+        r#"<link rel="manifest" href="/manifest.json">"#.to_string(),
+        format!(r#"<meta name="theme-color" content="{}">"#, couleur_theme),
+    ]
+    .join("\n");
+    
+    let message = if remarques.is_empty() {
+        format!("{} fichiers générés", fichiers.len())
+    } else {
+        format!("{} fichiers générés ({})", fichiers.len(), remarques.join(", "))
+    };
+    
+    Ok(ResultatIcones {
+        succes: true,
+        dossier_sortie: dossier_sortie.to_string(),
+        fichiers,
+        manifeste,
+        balises_html,
+        message,
+    })
+}
+
+/**
+ * Redimensionne une icône carrée, éventuellement posée sur un fond opaque
+ */
+fn redimensionner_icone(source: &image::RgbaImage, cote: u32, fond: Option<[u8; 3]>) -> image::DynamicImage {
+    let icone = image::imageops::resize(source, cote, cote, image::imageops::FilterType::Lanczos3);
+    
+    match fond {
+        Some([r, g, b]) => {
+            let mut resultat = image::RgbaImage::from_pixel(cote, cote, image::Rgba([r, g, b, 255]));
+            superposer(&mut resultat, &icone, 0, 0);
+            image::DynamicImage::ImageRgb8(image::DynamicImage::ImageRgba8(resultat).to_rgb8())
+        }
+        None => image::DynamicImage::ImageRgba8(icone),
+    }
+}
+
+async fn optimiser_image_interne(
+    chemin_entree: &str,
+    chemin_sortie: &str,
+    options: &OptionsOptimisation
+) -> ResultatApplication<ResultatConversion> {
+    let path_entree = Path::new(chemin_entree);
+    let path_sortie = Path::new(chemin_sortie);
+    
+    if !verifier_fichier_existe(chemin_entree).await {
+        return Err(ErreurApplication::Validation {
+            message: format!("Le fichier source n'existe pas : {}", chemin_entree),
+        });
+    }
+    
+    let origine = tokio::fs::read(path_entree).await.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible de lire le fichier d'origine : {}", e),
+    })?;
+    let format = detecter_format_fichier(path_entree)?;
+    
+    let (optimise, qualite) = match format {
+        ImageFormat::Png => (optimiser_png(&origine, options)?, None),
+        ImageFormat::Jpeg => optimiser_jpeg(&origine, options)?,
+        autre => {
+            return Err(ErreurApplication::Validation {
+                message: format!("Optimisation disponible pour PNG et JPEG uniquement (fichier {})", nom_format(autre)),
+            });
+        }
+    };
+    
+    if let Some(parent) = path_sortie.parent() {
+        creer_dossier_recursif(&parent.to_string_lossy()).await?;
+    }
+    
+    // On ne livre jamais un fichier plus lourd que l'original
+    let gain = optimise.len() < origine.len();
+    let sortie = if gain { &optimise } else { &origine };
+    
+    tokio::fs::write(path_sortie, sortie).await.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible d'écrire le fichier : {}", e),
+    })?;
+    
+    let (largeur, hauteur) = image::io::Reader::new(std::io::Cursor::new(sortie))
+        .with_guessed_format()
+        .ok()
+        .and_then(|lecteur| lecteur.into_dimensions().ok())
+        .unwrap_or((0, 0));
+    
+    let taille_avant = origine.len() as u64;
+    let taille_apres = sortie.len() as u64;
+    let reduction_pourcent = if taille_avant > 0 {
+        ((taille_avant as f32 - taille_apres as f32) / taille_avant as f32) * 100.0
+    } else {
+        0.0
+    };
+    
+    Ok(ResultatConversion {
+        succes: true,
+        fichier_origine: chemin_entree.to_string(),
+        fichier_sortie: chemin_sortie.to_string(),
+        format_origine: nom_format(format).to_string(),
+        format_sortie: nom_format(format).to_string(),
+        taille_avant,
+        taille_apres,
+        reduction_pourcent,
+        message: if gain {
+            let nature = match qualite {
+                Some(qualite) => format!("réencodage avec perte en qualité {}", qualite),
+                None if format == ImageFormat::Png && options.quantifier => "quantification avec perte à 256 couleurs".to_string(),
+                None => "sans perte".to_string(),
+            };
+            format!(
+                "Optimisation terminée ({}) : {} -> {} ({:.1}% de réduction)",
+                nature,
+                formater_taille_fichier(taille_avant),
+                formater_taille_fichier(taille_apres),
+                reduction_pourcent
+            )
+        } else {
+            "Fichier déjà optimal : l'original est conservé".to_string()
+        },
+        qualite_utilisee: if gain { qualite } else { None },
+        largeur_sortie: largeur,
+        hauteur_sortie: hauteur,
+    })
+}
+
+/**
+ * Recompresse un PNG sans perte (filtres, deflate, réduction de palette et de profondeur)
+ * 
+ * Avec la quantification, l'image est d'abord ramenée à 256 couleurs :
+ * oxipng la convertit ensuite en PNG à palette.
+ */
+fn optimiser_png(png: &[u8], options: &OptionsOptimisation) -> ResultatApplication<Vec<u8>> {
+    let quantifie;
+    let source = if options.quantifier {
+        let mut rgba = image::load_from_memory_with_format(png, ImageFormat::Png)
+            .map_err(|e| ErreurApplication::Systeme {
+                message: format!("Impossible de lire l'image : {}", e),
+            })?
+            .to_rgba8();
+        
+        let palette = color_quant::NeuQuant::new(10, 256, rgba.as_raw());
+        if options.tramage {
+            image::imageops::dither(&mut rgba, &palette);
+        } else {
+            for pixel in rgba.pixels_mut() {
+                image::imageops::ColorMap::map_color(&palette, pixel);
+            }
+        }
+        
+        quantifie = encoder_image(&image::DynamicImage::ImageRgba8(rgba), ImageFormat::Png, 100)?;
+        &quantifie
+    } else {
+        png
+    };
+    
+    let mut reglages = oxipng::Options::from_preset(options.niveau_png.unwrap_or(2).min(6));
+    reglages.strip = if options.conserver_metadonnees {
+        oxipng::StripChunks::None
+    } else {
+        // Retire ce qui n'influe pas sur l'affichage, en gardant profil ICC et gamma
+        oxipng::StripChunks::Safe
+    };
+    
+    oxipng::optimize_from_memory(source, &reglages).map_err(|e| ErreurApplication::Systeme {
+        message: format!("Erreur d'optimisation PNG : {}", e),
+    })
+}
+
+/**
+ * Optimise un JPEG, sans perte sauf si le réencodage est demandé
+ * 
+ * La transcodification sans perte recode les coefficients DCT d'origine,
+ * à la manière de jpegtran ; les JPEG déjà progressifs ou à codage
+ * arithmétique sont rendus tels quels.
+ * 
+ * # Retour
+ * * `(Vec<u8>, Option<u8>)` - JPEG optimisé et qualité utilisée en cas de réencodage
+ */
+fn optimiser_jpeg(jpeg: &[u8], options: &OptionsOptimisation) -> ResultatApplication<(Vec<u8>, Option<u8>)> {
+    if options.reencoder {
+        let (sortie, qualite) = reencoder_jpeg(jpeg, options)?;
+        return Ok((sortie, Some(qualite)));
+    }
+    
+    // Sans les métadonnées, un EXIF réduit à l'orientation remplace l'original
+    let metadonnee = |segment: &[u8], conserves: &mut Vec<u8>| {
+        if options.conserver_metadonnees {
+            conserves.extend_from_slice(segment);
+        } else if segment[1] == 0xE1 && segment[4..].starts_with(b"Exif\0\0") {
+            if let Some(orientation) = lire_orientation_tiff(&segment[10..]) {
+                conserves.extend(segment_exif_orientation(orientation));
+            }
+        }
+    };
+    let sortie = lire_coefficients_jpeg(jpeg, metadonnee)
+        .map(|image| ecrire_jpeg_dct(&image, options.progressif))
+        .unwrap_or_else(|| jpeg.to_vec());
+    Ok((sortie, None))
+}
+
+/**
+ * Réencode un JPEG (avec perte) avec des tables de Huffman optimisées
+ * 
+ * Les pixels sont décodés sans appliquer l'orientation EXIF, qui reste
+ * donc valable : elle est recopiée seule quand les métadonnées sont retirées.
+ */
+fn reencoder_jpeg(jpeg: &[u8], options: &OptionsOptimisation) -> ResultatApplication<(Vec<u8>, u8)> {
+    let img = image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg).map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible de lire l'image : {}", e),
+    })?;
+    
+    let qualite = options.qualite
+        .or_else(|| estimer_qualite_jpeg(jpeg))
+        .unwrap_or(85)
+        .clamp(1, 100);
+    
+    let echantillonnage = match options.sous_echantillonnage.as_deref().unwrap_or("4:2:0") {
+        "4:4:4" => jpeg_encoder::SamplingFactor::R_4_4_4,
+        "4:2:2" => jpeg_encoder::SamplingFactor::R_4_2_2,
+        "4:2:0" => jpeg_encoder::SamplingFactor::R_4_2_0,
+        autre => {
+            return Err(ErreurApplication::Validation {
+                message: format!("Sous-échantillonnage inconnu : {}", autre),
+            });
+        }
+    };
+    
+    let (largeur, hauteur) = match (u16::try_from(img.width()), u16::try_from(img.height())) {
+        (Ok(largeur), Ok(hauteur)) => (largeur, hauteur),
+        _ => {
+            return Err(ErreurApplication::Validation {
+                message: "Dimensions trop grandes pour un JPEG (65535 pixels maximum)".to_string(),
+            });
+        }
+    };
+    
+    let mut sortie = Vec::new();
+    let mut encodeur = jpeg_encoder::Encoder::new(&mut sortie, qualite);
+    encodeur.set_optimized_huffman_tables(true);
+    encodeur.set_progressive(options.progressif);
+    encodeur.set_sampling_factor(echantillonnage);
+    
+    let encodage = if img.color().has_color() {
+        encodeur.encode(&img.to_rgb8(), largeur, hauteur, jpeg_encoder::ColorType::Rgb)
+    } else {
+        encodeur.encode(&img.to_luma8(), largeur, hauteur, jpeg_encoder::ColorType::Luma)
+    };
+    encodage.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Erreur d'encodage JPEG : {}", e),
+    })?;
+    
+    if options.conserver_metadonnees {
+        recopier_metadonnees_jpeg(jpeg, &mut sortie);
+    } else if let Some(orientation) = extraire_segment_exif_jpeg(jpeg).and_then(|segment| lire_orientation_tiff(&segment[10..])) {
+        // Sans elle, la photo s'afficherait couchée
+        let insertion = position_apres_app0(&sortie);
+        sortie.splice(insertion..insertion, segment_exif_orientation(orientation));
+    }
+    
+    Ok((sortie, qualite))
+}
+
+/// Table de quantification de luminance de référence (IJG, qualité 50)
+const TABLE_LUMINANCE_JPEG: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61,
+    12, 12, 14, 19, 26, 58, 60, 55,
+    14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62,
+    18, 22, 37, 56, 68, 109, 103, 77,
+    24, 35, 55, 64, 81, 104, 113, 92,
+    49, 64, 78, 87, 103, 121, 120, 101,
+    72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/**
+ * Estime la qualité (échelle IJG) d'un JPEG d'après sa table de luminance
+ */
+fn estimer_qualite_jpeg(jpeg: &[u8]) -> Option<u8> {
+    let mut position = 2;
+    
+    while position + 4 <= jpeg.len() && jpeg[position] == 0xFF {
+        let marqueur = jpeg[position + 1];
+        if marqueur == 0xDA || marqueur == 0xD9 {
+            return None;
+        }
+        
+        let longueur = u16::from_be_bytes([jpeg[position + 2], jpeg[position + 3]]) as usize;
+        let segment = jpeg.get(position + 4..position + 2 + longueur)?;
+        
+        if marqueur == 0xDB {
+            let mut curseur = 0;
+            while curseur < segment.len() {
+                let precision_16_bits = segment[curseur] >> 4 != 0;
+                let taille = if precision_16_bits { 128 } else { 64 };
+                let table = segment.get(curseur + 1..curseur + 1 + taille)?;
+                
+                if segment[curseur] & 0x0F == 0 {
+                    let somme: u32 = if precision_16_bits {
+                        table.chunks(2).map(|valeur| u16::from_be_bytes([valeur[0], valeur[1]]) as u32).sum()
+                    } else {
+                        table.iter().map(|&valeur| valeur as u32).sum()
+                    };
+                    let reference: u32 = TABLE_LUMINANCE_JPEG.iter().map(|&valeur| valeur as u32).sum();
+                    
+                    let echelle = somme as f32 * 100.0 / reference as f32;
+                    let qualite = if echelle <= 100.0 { (200.0 - echelle) / 2.0 } else { 5000.0 / echelle };
+                    return Some(qualite.round().clamp(1.0, 100.0) as u8);
+                }
+                curseur += 1 + taille;
+            }
+        }
+        position += 2 + longueur;
+    }
+    
+    None
+}
+
+/**
+ * Recopie les segments APP1 à APP15 (EXIF, ICC, XMP, IPTC…) et les commentaires
+ * d'un JPEG d'origine à la suite de l'en-tête JFIF d'un JPEG réencodé
+ */
+fn recopier_metadonnees_jpeg(origine: &[u8], sortie: &mut Vec<u8>) {
+    let mut segments = Vec::new();
+    let mut position = 2;
+    
+    while position + 4 <= origine.len() && origine[position] == 0xFF {
+        let marqueur = origine[position + 1];
+        if marqueur == 0xDA || marqueur == 0xD9 {
+            break;
+        }
+        
+        let longueur = u16::from_be_bytes([origine[position + 2], origine[position + 3]]) as usize;
+        let Some(segment) = origine.get(position..position + 2 + longueur) else {
+            break;
+        };
+        if (0xE1..=0xEF).contains(&marqueur) || marqueur == 0xFE {
+            segments.extend_from_slice(segment);
+        }
+        position += 2 + longueur;
+    }
+    
+    let insertion = position_apres_app0(sortie);
+    sortie.splice(insertion..insertion, segments);
+}
+
+/**
+ * Position d'insertion des segments APPn d'un JPEG : après l'APP0 JFIF, qui doit rester en tête
+ */
+fn position_apres_app0(jpeg: &[u8]) -> usize {
+    if jpeg.len() > 6 && jpeg[2..4] == [0xFF, 0xE0] {
+        (4 + u16::from_be_bytes([jpeg[4], jpeg[5]]) as usize).min(jpeg.len())
+    } else {
+        2
+    }
+}
+
+/**
+ * Écrit un fichier via un fichier temporaire voisin renommé ensuite
+ * 
+ * Une interruption en cours d'écriture laisse le fichier cible intact.
+ */
+fn ecrire_fichier_atomique(path: &Path, octets: &[u8]) -> ResultatApplication<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible de créer le dossier : {}", e),
+        })?;
+    }
+    
+    let temporaire = path.with_file_name(format!(".{}.tmp", path.file_name().unwrap_or_default().to_string_lossy()));
+    std::fs::write(&temporaire, octets)
+        .and_then(|_| std::fs::rename(&temporaire, path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temporaire);
+            ErreurApplication::Systeme {
+                message: format!("Impossible d'écrire le fichier : {}", e),
+            }
+        })
+}
+
+/**
+ * Détecte le format d'une image depuis sa signature, puis depuis son extension
+ * si le fichier est illisible ou sa signature inconnue
+ */
+fn detecter_format_fichier(path: &Path) -> ResultatApplication<ImageFormat> {
+    let mut entete = [0u8; 32];
+    let lus = std::fs::File::open(path)
+        .and_then(|mut fichier| std::io::Read::read(&mut fichier, &mut entete))
+        .unwrap_or(0);
+    
+    if let Some(format) = deviner_format(&entete[..lus]) {
+        return Ok(format);
+    }
+    
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ImageFormat::from_extension(ext).ok_or_else(|| ErreurApplication::Validation {
+            message: format!("Extension de fichier non reconnue : {}", ext),
+        }),
+        None => Err(ErreurApplication::Validation {
+            message: "Impossible de déterminer le format du fichier".to_string(),
+        }),
+    }
+}
+
+/**
+ * Reconnaît le format d'après les octets magiques
+ * 
+ * image ne reconnaît l'AVIF que pour une boîte ftyp de 28 octets :
+ * on accepte ici toute taille de boîte portant la marque avif/avis.
+ */
+fn deviner_format(entete: &[u8]) -> Option<ImageFormat> {
+    if entete.len() >= 12 && &entete[4..8] == b"ftyp" && matches!(&entete[8..12], b"avif" | b"avis") {
+        return Some(ImageFormat::Avif);
+    }
+    
+    image::guess_format(entete).ok()
+}
+
+fn detecter_format_image(path: &Path) -> ResultatApplication<&'static str> {
+    detecter_format_fichier(path).map(nom_format)
+}
+
+fn nom_format(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpeg => "JPEG",
+        ImageFormat::Png => "PNG",
+        ImageFormat::WebP => "WebP",
+        ImageFormat::Bmp => "BMP",
+        ImageFormat::Tiff => "TIFF",
+        ImageFormat::Gif => "GIF",
+        ImageFormat::Ico => "ICO",
+        ImageFormat::Avif => "AVIF",
+        autre => autre.extensions_str().first().copied().unwrap_or("inconnu"),
+    }
+}
+
+async fn corriger_extension_interne(chemin: &str, appliquer: bool) -> CorrectionExtension {
+    let path = Path::new(chemin);
+    let mut correction = CorrectionExtension {
+        chemin_origine: chemin.to_string(),
+        chemin_corrige: chemin.to_string(),
+        format: String::new(),
+        renomme: false,
+        erreur: None,
+    };
+    
+    let format_reel = match detecter_format_fichier(path) {
+        Ok(format) => format,
+        Err(e) => {
+            correction.erreur = Some(e.to_string());
+            return correction;
+        }
+    };
+    correction.format = nom_format(format_reel).to_string();
+    
+    if ImageFormat::from_path(path).ok() == Some(format_reel) {
+        return correction;
+    }
+    
+    let chemin_corrige = path.with_extension(format_reel.extensions_str()[0]);
+    correction.chemin_corrige = chemin_corrige.to_string_lossy().to_string();
+    
+    if chemin_corrige.exists() {
+        correction.erreur = Some(format!("Le fichier {} existe déjà", correction.chemin_corrige));
+        return correction;
+    }
+    
+    if appliquer {
+        if let Err(e) = tokio::fs::rename(path, &chemin_corrige).await {
+            correction.erreur = Some(format!("Impossible de renommer le fichier : {}", e));
+            return correction;
+        }
+    }
+    
+    correction.renomme = appliquer;
+    correction
+}
+
+fn lire_metadonnees_image(path: &Path, format: ImageFormat) -> MetadonneesImage {
+    let mut metadonnees = MetadonneesImage::default();
+    
+    let Ok(donnees) = std::fs::read(path) else {
+        return metadonnees;
+    };
+    
+    (metadonnees.contient_xmp, metadonnees.contient_iptc) = detecter_xmp_iptc(&donnees, format);
+    
+    let Ok(exif) = exif::Reader::new().read_from_container(&mut std::io::Cursor::new(&donnees)) else {
+        return metadonnees;
+    };
+    
+    let texte = |tag: exif::Tag| -> Option<String> {
+        match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+            exif::Value::Ascii(valeurs) => valeurs
+                .first()
+                .map(|valeur| String::from_utf8_lossy(valeur).trim_end_matches('\0').trim().to_string())
+                .filter(|valeur| !valeur.is_empty()),
+            _ => None,
+        }
+    };
+    let avec_unite = |tag: exif::Tag| -> Option<String> {
+        exif.get_field(tag, exif::In::PRIMARY)
+            .map(|champ| champ.display_value().with_unit(&exif).to_string())
+    };
+    let entier = |tag: exif::Tag| exif.get_field(tag, exif::In::PRIMARY).and_then(|champ| champ.value.get_uint(0));
+    
+    metadonnees.contient_exif = true;
+    metadonnees.appareil = match (texte(exif::Tag::Make), texte(exif::Tag::Model)) {
+        (Some(marque), Some(modele)) if modele.starts_with(&marque) => Some(modele),
+        (Some(marque), Some(modele)) => Some(format!("{} {}", marque, modele)),
+        (marque, modele) => marque.or(modele),
+    };
+    metadonnees.objectif = texte(exif::Tag::LensModel);
+    metadonnees.temps_pose = avec_unite(exif::Tag::ExposureTime);
+    metadonnees.ouverture = avec_unite(exif::Tag::FNumber);
+    metadonnees.iso = entier(exif::Tag::PhotographicSensitivity);
+    metadonnees.focale = avec_unite(exif::Tag::FocalLength);
+    metadonnees.date_prise_vue = texte(exif::Tag::DateTimeOriginal)
+        .or_else(|| texte(exif::Tag::DateTime))
+        .map(|date| date.replacen(':', "-", 2));
+    metadonnees.orientation = entier(exif::Tag::Orientation).map(|orientation| orientation as u16);
+    metadonnees.auteur = texte(exif::Tag::Artist);
+    metadonnees.copyright = texte(exif::Tag::Copyright);
+    metadonnees.logiciel = texte(exif::Tag::Software);
+    
+    let coordonnee = |tag: exif::Tag, reference: exif::Tag, negatif: &str| -> Option<f64> {
+        let exif::Value::Rational(parties) = &exif.get_field(tag, exif::In::PRIMARY)?.value else {
+            return None;
+        };
+        let degres = parties.iter().zip([1.0, 60.0, 3600.0]).map(|(partie, diviseur)| partie.to_f64() / diviseur).sum::<f64>();
+        Some(if texte(reference).as_deref() == Some(negatif) { -degres } else { degres })
+    };
+    
+    if let (Some(latitude), Some(longitude)) = (
+        coordonnee(exif::Tag::GPSLatitude, exif::Tag::GPSLatitudeRef, "S"),
+        coordonnee(exif::Tag::GPSLongitude, exif::Tag::GPSLongitudeRef, "W"),
+    ) {
+        let altitude = match exif.get_field(exif::Tag::GPSAltitude, exif::In::PRIMARY).map(|champ| &champ.value) {
+            Some(exif::Value::Rational(valeurs)) if !valeurs.is_empty() => {
+                let sous_niveau_mer = entier(exif::Tag::GPSAltitudeRef) == Some(1);
+                Some(if sous_niveau_mer { -valeurs[0].to_f64() } else { valeurs[0].to_f64() })
+            }
+            _ => None,
+        };
+        metadonnees.gps = Some(CoordonneesGps { latitude, longitude, altitude });
+    }
+    
+    metadonnees.champs = exif
+        .fields()
+        .filter(|champ| champ.tag != exif::Tag::MakerNote)
+        .map(|champ| {
+            let mut valeur = champ.display_value().with_unit(&exif).to_string();
+            if valeur.len() > 200 {
+                let limite = (0..=200).rev().find(|&index| valeur.is_char_boundary(index)).unwrap_or(0);
+                valeur.truncate(limite);
+                valeur.push('…');
+            }
+            ChampMetadonnee {
+                groupe: if champ.ifd_num == exif::In::THUMBNAIL { "Miniature" } else { "Principal" }.to_string(),
+                nom: champ.tag.to_string(),
+                valeur,
+            }
+        })
+        .collect();
+    
+    metadonnees
+}
+
+async fn nettoyer_metadonnees_interne(
+    chemin_entree: &str,
+    chemin_sortie: &str,
+    mode: &str
+) -> ResultatApplication<ResultatNettoyageMetadonnees> {
+    let partiel = match mode {
+        "tout" => false,
+        "gps_et_personnel" => true,
+        _ => {
+            return Err(ErreurApplication::Validation {
+                message: format!("Mode de nettoyage inconnu : {}", mode),
+            });
         }
+    };
+    
+    let path_entree = Path::new(chemin_entree);
+    let path_sortie = Path::new(chemin_sortie);
+    
+    if !verifier_fichier_existe(chemin_entree).await {
+        return Err(ErreurApplication::Validation {
+            message: format!("Le fichier source n'existe pas : {}", chemin_entree),
+        });
+    }
+    
+    let donnees = tokio::fs::read(path_entree).await.map_err(|e| ErreurApplication::Systeme {
+        message: format!("Impossible de lire le fichier d'origine : {}", e),
     })?;
+    let format = detecter_format_fichier(path_entree)?;
+    
+    if let Some(parent) = path_sortie.parent() {
+        creer_dossier_recursif(&parent.to_string_lossy()).await?;
+    }
+    
+    let mut elements_supprimes = Vec::new();
+    let pixels_reencodes = format == ImageFormat::Tiff && !partiel;
+    
+    if pixels_reencodes {
+        // Seule la première page serait réécrite
+        if tiff_multipage(&donnees) {
+            return Err(ErreurApplication::Validation {
+                message: "TIFF multipage : le nettoyage complet ne conserverait que la première page, utilisez le mode gps_et_personnel".to_string(),
+            });
+        }
+        
+        // Les métadonnées TIFF font partie de la structure du fichier : on réécrit l'image,
+        // orientation appliquée aux pixels, sans profil ICC
+        charger_image(path_entree)?
+            .save_with_format(path_sortie, ImageFormat::Tiff)
+            .map_err(|e| ErreurApplication::Systeme {
+                message: format!("Erreur de sauvegarde : {}", e),
+            })?;
+        elements_supprimes.push("Toutes les balises TIFF non essentielles".to_string());
+        elements_supprimes.push("Profil ICC".to_string());
+    } else {
+        let nettoye = nettoyer_conteneur(&donnees, format, partiel, &mut elements_supprimes)?;
+        tokio::fs::write(path_sortie, nettoye).await.map_err(|e| ErreurApplication::Systeme {
+            message: format!("Impossible d'écrire le fichier : {}", e),
+        })?;
+    }
+    
+    let taille_apres = tokio::fs::metadata(path_sortie).await.map(|metadata| metadata.len()).unwrap_or(0);
+    
+    Ok(ResultatNettoyageMetadonnees {
+        succes: true,
+        fichier_origine: chemin_entree.to_string(),
+        fichier_sortie: chemin_sortie.to_string(),
+        message: if elements_supprimes.is_empty() {
+            "Aucune métadonnée à supprimer".to_string()
+        } else {
+            format!("Supprimé : {}", elements_supprimes.join(", "))
+        },
+        elements_supprimes,
+        pixels_reencodes,
+        taille_avant: donnees.len() as u64,
+        taille_apres,
+    })
+}
+
+/**
+ * Retire les métadonnées d'un fichier sans toucher aux données d'image
+ * 
+ * En mode partiel, le bloc EXIF est conservé mais expurgé du GPS et des
+ * champs personnels ; les blocs XMP et IPTC, qui peuvent dupliquer ces
+ * informations, sont supprimés.
+ */
+fn nettoyer_conteneur(
+    donnees: &[u8],
+    format: ImageFormat,
+    partiel: bool,
+    supprimes: &mut Vec<String>
+) -> ResultatApplication<Vec<u8>> {
+    match format {
+        ImageFormat::Jpeg => nettoyer_jpeg(donnees, partiel, supprimes),
+        ImageFormat::Png => nettoyer_png(donnees, partiel, supprimes),
+        ImageFormat::WebP => nettoyer_webp(donnees, partiel, supprimes),
+        ImageFormat::Tiff if partiel => {
+            let mut tiff = donnees.to_vec();
+            expurger_tiff(&mut tiff, supprimes)?;
+            Ok(tiff)
+        }
+        _ => Err(ErreurApplication::Validation {
+            message: format!("Nettoyage des métadonnées non pris en charge pour le format {}", nom_format(format)),
+        }),
+    }
+}
+
+fn nettoyer_jpeg(donnees: &[u8], partiel: bool, supprimes: &mut Vec<String>) -> ResultatApplication<Vec<u8>> {
+    let erreur_structure = || ErreurApplication::Donnees {
+        message: "Structure JPEG invalide".to_string(),
+    };
+    
+    if !donnees.starts_with(&[0xFF, 0xD8]) {
+        return Err(erreur_structure());
+    }
+    
+    let mut sortie = vec![0xFF, 0xD8];
+    let mut position = 2;
+    
+    while position + 1 < donnees.len() {
+        if donnees[position] != 0xFF {
+            return Err(erreur_structure());
+        }
+        let marqueur = donnees[position + 1];
+        
+        // Octets de remplissage entre segments
+        if marqueur == 0xFF {
+            position += 1;
+            continue;
+        }
+        
+        // Fin d'image : les éventuels aperçus MPF qui suivent sont abandonnés
+        if marqueur == 0xD9 {
+            sortie.extend_from_slice(&[0xFF, 0xD9]);
+            if position + 2 < donnees.len() {
+                ajouter_element(supprimes, "Aperçus intégrés (MPF)");
+            }
+            return Ok(sortie);
+        }
+        
+        if (0xD0..=0xD7).contains(&marqueur) || marqueur == 0x01 {
+            sortie.extend_from_slice(&donnees[position..position + 2]);
+            position += 2;
+            continue;
+        }
+        
+        let longueur = donnees
+            .get(position + 2..position + 4)
+            .map(|octets| u16::from_be_bytes([octets[0], octets[1]]) as usize)
+            .ok_or_else(erreur_structure)?;
+        // La longueur inclut ses deux octets : en dessous, le segment est corrompu
+        if longueur < 2 {
+            return Err(erreur_structure());
+        }
+        let fin = position + 2 + longueur;
+        let segment = donnees.get(position..fin).ok_or_else(erreur_structure)?;
+        let contenu = &segment[4..];
+        
+        let conserver = match marqueur {
+            0xE1 if contenu.starts_with(b"Exif\0\0") => {
+                if partiel {
+                    let mut segment_expurge = segment.to_vec();
+                    expurger_tiff(&mut segment_expurge[10..], supprimes)?;
+                    sortie.extend_from_slice(&segment_expurge);
+                } else {
+                    ajouter_element(supprimes, "EXIF");
+                    if let Some(orientation) = lire_orientation_tiff(&contenu[6..]) {
+                        sortie.extend(segment_exif_orientation(orientation));
+                    }
+                }
+                false
+            }
+            0xE1 if contenu.starts_with(b"http://ns.adobe.com/") => {
+                ajouter_element(supprimes, "XMP");
+                false
+            }
+            0xED => {
+                ajouter_element(supprimes, "IPTC");
+                false
+            }
+            0xE2 if contenu.starts_with(b"MPF\0") => {
+                ajouter_element(supprimes, "Aperçus intégrés (MPF)");
+                false
+            }
+            0xFE if !partiel => {
+                ajouter_element(supprimes, "Commentaire");
+                false
+            }
+            // JFIF, profil ICC (APP2) et segment Adobe sont nécessaires au rendu des couleurs
+            0xE0 | 0xE2 | 0xEE => true,
+            0xE1..=0xEF if !partiel => {
+                ajouter_element(supprimes, &format!("APP{}", marqueur - 0xE0));
+                false
+            }
+            _ => true,
+        };
+        
+        if conserver {
+            sortie.extend_from_slice(segment);
+        }
+        position = fin;
+        
+        // Après un début de scan, recopier les données compressées jusqu'au marqueur suivant
+        if marqueur == 0xDA {
+            let debut = position;
+            while position + 1 < donnees.len()
+                && (donnees[position] != 0xFF || matches!(donnees[position + 1], 0x00 | 0xD0..=0xD7))
+            {
+                position += 1;
+            }
+            sortie.extend_from_slice(&donnees[debut..position]);
+        }
+    }
+    
+    Err(erreur_structure())
+}
+
+fn nettoyer_png(donnees: &[u8], partiel: bool, supprimes: &mut Vec<String>) -> ResultatApplication<Vec<u8>> {
+    const SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    const MOTS_CLES_SENSIBLES: &[&str] = &[
+        "XML:com.adobe.xmp",
+        "Raw profile type exif",
+        "Raw profile type APP1",
+        "Raw profile type iptc",
+        "Raw profile type xmp",
+        "Author",
+    ];
+    
+    let erreur_structure = || ErreurApplication::Donnees {
+        message: "Structure PNG invalide".to_string(),
+    };
+    
+    if !donnees.starts_with(SIGNATURE) {
+        return Err(erreur_structure());
+    }
+    
+    let mut sortie = SIGNATURE.to_vec();
+    let mut position = SIGNATURE.len();
+    
+    while position + 8 <= donnees.len() {
+        let longueur = u32::from_be_bytes([donnees[position], donnees[position + 1], donnees[position + 2], donnees[position + 3]]) as usize;
+        let fin = position + 12 + longueur;
+        let bloc = donnees.get(position..fin).ok_or_else(erreur_structure)?;
+        let type_bloc = &bloc[4..8];
+        let contenu = &bloc[8..8 + longueur];
+        
+        match type_bloc {
+            b"eXIf" if partiel => {
+                let mut contenu_expurge = contenu.to_vec();
+                expurger_tiff(&mut contenu_expurge, supprimes)?;
+                sortie.extend_from_slice(&bloc[..8]);
+                sortie.extend_from_slice(&contenu_expurge);
+                sortie.extend_from_slice(&crc32fast::hash(&[type_bloc, &contenu_expurge[..]].concat()).to_be_bytes());
+            }
+            b"tEXt" | b"zTXt" | b"iTXt" => {
+                let mot_cle = String::from_utf8_lossy(contenu.split(|&octet| octet == 0).next().unwrap_or_default()).to_string();
+                let sensible = MOTS_CLES_SENSIBLES.contains(&mot_cle.as_str());
+                
+                if !partiel || sensible {
+                    let element = match mot_cle.as_str() {
+                        "XML:com.adobe.xmp" | "Raw profile type xmp" => "XMP".to_string(),
+                        "Raw profile type iptc" => "IPTC".to_string(),
+                        "Raw profile type exif" | "Raw profile type APP1" => "EXIF".to_string(),
+                        _ => format!("Texte PNG « {} »", mot_cle),
+                    };
+                    ajouter_element(supprimes, &element);
+                } else {
+                    sortie.extend_from_slice(bloc);
+                }
+            }
+            b"eXIf" => {
+                ajouter_element(supprimes, "EXIF");
+                if let Some(orientation) = lire_orientation_tiff(contenu) {
+                    let tiff = creer_exif_orientation(orientation);
+                    sortie.extend_from_slice(&(tiff.len() as u32).to_be_bytes());
+                    sortie.extend_from_slice(b"eXIf");
+                    sortie.extend_from_slice(&tiff);
+                    sortie.extend_from_slice(&crc32fast::hash(&[&b"eXIf"[..], &tiff[..]].concat()).to_be_bytes());
+                }
+            }
+            b"tIME" => ajouter_element(supprimes, "Date de modification PNG"),
+            _ => sortie.extend_from_slice(bloc),
+        }
+        
+        position = fin;
+        if type_bloc == b"IEND" {
+            return Ok(sortie);
+        }
+    }
+    
+    Err(erreur_structure())
+}
+
+fn nettoyer_webp(donnees: &[u8], partiel: bool, supprimes: &mut Vec<String>) -> ResultatApplication<Vec<u8>> {
+    const DRAPEAU_XMP: u8 = 0x04;
+    const DRAPEAU_EXIF: u8 = 0x08;
+    
+    let erreur_structure = || ErreurApplication::Donnees {
+        message: "Structure WebP invalide".to_string(),
+    };
+    
+    if donnees.len() < 12 || &donnees[0..4] != b"RIFF" || &donnees[8..12] != b"WEBP" {
+        return Err(erreur_structure());
+    }
+    
+    let mut sortie = donnees[0..12].to_vec();
+    let mut position = 12;
+    let mut position_vp8x = None;
+    let mut exif_conserve = false;
+    
+    while position + 8 <= donnees.len() {
+        let longueur = u32::from_le_bytes([donnees[position + 4], donnees[position + 5], donnees[position + 6], donnees[position + 7]]) as usize;
+        let fin = (position + 8 + longueur + (longueur & 1)).min(donnees.len());
+        let bloc = donnees.get(position..fin).ok_or_else(erreur_structure)?;
+        
+        match &bloc[0..4] {
+            b"EXIF" if partiel => {
+                let mut bloc_expurge = bloc.to_vec();
+                let debut_tiff = if bloc[8..].starts_with(b"Exif\0\0") { 14 } else { 8 };
+                let fin_tiff = (8 + longueur).min(bloc_expurge.len());
+                expurger_tiff(&mut bloc_expurge[debut_tiff..fin_tiff], supprimes)?;
+                sortie.extend_from_slice(&bloc_expurge);
+                exif_conserve = true;
+            }
+            b"EXIF" => {
+                ajouter_element(supprimes, "EXIF");
+                let contenu = &bloc[8..(8 + longueur).min(bloc.len())];
+                let tiff = contenu.strip_prefix(b"Exif\0\0").unwrap_or(contenu);
+                if let Some(orientation) = lire_orientation_tiff(tiff) {
+                    let tiff = creer_exif_orientation(orientation);
+                    sortie.extend_from_slice(b"EXIF");
+                    sortie.extend_from_slice(&(tiff.len() as u32).to_le_bytes());
+                    sortie.extend_from_slice(&tiff);
+                    exif_conserve = true;
+                }
+            }
+            b"XMP " => ajouter_element(supprimes, "XMP"),
+            type_bloc => {
+                if type_bloc == b"VP8X" {
+                    position_vp8x = Some(sortie.len());
+                }
+                sortie.extend_from_slice(bloc);
+            }
+        }
+        
+        position = fin;
+    }
+    
+    // Mettre à jour les indicateurs de l'en-tête étendu et la taille RIFF
+    if let Some(position_vp8x) = position_vp8x {
+        if let Some(drapeaux) = sortie.get_mut(position_vp8x + 8) {
+            *drapeaux &= !DRAPEAU_XMP;
+            if !exif_conserve {
+                *drapeaux &= !DRAPEAU_EXIF;
+            }
+        }
+    }
+    let taille_riff = (sortie.len() - 8) as u32;
+    sortie[4..8].copy_from_slice(&taille_riff.to_le_bytes());
+    
+    Ok(sortie)
+}
+
+/// Balises retirées en mode partiel : (balise, nom, répertoire EXIF plutôt que principal)
+const BALISES_PERSONNELLES: &[(u16, &str, bool)] = &[
+    (0x013B, "Artist", false),
+    (0x013C, "HostComputer", false),
+    (0x02BC, "XMP", false),
+    (0x83BB, "IPTC", false),
+    (0x8649, "Photoshop", false),
+    (0x8825, "GPS", false),
+    (0x9C9C, "XPComment", false),
+    (0x9C9D, "XPAuthor", false),
+    (0x927C, "MakerNote", true),
+    (0x9286, "UserComment", true),
+    (0xA420, "ImageUniqueID", true),
+    (0xA430, "CameraOwnerName", true),
+    (0xA431, "BodySerialNumber", true),
+    (0xA435, "LensSerialNumber", true),
+];
+
+const BALISE_IFD_EXIF: u16 = 0x8769;
+const BALISE_IFD_GPS: u16 = 0x8825;
+const BALISE_ORIENTATION: u16 = 0x0112;
+
+/**
+ * Retire sur place le GPS et les champs personnels d'une structure TIFF/EXIF
+ * 
+ * Les entrées supprimées sont retirées de leur répertoire (IFD) et leurs
+ * valeurs sont mises à zéro : les décalages du reste du fichier restent valides.
+ */
+fn expurger_tiff(tiff: &mut [u8], supprimes: &mut Vec<String>) -> ResultatApplication<()> {
+    let petit_boutiste = match tiff.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => {
+            return Err(ErreurApplication::Donnees {
+                message: "Structure EXIF invalide".to_string(),
+            });
+        }
+    };
+    let lecteur = LecteurTiff { petit_boutiste };
+    
+    let ifd_principal = lecteur.u32(tiff, 4).unwrap_or(0) as usize;
+    let ifd_exif = lecteur.chercher_entree(tiff, ifd_principal, BALISE_IFD_EXIF);
+    
+    lecteur.filtrer_ifd(tiff, ifd_principal, false, supprimes);
+    if let Some(ifd_exif) = ifd_exif {
+        lecteur.filtrer_ifd(tiff, ifd_exif, true, supprimes);
+    }
+    
+    Ok(())
+}
+
+/**
+ * Segment APP1 EXIF complet (marqueur compris) d'un JPEG
+ */
+fn extraire_segment_exif_jpeg(jpeg: &[u8]) -> Option<Vec<u8>> {
+    let mut position = 2;
+    
+    while position + 4 <= jpeg.len() && jpeg[position] == 0xFF {
+        let marqueur = jpeg[position + 1];
+        if marqueur == 0xDA || marqueur == 0xD9 {
+            return None;
+        }
+        
+        let longueur = u16::from_be_bytes([jpeg[position + 2], jpeg[position + 3]]) as usize;
+        // La longueur inclut ses deux octets : en dessous, le segment est corrompu
+        if longueur < 2 {
+            return None;
+        }
+        let segment = jpeg.get(position..position + 2 + longueur)?;
+        if marqueur == 0xE1 && segment[4..].starts_with(b"Exif\0\0") {
+            return Some(segment.to_vec());
+        }
+        position += 2 + longueur;
+    }
+    
+    None
+}
+
+/**
+ * Remet à 1 (normale) la balise Orientation du répertoire principal
+ */
+fn reinitialiser_orientation_tiff(tiff: &mut [u8]) {
+    let petit_boutiste = match tiff.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return,
+    };
+    let lecteur = LecteurTiff { petit_boutiste };
+    let ifd = lecteur.u32(tiff, 4).unwrap_or(0) as usize;
+    let nombre = lecteur.u16(tiff, ifd).unwrap_or(0) as usize;
+    
+    for entree in (0..nombre).map(|index| ifd + 2 + index * 12) {
+        if lecteur.u16(tiff, entree) == Some(BALISE_ORIENTATION) && entree + 10 <= tiff.len() {
+            lecteur.ecrire_u16(tiff, entree + 8, 1);
+        }
+    }
+}
+
+/**
+ * Orientation EXIF (balise 0x0112 de l'IFD principal) d'un bloc TIFF, si elle n'est pas la valeur neutre 1
+ */
+fn lire_orientation_tiff(tiff: &[u8]) -> Option<u16> {
+    let lecteur = LecteurTiff::depuis_entete(tiff)?;
+    let ifd = lecteur.u32(tiff, 4)? as usize;
+    let nombre = lecteur.u16(tiff, ifd)? as usize;
+    
+    (0..nombre)
+        .map(|index| ifd + 2 + index * 12)
+        .find(|&entree| lecteur.u16(tiff, entree) == Some(BALISE_ORIENTATION))
+        .and_then(|entree| lecteur.u16(tiff, entree + 8))
+        .filter(|orientation| (2..=8).contains(orientation))
+}
+
+/**
+ * Bloc TIFF minimal (petit-boutiste) ne contenant que la balise Orientation
+ */
+fn creer_exif_orientation(orientation: u16) -> Vec<u8> {
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&BALISE_ORIENTATION.to_le_bytes());
+    tiff.extend_from_slice(&3u16.to_le_bytes());
+    tiff.extend_from_slice(&1u32.to_le_bytes());
+    tiff.extend_from_slice(&(orientation as u32).to_le_bytes());
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    tiff
+}
+
+/**
+ * Segment APP1 EXIF minimal ne portant que l'orientation
+ */
+fn segment_exif_orientation(orientation: u16) -> Vec<u8> {
+    let tiff = creer_exif_orientation(orientation);
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+    segment
+}
+
+/**
+ * Indique si un TIFF contient plus d'une page (un IFD suivant le premier)
+ */
+fn tiff_multipage(tiff: &[u8]) -> bool {
+    let Some(lecteur) = LecteurTiff::depuis_entete(tiff) else {
+        return false;
+    };
+    let ifd = lecteur.u32(tiff, 4).unwrap_or(0) as usize;
+    let nombre = lecteur.u16(tiff, ifd).unwrap_or(0) as usize;
+    lecteur.u32(tiff, ifd + 2 + nombre * 12).is_some_and(|suivant| suivant != 0)
+}
+
+/**
+ * Détecte la présence de blocs XMP et IPTC en parcourant la structure du fichier
+ * 
+ * # Retour
+ * * `(bool, bool)` - XMP présent, IPTC présent
+ */
+fn detecter_xmp_iptc(donnees: &[u8], format: ImageFormat) -> (bool, bool) {
+    let (mut xmp, mut iptc) = (false, false);
+    
+    match format {
+        ImageFormat::Jpeg => {
+            let mut position = 2;
+            while position + 4 <= donnees.len() && donnees[position] == 0xFF {
+                let marqueur = donnees[position + 1];
+                if marqueur == 0xDA || marqueur == 0xD9 {
+                    break;
+                }
+                let longueur = u16::from_be_bytes([donnees[position + 2], donnees[position + 3]]) as usize;
+                let contenu = donnees.get(position + 4..position + 2 + longueur).unwrap_or_default();
+                xmp |= marqueur == 0xE1 && contenu.starts_with(b"http://ns.adobe.com/");
+                iptc |= marqueur == 0xED;
+                position += 2 + longueur;
+            }
+        }
+        ImageFormat::Png => {
+            let mut position = 8;
+            while position + 8 <= donnees.len() {
+                let longueur = u32::from_be_bytes([donnees[position], donnees[position + 1], donnees[position + 2], donnees[position + 3]]) as usize;
+                let type_bloc = &donnees[position + 4..position + 8];
+                if matches!(type_bloc, b"tEXt" | b"zTXt" | b"iTXt") {
+                    let contenu = donnees.get(position + 8..position + 8 + longueur).unwrap_or_default();
+                    let mot_cle = contenu.split(|&octet| octet == 0).next().unwrap_or_default();
+                    xmp |= mot_cle == b"XML:com.adobe.xmp" || mot_cle == b"Raw profile type xmp";
+                    iptc |= mot_cle == b"Raw profile type iptc";
+                }
+                position += 12 + longueur;
+            }
+        }
+        ImageFormat::WebP => {
+            let mut position = 12;
+            while position + 8 <= donnees.len() {
+                let longueur = u32::from_le_bytes([donnees[position + 4], donnees[position + 5], donnees[position + 6], donnees[position + 7]]) as usize;
+                xmp |= &donnees[position..position + 4] == b"XMP ";
+                position += 8 + longueur + (longueur & 1);
+            }
+        }
+        ImageFormat::Tiff => {
+            // Balises XMLPacket (700) et IPTC-NAA (33723) de l'IFD principal
+            if let Some(lecteur) = LecteurTiff::depuis_entete(donnees) {
+                let ifd = lecteur.u32(donnees, 4).unwrap_or(0) as usize;
+                let nombre = lecteur.u16(donnees, ifd).unwrap_or(0) as usize;
+                for entree in (0..nombre).map(|index| ifd + 2 + index * 12) {
+                    match lecteur.u16(donnees, entree) {
+                        Some(700) => xmp = true,
+                        Some(33723) => iptc = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
     
-    Ok([r, g, b])
+    (xmp, iptc)
 }
 
-fn supprimer_couleur_fond(img: &mut image::RgbaImage, couleur_cible: [u8; 3], tolerance: u8) {
-    for pixel in img.pixels_mut() {
-        let [r, g, b, a] = pixel.0;
-        
-        // Calculer la distance euclidienne entre la couleur du pixel et la couleur cible
-        let distance = (
-            ((r as i32 - couleur_cible[0] as i32).pow(2) +
-             (g as i32 - couleur_cible[1] as i32).pow(2) +
-             (b as i32 - couleur_cible[2] as i32).pow(2)) as f32
-        ).sqrt();
-        
-        // Si la couleur est proche de la couleur cible (dans la tolérance)
-        if distance <= tolerance as f32 {
-            // Rendre le pixel transparent
-            pixel.0[3] = 0;
-        } else if distance <= (tolerance as f32 * 1.5) {
-            // Transition douce pour les pixels proches
-            let alpha_factor = (distance - tolerance as f32) / (tolerance as f32 * 0.5);
-            pixel.0[3] = (a as f32 * alpha_factor.min(1.0)) as u8;
-        }
-    }
+struct LecteurTiff {
+    petit_boutiste: bool,
 }
 
-fn adoucir_bords_transparence(img: &mut image::RgbaImage, rayon: u8) {
-    if rayon == 0 {
-        return;
+impl LecteurTiff {
+    /// Lecteur adapté à l'ordre des octets annoncé par l'en-tête (« II » ou « MM »)
+    fn depuis_entete(tiff: &[u8]) -> Option<Self> {
+        match tiff.get(0..2)? {
+            b"II" => Some(LecteurTiff { petit_boutiste: true }),
+            b"MM" => Some(LecteurTiff { petit_boutiste: false }),
+            _ => None,
+        }
+    }
+    
+    fn u16(&self, tiff: &[u8], position: usize) -> Option<u16> {
+        let octets = [*tiff.get(position)?, *tiff.get(position + 1)?];
+        Some(if self.petit_boutiste { u16::from_le_bytes(octets) } else { u16::from_be_bytes(octets) })
+    }
+    
+    fn u32(&self, tiff: &[u8], position: usize) -> Option<u32> {
+        let octets: [u8; 4] = tiff.get(position..position + 4)?.try_into().ok()?;
+        Some(if self.petit_boutiste { u32::from_le_bytes(octets) } else { u32::from_be_bytes(octets) })
     }
     
-    let (width, height) = img.dimensions();
-    let mut nouvelle_img = img.clone();
+    fn ecrire_u16(&self, tiff: &mut [u8], position: usize, valeur: u16) {
+        let octets = if self.petit_boutiste { valeur.to_le_bytes() } else { valeur.to_be_bytes() };
+        tiff[position..position + 2].copy_from_slice(&octets);
+    }
+    
+    /// Valeur (décalage) de l'entrée portant la balise donnée
+    fn chercher_entree(&self, tiff: &[u8], ifd: usize, balise: u16) -> Option<usize> {
+        let nombre = self.u16(tiff, ifd)? as usize;
+        (0..nombre)
+            .map(|index| ifd + 2 + index * 12)
+            .find(|&entree| self.u16(tiff, entree) == Some(balise))
+            .and_then(|entree| self.u32(tiff, entree + 8))
+            .map(|valeur| valeur as usize)
+    }
+    
+    /// Plage des octets de la valeur d'une entrée lorsqu'elle est stockée hors de l'entrée
+    fn valeur_deportee(&self, tiff: &[u8], entree: usize) -> Option<std::ops::Range<usize>> {
+        let taille_type = match self.u16(tiff, entree + 2)? {
+            1 | 2 | 6 | 7 => 1,
+            3 | 8 => 2,
+            4 | 9 | 11 | 13 => 4,
+            5 | 10 | 12 => 8,
+            _ => return None,
+        };
+        let taille = taille_type * self.u32(tiff, entree + 4)? as usize;
+        let debut = self.u32(tiff, entree + 8)? as usize;
+        (taille > 4 && debut + taille <= tiff.len()).then_some(debut..debut + taille)
+    }
     
-    for y in 0..height {
-        for x in 0..width {
-            let pixel_actuel = img.get_pixel(x, y);
+    /// Retire d'un IFD les balises personnelles et efface leurs valeurs
+    fn filtrer_ifd(&self, tiff: &mut [u8], ifd: usize, repertoire_exif: bool, supprimes: &mut Vec<String>) {
+        let Some(nombre) = self.u16(tiff, ifd).map(|nombre| nombre as usize) else {
+            return;
+        };
+        let fin_entrees = ifd + 2 + nombre * 12;
+        if fin_entrees + 4 > tiff.len() {
+            return;
+        }
+        
+        let mut conservees = Vec::with_capacity(nombre * 12);
+        
+        for index in 0..nombre {
+            let entree = ifd + 2 + index * 12;
+            let balise = self.u16(tiff, entree).unwrap_or(0);
+            let personnelle = BALISES_PERSONNELLES
+                .iter()
+                .find(|(code, _, exif)| *code == balise && *exif == repertoire_exif);
             
-            // Si le pixel est partiellement transparent, adoucir
-            if pixel_actuel.0[3] > 0 && pixel_actuel.0[3] < 255 {
-                let mut alpha_total = 0u32;
-                let mut count = 0u32;
-                
-                // Examiner les pixels voisins
-                for dy in -(rayon as i32)..=(rayon as i32) {
-                    for dx in -(rayon as i32)..=(rayon as i32) {
-                        let nx = x as i32 + dx;
-                        let ny = y as i32 + dy;
-                        
-                        if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                            let voisin = img.get_pixel(nx as u32, ny as u32);
-                            alpha_total += voisin.0[3] as u32;
-                            count += 1;
+            match personnelle {
+                Some((_, nom, _)) => {
+                    if balise == BALISE_IFD_GPS && !repertoire_exif {
+                        if let Some(ifd_gps) = self.u32(tiff, entree + 8) {
+                            self.effacer_ifd(tiff, ifd_gps as usize);
                         }
                     }
+                    if let Some(plage) = self.valeur_deportee(tiff, entree) {
+                        tiff[plage].fill(0);
+                    }
+                    ajouter_element(supprimes, nom);
                 }
-                
-                if count > 0 {
-                    let alpha_moyen = (alpha_total / count) as u8;
-                    nouvelle_img.get_pixel_mut(x, y).0[3] = alpha_moyen;
-                }
+                None => conservees.extend_from_slice(&tiff[entree..entree + 12]),
             }
         }
+        
+        let ifd_suivant: [u8; 4] = tiff[fin_entrees..fin_entrees + 4].try_into().unwrap_or_default();
+        let nouveau_nombre = conservees.len() / 12;
+        let debut_entrees = ifd + 2;
+        
+        self.ecrire_u16(tiff, ifd, nouveau_nombre as u16);
+        tiff[debut_entrees..debut_entrees + conservees.len()].copy_from_slice(&conservees);
+        let position_suivant = debut_entrees + conservees.len();
+        tiff[position_suivant..position_suivant + 4].copy_from_slice(&ifd_suivant);
+        tiff[position_suivant + 4..fin_entrees + 4].fill(0);
     }
     
-    *img = nouvelle_img;
+    /// Met à zéro un IFD complet (entrées et valeurs)
+    fn effacer_ifd(&self, tiff: &mut [u8], ifd: usize) {
+        let Some(nombre) = self.u16(tiff, ifd).map(|nombre| nombre as usize) else {
+            return;
+        };
+        let fin = ifd + 2 + nombre * 12 + 4;
+        if fin > tiff.len() {
+            return;
+        }
+        
+        for index in 0..nombre {
+            if let Some(plage) = self.valeur_deportee(tiff, ifd + 2 + index * 12) {
+                tiff[plage].fill(0);
+            }
+        }
+        tiff[ifd..fin].fill(0);
+    }
 }
 
-fn detecter_format_image(path: &Path) -> ResultatApplication<&'static str> {
-    match path.extension().and_then(|ext| ext.to_str()) {
-        Some(ext) => match ext.to_lowercase().as_str() {
-            "jpg" | "jpeg" => Ok("JPEG"),
-            "png" => Ok("PNG"),
-            "webp" => Ok("WebP"),
-            "bmp" => Ok("BMP"),
-            "tiff" | "tif" => Ok("TIFF"),
-            "gif" => Ok("GIF"),
-            _ => Err(ErreurApplication::Validation {
-                message: format!("Extension de fichier non reconnue : {}", ext),
-            }),
-        },
-        None => Err(ErreurApplication::Validation {
-            message: "Impossible de déterminer le format du fichier".to_string(),
-        }),
+fn ajouter_element(elements: &mut Vec<String>, element: &str) {
+    if !elements.iter().any(|existant| existant == element) {
+        elements.push(element.to_string());
     }
 }
 
@@ -595,4 +5732,1003 @@ mod tests {
         assert_eq!(detecter_format_image(Path::new("test.png")).unwrap(), "PNG");
         assert_eq!(detecter_format_image(Path::new("test.webp")).unwrap(), "WebP");
     }
+    
+    #[test]
+    fn test_appliquer_modele_nom() {
+        let nom = appliquer_modele_nom("{nom}_{largeur}x{hauteur}.{ext}", "vacances", 800, 600, "webp");
+        assert_eq!(nom, "vacances_800x600.webp");
+    }
+    
+    #[tokio::test]
+    async fn test_lister_images_lot_noms_uniques() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_lot_noms_{}", std::process::id()));
+        std::fs::create_dir_all(dossier.join("ete")).unwrap();
+        for nom in ["a.png", "a.jpg", "A.gif", "ete/a.png", "b.png"] {
+            std::fs::write(dossier.join(nom), b"").unwrap();
+        }
+        
+        let racine = dossier.to_string_lossy().to_string();
+        let b = dossier.join("b.png").to_string_lossy().to_string();
+        let fichiers = lister_images_lot(&[racine, b], true).await.unwrap();
+        
+        // b.png, donné deux fois, n'apparaît qu'une fois
+        assert_eq!(fichiers.len(), 5);
+        let mut sorties: Vec<String> = fichiers
+            .iter()
+            .map(|fichier| fichier.sous_dossier.join(&fichier.nom).to_string_lossy().to_lowercase())
+            .collect();
+        sorties.sort();
+        sorties.dedup();
+        assert_eq!(sorties.len(), 5, "{:?}", sorties);
+        assert!(fichiers.iter().any(|fichier| fichier.sous_dossier == Path::new("ete") && fichier.nom == "a"));
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    /// Construit un bloc TIFF/EXIF minimal avec marque, auteur et position GPS
+    fn creer_exif_test() -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        
+        let entree = |tiff: &mut Vec<u8>, balise: u16, type_valeur: u16, nombre: u32, valeur: u32| {
+            tiff.extend_from_slice(&balise.to_le_bytes());
+            tiff.extend_from_slice(&type_valeur.to_le_bytes());
+            tiff.extend_from_slice(&nombre.to_le_bytes());
+            tiff.extend_from_slice(&valeur.to_le_bytes());
+        };
+        
+        // IFD principal en 8, valeurs en 62 et 68, IFD GPS en 74, coordonnées en 104
+        tiff.extend_from_slice(&4u16.to_le_bytes());
+        entree(&mut tiff, 0x010F, 2, 6, 62);
+        entree(&mut tiff, BALISE_ORIENTATION, 3, 1, 6);
+        entree(&mut tiff, 0x013B, 2, 5, 68);
+        entree(&mut tiff, 0x8825, 4, 1, 74);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(b"Canon\0Jean\0\0");
+        
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        entree(&mut tiff, 0x0001, 2, 2, u32::from_le_bytes(*b"N\0\0\0"));
+        entree(&mut tiff, 0x0002, 5, 3, 104);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        for (numerateur, denominateur) in [(48u32, 1u32), (51, 1), (30, 1)] {
+            tiff.extend_from_slice(&numerateur.to_le_bytes());
+            tiff.extend_from_slice(&denominateur.to_le_bytes());
+        }
+        
+        tiff
+    }
+    
+    #[test]
+    fn test_redresser_fichier_vers_dossier_sortie() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_redressement_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        // EXIF minimal : une seule balise Orientation = 6 (rotation de 90°)
+        let tiff = creer_exif_orientation(6);
+        
+        let mut jpeg = encoder_image(&image::DynamicImage::ImageRgb8(image::RgbImage::new(32, 16)), ImageFormat::Jpeg, 90).unwrap();
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        let insertion = position_apres_app0(&jpeg);
+        jpeg.splice(insertion..insertion, segment);
+        
+        let source = dossier.join("photo.jpg");
+        std::fs::write(&source, &jpeg).unwrap();
+        let fichier = FichierLot { source: source.clone(), sous_dossier: PathBuf::from("telephone"), nom: "photo".to_string() };
+        let sortie_dossier = dossier.join("sortie");
+        
+        let IssueConversionLot::Convertie(resultat) = redresser_fichier(&fichier, Some(&sortie_dossier), 90) else {
+            panic!("L'image aurait dû être redressée");
+        };
+        assert_eq!(std::fs::read(&source).unwrap(), jpeg, "L'original doit rester intact");
+        
+        let sortie = std::fs::read(&resultat.fichier_sortie).unwrap();
+        assert_eq!(Path::new(&resultat.fichier_sortie), sortie_dossier.join("telephone").join("photo.jpg"));
+        assert_eq!((resultat.largeur_sortie, resultat.hauteur_sortie), (16, 32));
+        // APP0 JFIF en tête, puis l'EXIF avec l'orientation remise à 1
+        assert_eq!(sortie[2..4], [0xFF, 0xE0]);
+        let app1 = position_apres_app0(&sortie);
+        assert_eq!(sortie[app1..app1 + 2], [0xFF, 0xE1]);
+        assert_eq!(lire_orientation_exif(Path::new(&resultat.fichier_sortie)), Some(1));
+        
+        // Segment de longueur 1 : ignoré sans lire hors du tampon
+        let mut corrompu = jpeg[..2].to_vec();
+        corrompu.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x01, 0xFF, 0xD9]);
+        assert!(extraire_segment_exif_jpeg(&corrompu).is_none());
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[tokio::test]
+    async fn test_redresser_lot_dossier_sortie_source() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_redressement_lot_{}", std::process::id()));
+        std::fs::create_dir_all(dossier.join("vacances")).unwrap();
+        
+        let tiff = creer_exif_orientation(6);
+        let mut jpeg = encoder_image(&image::DynamicImage::ImageRgb8(image::RgbImage::new(32, 16)), ImageFormat::Jpeg, 90).unwrap();
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        let insertion = position_apres_app0(&jpeg);
+        jpeg.splice(insertion..insertion, segment);
+        let source = dossier.join("vacances").join("photo.jpg");
+        std::fs::write(&source, &jpeg).unwrap();
+        
+        // Même dossier écrit autrement : l'image du sous-dossier retomberait sur elle-même
+        let entrees = vec![dossier.to_string_lossy().to_string()];
+        let sortie = dossier.join(".").to_string_lossy().to_string();
+        let erreur = redresser_images_lot(entrees.clone(), true, None, Some(sortie.clone()), None).await.unwrap_err();
+        assert!(erreur.contains("contient les originaux"), "{}", erreur);
+        assert_eq!(std::fs::read(&source).unwrap(), jpeg, "L'original doit rester intact");
+        
+        let bilan = redresser_images_lot(entrees, true, None, Some(sortie), Some(true)).await.unwrap();
+        assert_eq!(bilan.fichiers_convertis, 1);
+        assert_eq!(lire_orientation_exif(&source), Some(1));
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[test]
+    fn test_nettoyage_metadonnees_jpeg() {
+        let mut jpeg = Vec::new();
+        image::RgbImage::new(8, 8)
+            .write_to(&mut std::io::Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+        
+        let exif = creer_exif_test();
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&exif);
+        jpeg.splice(2..2, segment);
+        
+        let lire_exif = |donnees: &[u8]| exif::Reader::new().read_from_container(&mut std::io::Cursor::new(donnees));
+        let origine = lire_exif(&jpeg).unwrap();
+        assert!(origine.get_field(exif::Tag::GPSLatitude, exif::In::PRIMARY).is_some());
+        
+        let mut supprimes = Vec::new();
+        let partiel = nettoyer_jpeg(&jpeg, true, &mut supprimes).unwrap();
+        let expurge = lire_exif(&partiel).unwrap();
+        assert!(expurge.get_field(exif::Tag::Make, exif::In::PRIMARY).is_some(), "La marque doit être conservée");
+        assert!(expurge.get_field(exif::Tag::Artist, exif::In::PRIMARY).is_none());
+        assert!(expurge.get_field(exif::Tag::GPSLatitude, exif::In::PRIMARY).is_none());
+        assert_eq!(supprimes, vec!["Artist".to_string(), "GPS".to_string()]);
+        assert!(image::load_from_memory(&partiel).is_ok(), "Les pixels doivent rester lisibles");
+        
+        let complet = nettoyer_jpeg(&jpeg, false, &mut Vec::new()).unwrap();
+        assert!(lire_exif(&complet).unwrap().get_field(exif::Tag::Make, exif::In::PRIMARY).is_none(), "Seule l'orientation doit subsister");
+        assert!(image::load_from_memory(&complet).is_ok());
+        
+        // Segment de longueur 0 ou 1 : erreur plutôt que panique
+        for longueur in [0u8, 1] {
+            let mut corrompu = jpeg[..2].to_vec();
+            corrompu.extend_from_slice(&[0xFF, 0xE1, 0x00, longueur, 0xFF, 0xD9]);
+            assert!(nettoyer_jpeg(&corrompu, false, &mut Vec::new()).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_nettoyage_complet_conserve_orientation_et_icc() {
+        let mut jpeg = encoder_image(&image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8)), ImageFormat::Jpeg, 90).unwrap();
+        
+        // EXIF complet (orientation 6), profil ICC (APP2) et XMP
+        let exif = creer_exif_test();
+        let mut segments = Vec::new();
+        for (marqueur, entete, contenu) in [
+            (0xE1u8, &b"Exif\0\0"[..], exif),
+            (0xE2, &b"ICC_PROFILE\0\x01\x01"[..], vec![0x42; 32]),
+            (0xE1, &b"http://ns.adobe.com/xap/1.0/\0"[..], b"<x:xmpmeta/>".to_vec()),
+        ] {
+            segments.extend_from_slice(&[0xFF, marqueur]);
+            segments.extend_from_slice(&((entete.len() + contenu.len() + 2) as u16).to_be_bytes());
+            segments.extend_from_slice(entete);
+            segments.extend_from_slice(&contenu);
+        }
+        let insertion = position_apres_app0(&jpeg);
+        jpeg.splice(insertion..insertion, segments);
+        
+        assert_eq!(detecter_xmp_iptc(&jpeg, ImageFormat::Jpeg), (true, false));
+        let lire_exif = |donnees: &[u8]| exif::Reader::new().read_from_container(&mut std::io::Cursor::new(donnees));
+        assert!(lire_exif(&jpeg).unwrap().get_field(exif::Tag::GPSLatitude, exif::In::PRIMARY).is_some());
+        
+        let complet = nettoyer_jpeg(&jpeg, false, &mut Vec::new()).unwrap();
+        let restant = lire_exif(&complet).unwrap();
+        assert_eq!(restant.fields().count(), 1, "Seule l'orientation doit subsister");
+        assert_eq!(restant.get_field(exif::Tag::Orientation, exif::In::PRIMARY).and_then(|champ| champ.value.get_uint(0)), Some(6));
+        assert!(complet.windows(12).any(|fenetre| fenetre == b"ICC_PROFILE\0"), "Le profil ICC doit être conservé");
+        assert_eq!(detecter_xmp_iptc(&complet, ImageFormat::Jpeg), (false, false));
+        assert!(image::load_from_memory(&complet).is_ok());
+        
+        let mut tiff = creer_exif_orientation(1);
+        assert!(!tiff_multipage(&tiff));
+        tiff[22..26].copy_from_slice(&8u32.to_le_bytes());
+        assert!(tiff_multipage(&tiff));
+    }
+    
+    #[test]
+    fn test_appliquer_orientation() {
+        let mut img = image::RgbImage::new(3, 2);
+        img.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        let img = image::DynamicImage::ImageRgb8(img);
+        
+        // Orientation 6 : rotation de 90° dans le sens horaire, le coin haut-gauche passe en haut à droite
+        let redressee = appliquer_orientation(img.clone(), 6).to_rgb8();
+        assert_eq!(redressee.dimensions(), (2, 3));
+        assert_eq!(redressee.get_pixel(1, 0).0, [255, 0, 0]);
+        
+        // Orientation 5 : transposition, le coin haut-gauche reste en place
+        let transposee = appliquer_orientation(img.clone(), 5).to_rgb8();
+        assert_eq!(transposee.dimensions(), (2, 3));
+        assert_eq!(transposee.get_pixel(0, 0).0, [255, 0, 0]);
+        
+        assert_eq!(appliquer_orientation(img, 1).width(), 3);
+    }
+    
+    #[test]
+    fn test_transformations() {
+        assert_eq!(calculer_recadrage_ratio(400, 300, 1, 1, "centre").unwrap(), (50, 0, 300, 300));
+        assert_eq!(calculer_recadrage_ratio(400, 300, 16, 9, "haut").unwrap(), (0, 0, 400, 225));
+        assert_eq!(calculer_recadrage_ratio(400, 300, 1, 1, "droite").unwrap(), (100, 0, 300, 300));
+        assert!(calculer_recadrage_ratio(400, 300, 1, 1, "milieu").is_err());
+        
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(100, 50, image::Rgb([200, 10, 10])));
+        let etapes = vec![
+            EtapeTransformation::Marge { haut: 5, droite: 5, bas: 5, gauche: 5, couleur: "#FFFFFF".to_string() },
+            EtapeTransformation::Rotation { angle_degres: 90.0, couleur_fond: "transparent".to_string() },
+            EtapeTransformation::CoinsArrondis { rayon: 10 },
+        ];
+        let resultat = appliquer_transformations(img, &etapes).unwrap().to_rgba8();
+        assert_eq!(resultat.dimensions(), (60, 110));
+        assert_eq!(resultat.get_pixel(0, 0).0[3], 0, "Le coin doit être transparent");
+        assert_eq!(resultat.get_pixel(30, 55).0, [200, 10, 10, 255]);
+        
+        let pivotee = pivoter_image(&image::RgbaImage::new(100, 100), 45.0, [0, 0, 0, 0]);
+        assert_eq!(pivotee.dimensions(), (141, 141));
+        
+        // Valeurs extrêmes : erreur de validation, jamais de débordement
+        let petite = image::DynamicImage::ImageRgb8(image::RgbImage::new(4, 4));
+        for etape in [
+            EtapeTransformation::Recadrage { x: u32::MAX, y: 0, largeur: 2, hauteur: 2 },
+            EtapeTransformation::Recadrage { x: 0, y: 1, largeur: 1, hauteur: u32::MAX },
+            EtapeTransformation::Marge { haut: 0, droite: u32::MAX, bas: 0, gauche: 1, couleur: "#FFFFFF".to_string() },
+        ] {
+            assert!(matches!(appliquer_transformations(petite.clone(), &[etape]), Err(ErreurApplication::Validation { .. })));
+        }
+        let bordee = appliquer_transformations(petite, &[EtapeTransformation::Bordure { epaisseur: u32::MAX, couleur: "#FF0000".to_string() }]).unwrap();
+        assert_eq!(bordee.to_rgba8().get_pixel(2, 2).0, [255, 0, 0, 255]);
+        
+        assert!(parser_couleur_rgba("#FFFFFFé").is_err());
+        assert!(parser_couleur_rgba("aé12345").is_err());
+        assert_eq!(parser_couleur_rgba("#10203040").unwrap(), [0x10, 0x20, 0x30, 0x40]);
+    }
+    
+    #[test]
+    fn test_reglages() {
+        // Image terne : niveaux entre 100 et 150
+        let terne = image::RgbImage::from_fn(51, 1, |x, _| image::Rgb([100 + x as u8; 3]));
+        let etiree = appliquer_reglages(
+            image::DynamicImage::ImageRgb8(terne),
+            &[ReglageImage::NiveauxAutomatiques { ecretage_pourcent: 0.0 }],
+        ).to_rgba8();
+        assert_eq!(etiree.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(etiree.get_pixel(50, 0).0, [255, 255, 255, 255]);
+        
+        let gris = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba([64, 64, 64, 128])));
+        let corrige = appliquer_reglages(gris.clone(), &[ReglageImage::Gamma { valeur: 2.0 }]).to_rgba8();
+        assert_eq!(corrige.get_pixel(0, 0).0, [128, 128, 128, 128], "Le gamma ne doit pas toucher à l'alpha");
+        
+        let rouge = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([200, 0, 0])));
+        let desature = appliquer_reglages(rouge, &[ReglageImage::Saturation { facteur: 0.0 }]).to_rgba8();
+        assert_eq!(desature.get_pixel(0, 0).0, [60, 60, 60, 255]);
+    }
+    
+    #[test]
+    fn test_encodage_taille_cible() {
+        // Bruit pseudo-aléatoire : difficile à compresser
+        let mut graine = 12345u32;
+        let bruit = image::RgbImage::from_fn(256, 256, |_, _| {
+            graine = graine.wrapping_mul(1103515245).wrapping_add(12345);
+            image::Rgb([(graine >> 16) as u8, (graine >> 8) as u8, (graine >> 24) as u8])
+        });
+        let img = image::DynamicImage::ImageRgb8(bruit);
+        
+        let mut options = OptionsConversion {
+            format_sortie: "jpeg".to_string(),
+            qualite: Some(90),
+            largeur_max: None,
+            hauteur_max: None,
+            conserver_ratio: true,
+            transformations: None,
+            reglages: None,
+            taille_cible_octets: Some(60_000),
+            filigrane: None,
+        };
+        
+        let encodage = encoder_sortie(&img, ImageFormat::Jpeg, &options).unwrap();
+        assert!(encodage.octets.len() <= 60_000);
+        assert!(encodage.qualite.is_some());
+        
+        // Cible inatteignable par la seule qualité : les dimensions doivent baisser
+        options.taille_cible_octets = Some(8_000);
+        let reduite = encoder_sortie(&img, ImageFormat::Jpeg, &options).unwrap();
+        assert!(reduite.octets.len() <= 8_000);
+        assert!(reduite.largeur < 256 && reduite.hauteur < 256);
+        assert_eq!(reduite.largeur, reduite.hauteur, "Le ratio doit être conservé");
+    }
+    
+    #[test]
+    fn test_encodage_webp_avif() {
+        let degrade = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(32, 32, |x, y| image::Rgb([x as u8 * 8, y as u8 * 8, 128])));
+        
+        let webp = encoder_image(&degrade, ImageFormat::WebP, 50).unwrap();
+        assert_eq!(image::guess_format(&webp).unwrap(), ImageFormat::WebP);
+        assert_eq!(image::load_from_memory(&webp).unwrap().width(), 32);
+        
+        // Au-delà de 16383 pixels de côté, refus plutôt que panique de libwebp
+        let large = image::DynamicImage::ImageRgb8(image::RgbImage::new(DIMENSION_MAX_WEBP + 1, 1));
+        assert!(matches!(encoder_image(&large, ImageFormat::WebP, 50), Err(ErreurApplication::Validation { .. })));
+        
+        let avif = encoder_image(&degrade, ImageFormat::Avif, 50).unwrap();
+        assert_eq!(deviner_format(&avif), Some(ImageFormat::Avif));
+        assert_eq!(detecter_format_image(Path::new("photo.avif")).unwrap(), "AVIF");
+        
+        // L'AVIF n'est pas relu : erreur explicite
+        let chemin = std::env::temp_dir().join(format!("macgyver_avif_{}.avif", std::process::id()));
+        std::fs::write(&chemin, &avif).unwrap();
+        assert!(matches!(charger_image(&chemin), Err(ErreurApplication::Validation { .. })));
+        std::fs::remove_file(&chemin).unwrap();
+    }
+    
+    #[test]
+    fn test_optimisation_png_jpeg() {
+        let options = OptionsOptimisation {
+            niveau_png: Some(2),
+            quantifier: false,
+            tramage: false,
+            reencoder: true,
+            qualite: None,
+            progressif: true,
+            sous_echantillonnage: None,
+            conserver_metadonnees: true,
+        };
+        
+        // PNG RGBA à quatre couleurs opaques : réduit sans perte en palette
+        let aplats = image::DynamicImage::ImageRgba8(image::RgbaImage::from_fn(64, 64, |x, y| {
+            image::Rgba([if x < 32 { 255 } else { 0 }, if y < 32 { 255 } else { 0 }, 0, 255])
+        }));
+        let png = encoder_image(&aplats, ImageFormat::Png, 100).unwrap();
+        let optimise = optimiser_png(&png, &options).unwrap();
+        assert!(optimise.len() < png.len());
+        assert_eq!(image::load_from_memory(&optimise).unwrap().to_rgba8(), aplats.to_rgba8());
+        
+        // Quantification d'un dégradé : 256 couleurs au plus
+        let degrade = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([x as u8 * 4, y as u8 * 4, (x + y) as u8])));
+        let png = encoder_image(&degrade, ImageFormat::Png, 100).unwrap();
+        let quantifie = optimiser_png(&png, &OptionsOptimisation { quantifier: true, tramage: true, ..options.clone() }).unwrap();
+        let couleurs: std::collections::HashSet<_> = image::load_from_memory(&quantifie).unwrap().to_rgba8().pixels().copied().collect();
+        assert!(couleurs.len() <= 256);
+        
+        // JPEG : qualité d'origine retrouvée et EXIF recopié
+        let mut jpeg = encoder_image(&degrade, ImageFormat::Jpeg, 90).unwrap();
+        let exif = creer_exif_test();
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&exif);
+        jpeg.splice(2..2, segment);
+        let qualite = estimer_qualite_jpeg(&jpeg).unwrap();
+        assert!((88..=92).contains(&qualite));
+        
+        let (progressif, qualite_utilisee) = optimiser_jpeg(&jpeg, &options).unwrap();
+        assert_eq!(qualite_utilisee, Some(qualite));
+        assert!(extraire_segment_exif_jpeg(&progressif).is_some());
+        assert_eq!(image::load_from_memory(&progressif).unwrap().width(), 64);
+    }
+    
+    #[test]
+    fn test_transcodage_jpeg_sans_perte() {
+        // Dimensions hors multiples de 16, chrominance 4:2:0 et intervalles de reprise
+        let degrade = image::RgbImage::from_fn(70, 45, |x, y| image::Rgb([(x * 3) as u8, (y * 5) as u8, ((x ^ y) * 7) as u8]));
+        let mut jpeg = Vec::new();
+        let mut encodeur = jpeg_encoder::Encoder::new(&mut jpeg, 80);
+        encodeur.set_restart_interval(3);
+        encodeur.encode(degrade.as_raw(), 70, 45, jpeg_encoder::ColorType::Rgb).unwrap();
+        let pixels = image::load_from_memory(&jpeg).unwrap().to_rgb8();
+        
+        let options = OptionsOptimisation {
+            niveau_png: None,
+            quantifier: false,
+            tramage: false,
+            reencoder: false,
+            qualite: None,
+            progressif: false,
+            sous_echantillonnage: None,
+            conserver_metadonnees: false,
+        };
+        for progressif in [false, true] {
+            let (sortie, qualite) = optimiser_jpeg(&jpeg, &OptionsOptimisation { progressif, ..options.clone() }).unwrap();
+            assert_eq!(qualite, None, "Aucun réencodage ne doit avoir lieu");
+            assert!(sortie.len() < jpeg.len(), "Les tables optimales doivent réduire le fichier");
+            assert_eq!(sortie.windows(2).any(|marqueur| marqueur == [0xFF, 0xC2]), progressif);
+            assert_eq!(image::load_from_memory(&sortie).unwrap().to_rgb8(), pixels, "Les pixels doivent être identiques");
+        }
+        
+        // Un JPEG déjà progressif est rendu tel quel
+        let progressif = optimiser_jpeg(&jpeg, &OptionsOptimisation { progressif: true, ..options.clone() }).unwrap().0;
+        assert_eq!(optimiser_jpeg(&progressif, &options).unwrap().0, progressif);
+        
+        // Niveaux de gris
+        let gris = encoder_image(&image::DynamicImage::ImageLuma8(image::GrayImage::from_fn(33, 17, |x, y| image::Luma([(x * y) as u8]))), ImageFormat::Jpeg, 90).unwrap();
+        let (sortie, _) = optimiser_jpeg(&gris, &options).unwrap();
+        assert_eq!(image::load_from_memory(&sortie).unwrap().to_luma8(), image::load_from_memory(&gris).unwrap().to_luma8());
+        
+        // Segment de longueur 1 : rendu tel quel plutôt que de paniquer
+        let mut corrompu = jpeg[..2].to_vec();
+        corrompu.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x01, 0xFF, 0xD9]);
+        assert_eq!(optimiser_jpeg(&corrompu, &options).unwrap().0, corrompu);
+    }
+    
+    #[test]
+    fn test_optimisation_jpeg_conserve_orientation() {
+        // EXIF complet avec orientation 6 (photo prise en portrait)
+        let mut jpeg = encoder_image(&image::DynamicImage::ImageRgb8(image::RgbImage::new(32, 16)), ImageFormat::Jpeg, 90).unwrap();
+        let exif = creer_exif_test();
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((exif.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&exif);
+        let insertion = position_apres_app0(&jpeg);
+        jpeg.splice(insertion..insertion, segment);
+        
+        let options = OptionsOptimisation {
+            niveau_png: None,
+            quantifier: false,
+            tramage: false,
+            reencoder: false,
+            qualite: None,
+            progressif: false,
+            sous_echantillonnage: None,
+            conserver_metadonnees: false,
+        };
+        let lire_exif = |donnees: &[u8]| exif::Reader::new().read_from_container(&mut std::io::Cursor::new(donnees));
+        for reencoder in [false, true] {
+            let (sortie, _) = optimiser_jpeg(&jpeg, &OptionsOptimisation { reencoder, ..options.clone() }).unwrap();
+            let exif = lire_exif(&sortie).expect("L'orientation doit être conservée");
+            let orientation = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY).and_then(|champ| champ.value.get_uint(0));
+            assert_eq!(orientation, Some(6));
+            assert!(exif.get_field(exif::Tag::Make, exif::In::PRIMARY).is_none(), "Le reste de l'EXIF doit être retiré");
+            assert_eq!(sortie[2..4], [0xFF, 0xE0], "L'APP0 JFIF doit rester en tête");
+        }
+    }
+    
+    #[test]
+    fn test_suppression_fond_depuis_bords() {
+        // Fond blanc, carré noir creux dont l'intérieur est blanc lui aussi
+        let mut img = image::RgbaImage::from_pixel(20, 20, image::Rgba([250, 250, 250, 255]));
+        for i in 5..15 {
+            for (x, y) in [(i, 5), (i, 14), (5, i), (14, i)] {
+                img.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+            }
+        }
+        
+        assert_eq!(detecter_couleur_bordure(&img), Some([250, 250, 250]));
+        
+        let mut bords = img.clone();
+        remplir_fond_depuis(&mut bords, &pixels_pourtour(20, 20), [255, 255, 255], 20, EspaceCouleur::Rgb);
+        assert_eq!(bords.get_pixel(0, 0).0[3], 0);
+        assert_eq!(bords.get_pixel(5, 5).0[3], 255);
+        assert_eq!(bords.get_pixel(10, 10).0[3], 255);
+        
+        let mut global = img.clone();
+        supprimer_couleur_fond(&mut global, [255, 255, 255], 20, EspaceCouleur::Rgb);
+        assert_eq!(global.get_pixel(10, 10).0[3], 0);
+        
+        let mut interieur = img;
+        remplir_fond_depuis(&mut interieur, &[(10, 10)], [250, 250, 250], 20, EspaceCouleur::Lab);
+        assert_eq!(interieur.get_pixel(10, 10).0[3], 0);
+        assert_eq!(interieur.get_pixel(0, 0).0[3], 255);
+        
+        // En HSV, un gris sombre reste proche du noir quelle que soit sa teinte
+        let noir = EspaceCouleur::Hsv.coordonnees([0, 0, 0]);
+        assert!(EspaceCouleur::Hsv.distance(noir, [20, 10, 10]) < 25.0);
+        let blanc_lab = EspaceCouleur::Lab.coordonnees([255, 255, 255]);
+        assert!((blanc_lab[0] - 255.0).abs() < 0.5 && blanc_lab[1].abs() < 0.5);
+    }
+    
+    #[test]
+    fn test_adoucissement_et_decontamination() {
+        let mut img = image::RgbaImage::from_fn(40, 30, |x, y| {
+            let alpha = match (x + 2 * y) % 7 {
+                0 => 0,
+                1 | 2 => 255,
+                reste => reste as u8 * 40,
+            };
+            image::Rgba([10, 20, 30, alpha])
+        });
+        
+        // Référence : moyenne directe sur la fenêtre tronquée, comme l'ancienne version
+        let rayon = 3i32;
+        let mut attendu = img.clone();
+        for (x, y, pixel) in attendu.enumerate_pixels_mut() {
+            let alpha = img.get_pixel(x, y).0[3];
+            if alpha == 0 || alpha == 255 {
+                continue;
+            }
+            let (mut total, mut nombre) = (0u32, 0u32);
+            for dy in -rayon..=rayon {
+                for dx in -rayon..=rayon {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if (0..40).contains(&nx) && (0..30).contains(&ny) {
+                        total += img.get_pixel(nx as u32, ny as u32).0[3] as u32;
+                        nombre += 1;
+                    }
+                }
+            }
+            pixel.0[3] = (total / nombre) as u8;
+        }
+        adoucir_bords_transparence(&mut img, rayon as u8);
+        assert_eq!(img, attendu);
+        
+        // Le fondu n'augmente jamais l'alpha
+        let mut disque = image::RgbaImage::from_fn(21, 21, |x, y| {
+            let (dx, dy) = (x as i32 - 10, y as i32 - 10);
+            image::Rgba([0, 0, 0, if dx * dx + dy * dy <= 64 { 255 } else { 0 }])
+        });
+        let origine = disque.clone();
+        fondre_contour(&mut disque, 2);
+        assert_eq!(disque.get_pixel(0, 0).0[3], 0);
+        assert_eq!(disque.get_pixel(10, 10).0[3], 255);
+        assert!(disque.get_pixel(10, 2).0[3] < 255);
+        assert!(disque.pixels().zip(origine.pixels()).all(|(apres, avant)| apres.0[3] <= avant.0[3]));
+        
+        // Rouge mélangé à moitié avec un fond blanc : le rouge pur est retrouvé
+        let mut bord = image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 128, 128, 128]));
+        decontaminer_couleurs(&mut bord, [255, 255, 255]);
+        let [r, g, b, _] = bord.get_pixel(0, 0).0;
+        assert_eq!(r, 255);
+        assert!(g < 5 && b < 5);
+    }
+    
+    #[test]
+    fn test_cle_chromatique() {
+        // Fond vert, sujet couleur peau teinté par le reflet du fond
+        let mut img = image::RgbaImage::from_pixel(10, 10, image::Rgba([20, 200, 40, 255]));
+        img.put_pixel(5, 5, image::Rgba([200, 210, 120, 255]));
+        img.put_pixel(6, 5, image::Rgba([110, 170, 100, 255]));
+        
+        let cle = OptionsCleChromatique {
+            tolerance_teinte: 25.0,
+            tolerance_saturation: 0.4,
+            tolerance_valeur: 0.5,
+            seuil_exterieur: 2.0,
+            supprimer_debordement: true,
+        };
+        let couleur_cle = detecter_couleur_bordure(&img).unwrap();
+        incruster_cle_chromatique(&mut img, couleur_cle, &cle);
+        
+        assert_eq!(img.get_pixel(0, 0).0[3], 0);
+        assert_eq!(img.get_pixel(5, 5).0, [200, 200, 120, 255]);
+        
+        // Vert désaturé : entre les seuils, donc semi-transparent
+        let alpha = img.get_pixel(6, 5).0[3];
+        assert!(alpha > 0 && alpha < 255);
+    }
+    
+    #[test]
+    fn test_composition_sur_fond() {
+        let mut sujet = image::RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 0, 0]));
+        sujet.put_pixel(3, 3, image::Rgba([255, 0, 0, 255]));
+        sujet.put_pixel(4, 3, image::Rgba([255, 0, 0, 128]));
+        
+        let colore = composer_sur_fond(&sujet, &FondComposition::Couleur { couleur: "#0000FF".to_string() }).unwrap();
+        assert_eq!(colore.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(colore.get_pixel(3, 3).0, [255, 0, 0, 255]);
+        let melange = colore.get_pixel(4, 3).0;
+        assert_eq!(melange, [128, 0, 127, 255]);
+        
+        let damier = composer_sur_fond(&sujet, &FondComposition::Damier { taille_case: 2 }).unwrap();
+        assert_eq!(damier.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(damier.get_pixel(2, 0).0, [204, 204, 204, 255]);
+        
+        assert!(gere_transparence(ImageFormat::WebP));
+        assert!(!gere_transparence(ImageFormat::Jpeg));
+        
+        // Le WebP transparent conserve la couche alpha
+        let webp = encoder_image(&image::DynamicImage::ImageRgba8(sujet), ImageFormat::WebP, 100).unwrap();
+        let relu = image::load_from_memory(&webp).unwrap().to_rgba8();
+        assert_eq!(relu.get_pixel(0, 0).0[3], 0);
+        assert_eq!(relu.get_pixel(3, 3).0, [255, 0, 0, 255]);
+    }
+    
+    #[test]
+    fn test_filigrane_texte_et_position() {
+        let texte = rendre_texte("MacGyver", 200, [255, 255, 255, 255]).unwrap();
+        assert_eq!(texte.width(), 200);
+        assert!(texte.pixels().any(|pixel| pixel.0[3] == 255));
+        assert!(texte.pixels().any(|pixel| pixel.0[3] == 0));
+        
+        let fond = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(400, 300, image::Rgb([0, 0, 0])));
+        let mut filigrane = OptionsFiligrane {
+            contenu: ContenuFiligrane::Texte { texte: "©".to_string(), couleur: "#FFFFFF".to_string() },
+            position: "bas_droite".to_string(),
+            marge: 10,
+            opacite: 1.0,
+            echelle: 0.1,
+            rotation_degres: 0.0,
+        };
+        
+        let rgba = appliquer_filigrane(fond.clone(), &filigrane).unwrap().to_rgba8();
+        let (eclaires_x, eclaires_y): (Vec<u32>, Vec<u32>) = rgba
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] > 128)
+            .map(|(x, y, _)| (x, y))
+            .unzip();
+        assert!(!eclaires_x.is_empty());
+        assert!(eclaires_x.iter().all(|x| (350..390).contains(x)));
+        assert!(eclaires_y.iter().all(|y| (250..290).contains(y)));
+        
+        filigrane.position = "mosaique".to_string();
+        filigrane.opacite = 0.5;
+        let mosaique = appliquer_filigrane(fond, &filigrane).unwrap().to_rgba8();
+        assert!(mosaique.enumerate_pixels().any(|(x, y, pixel)| x < 50 && y < 50 && pixel.0[0] > 60));
+        assert!(mosaique.pixels().all(|pixel| pixel.0[0] <= 128 && pixel.0[3] == 255));
+        
+        filigrane.position = "milieu".to_string();
+        assert!(appliquer_filigrane(image::DynamicImage::new_rgb8(10, 10), &filigrane).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_ajouter_filigrane_image() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_filigrane_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        let entree = dossier.join("noir.png");
+        let sortie = dossier.join("signe.png");
+        image::RgbImage::from_pixel(400, 300, image::Rgb([0, 0, 0])).save(&entree).unwrap();
+        
+        let filigrane = OptionsFiligrane {
+            contenu: ContenuFiligrane::Texte { texte: "©".to_string(), couleur: "#FFFFFF".to_string() },
+            position: "bas_droite".to_string(),
+            marge: 10,
+            opacite: 1.0,
+            echelle: 0.1,
+            rotation_degres: 0.0,
+        };
+        ajouter_filigrane_image(entree.to_string_lossy().to_string(), sortie.to_string_lossy().to_string(), filigrane, None)
+            .await
+            .unwrap();
+        
+        // Le filigrane doit figurer dans le fichier écrit, dans le coin demandé
+        let resultat = image::open(&sortie).unwrap().to_rgb8();
+        let eclaires: Vec<(u32, u32)> = resultat
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] > 128)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!eclaires.is_empty(), "Aucun filigrane dans l'image écrite");
+        assert!(eclaires.iter().all(|&(x, y)| x >= 350 && y >= 250));
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[tokio::test]
+    async fn test_generer_icones() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_icones_{}", std::process::id()));
+        let source = dossier.join("logo.png");
+        std::fs::create_dir_all(&dossier).unwrap();
+        image::RgbaImage::from_fn(300, 200, |x, _| image::Rgba([x as u8, 0, 0, if x < 150 { 0 } else { 255 }]))
+            .save(&source)
+            .unwrap();
+        
+        let options = OptionsIcones {
+            nom_application: "MacGyver".to_string(),
+            nom_court: None,
+            couleur_theme: Some("#336699".to_string()),
+            couleur_fond: None,
+        };
+        let resultat = generer_icones_interne(&source.to_string_lossy(), &dossier.to_string_lossy(), &options)
+            .await
+            .unwrap();
+        
+        assert_eq!(resultat.fichiers.len(), 9);
+        assert!(resultat.message.contains("recadrée"));
+        
+        let ico = std::fs::read(dossier.join("favicon.ico")).unwrap();
+        assert_eq!(u16::from_le_bytes([ico[4], ico[5]]), 4);
+        assert_eq!(image::load_from_memory(&ico).unwrap().width(), 256);
+        
+        let apple = image::open(dossier.join("apple-touch-icon.png")).unwrap();
+        assert_eq!((apple.width(), apple.height()), (180, 180));
+        assert!(!apple.color().has_alpha());
+        assert_eq!(apple.to_rgb8().get_pixel(0, 0).0, [255, 255, 255]);
+        
+        let manifeste: serde_json::Value = serde_json::from_str(&resultat.manifeste).unwrap();
+        assert_eq!(manifeste["short_name"], "MacGyver");
+        assert_eq!(manifeste["icons"][1]["sizes"], "512x512");
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[tokio::test]
+    async fn test_animations_gif_et_webp() {
+        let couleurs = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 128]];
+        let trames: Vec<Trame> = couleurs
+            .iter()
+            .zip([100, 200, 300])
+            .map(|(couleur, delai_ms)| Trame { image: image::RgbaImage::from_pixel(64, 48, image::Rgba(*couleur)), delai_ms })
+            .collect();
+        
+        let dossier = std::env::temp_dir().join(format!("macgyver_animation_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        for (nom, format) in [("anim.webp", ImageFormat::WebP), ("anim.gif", ImageFormat::Gif)] {
+            let chemin = dossier.join(nom);
+            std::fs::write(&chemin, encoder_animation(&trames, format, 100, 0).unwrap()).unwrap();
+            
+            let relues = lire_trames(&chemin).unwrap();
+            assert_eq!(relues.len(), 3, "{}", nom);
+            assert_eq!(relues.iter().map(|trame| trame.delai_ms).collect::<Vec<_>>(), vec![100, 200, 300]);
+            assert_eq!(relues[0].image.dimensions(), (64, 48));
+            assert_eq!(relues[1].image.get_pixel(10, 10).0, [0, 255, 0, 255]);
+            
+            // Durées lues sans décoder les images
+            assert_eq!(lire_delais_trames(&std::fs::read(&chemin).unwrap(), format), Some(vec![100, 200, 300]), "{}", nom);
+        }
+        
+        // Une animation convertie vers un format animé garde toutes ses images,
+        // vers un format fixe le résultat signale la perte
+        let entree = dossier.join("anim.gif").to_string_lossy().to_string();
+        let mut options = OptionsConversion {
+            format_sortie: "webp".to_string(),
+            qualite: Some(100),
+            largeur_max: None,
+            hauteur_max: None,
+            conserver_ratio: true,
+            transformations: None,
+            reglages: None,
+            taille_cible_octets: None,
+            filigrane: None,
+        };
+        let sortie = dossier.join("converti.webp");
+        convertir_image_interne(&entree, &sortie.to_string_lossy(), &options).await.unwrap();
+        assert_eq!(compter_trames(&sortie), 3);
+        
+        options.format_sortie = "png".to_string();
+        let resultat = convertir_image_interne(&entree, &dossier.join("premiere.png").to_string_lossy(), &options).await.unwrap();
+        assert!(resultat.message.contains("seule la première"), "{}", resultat.message);
+        
+        options.format_sortie = "gif".to_string();
+        options.largeur_max = Some(10);
+        assert!(convertir_image_interne(&entree, &dossier.join("reduit.gif").to_string_lossy(), &options).await.is_err());
+        
+        // Le WebP sans perte conserve la transparence partielle
+        let webp = lire_trames(&dossier.join("anim.webp")).unwrap();
+        assert_eq!(webp[2].image.get_pixel(0, 0).0, [0, 0, 255, 128]);
+        
+        assert!(encoder_animation(&trames, ImageFormat::Png, 100, 0).is_err());
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[tokio::test]
+    async fn test_comparer_images() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_comparaison_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        let a = image::RgbaImage::from_fn(40, 20, |x, y| image::Rgba([(x * 6) as u8, (y * 12) as u8, 128, 255]));
+        let mut b = a.clone();
+        for x in 0..10 {
+            for y in 0..4 {
+                b.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+            }
+        }
+        let chemin_a = dossier.join("a.png");
+        let chemin_b = dossier.join("b.png");
+        a.save(&chemin_a).unwrap();
+        b.save(&chemin_b).unwrap();
+        let (chemin_a, chemin_b) = (chemin_a.to_string_lossy().to_string(), chemin_b.to_string_lossy().to_string());
+        
+        let identiques = comparer_pixels(&a, &a, 0);
+        assert_eq!(identiques.pixels_differents, 0);
+        assert_eq!(identiques.psnr, None);
+        assert!((identiques.ssim - 1.0).abs() < 1e-9);
+        
+        let options = OptionsComparaison {
+            chemin_diff: Some(dossier.join("diff.png").to_string_lossy().to_string()),
+            tolerance: None,
+            alignement: None,
+        };
+        let resultat = comparer_images_interne(&chemin_a, &chemin_b, &options).await.unwrap();
+        assert_eq!(resultat.pixels_differents, 40);
+        assert!((resultat.pourcentage_differents - 5.0).abs() < 1e-4);
+        assert!(resultat.ssim < 1.0);
+        
+        // PSNR recalculé à la main sur les canaux RGB
+        let somme_carres: f64 = a
+            .pixels()
+            .zip(b.pixels())
+            .flat_map(|(p, q)| (0..3).map(move |c| (p.0[c] as f64 - q.0[c] as f64).powi(2)))
+            .sum();
+        let attendu = 10.0 * (255.0f64.powi(2) / (somme_carres / (40.0 * 20.0 * 3.0))).log10();
+        assert!((resultat.psnr.unwrap() - attendu).abs() < 1e-9);
+        
+        let diff = image::open(dossier.join("diff.png")).unwrap().to_rgba8();
+        assert_eq!(diff.get_pixel(2, 2).0[0], 255);
+        assert!(diff.get_pixel(2, 2).0[1] < 128);
+        assert_eq!(diff.get_pixel(30, 15).0[0], diff.get_pixel(30, 15).0[1]);
+        
+        // Tailles différentes : recadrage sur la zone commune
+        let petite = dossier.join("petite.png");
+        image::imageops::crop_imm(&a, 0, 0, 30, 20).to_image().save(&petite).unwrap();
+        let options = OptionsComparaison { chemin_diff: None, tolerance: None, alignement: Some("recadrer".to_string()) };
+        let resultat = comparer_images_interne(&chemin_a, &petite.to_string_lossy(), &options).await.unwrap();
+        assert!(!resultat.dimensions_identiques);
+        assert_eq!((resultat.largeur, resultat.pixels_differents), (30, 0));
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[tokio::test]
+    async fn test_assembler_images() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_assemblage_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        let mut chemins = Vec::new();
+        for (nom, largeur, hauteur, couleur) in [("rouge", 40, 20, [255, 0, 0, 255]), ("vert", 30, 30, [0, 255, 0, 255]), ("bleu pâle", 10, 50, [0, 0, 255, 255])] {
+            let chemin = dossier.join(format!("{}.png", nom));
+            image::RgbaImage::from_pixel(largeur, hauteur, image::Rgba(couleur)).save(&chemin).unwrap();
+            chemins.push(chemin.to_string_lossy().to_string());
+        }
+        
+        // Sprites : deux par ligne, sans marge, fond transparent
+        let sortie = dossier.join("sprites.png").to_string_lossy().to_string();
+        let options = OptionsAssemblage {
+            disposition: DispositionAssemblage::Sprites { colonnes: Some(2), prefixe_classe: None },
+            espacement: None,
+            couleur_fond: None,
+            qualite: None,
+        };
+        let resultat = assembler_images_interne(&chemins, &sortie, &options).await.unwrap();
+        assert_eq!((resultat.largeur, resultat.hauteur), (70, 80));
+        assert_eq!((resultat.positions[1].x, resultat.positions[1].y), (40, 0));
+        assert_eq!((resultat.positions[2].x, resultat.positions[2].y), (0, 30));
+        
+        let planche = image::open(&sortie).unwrap().to_rgba8();
+        assert_eq!(planche.get_pixel(45, 5).0, [0, 255, 0, 255]);
+        assert_eq!(planche.get_pixel(45, 35).0[3], 0);
+        let css = std::fs::read_to_string(resultat.fichier_css.unwrap()).unwrap();
+        assert!(css.contains(".sprite-bleu-p-le {\n  width: 10px;\n  height: 50px;\n  background-position: -0px -30px;"));
+        assert!(std::fs::read_to_string(resultat.fichier_json.unwrap()).unwrap().contains("\"classe\": \"sprite-vert\""));
+        
+        // Noms réduits à la même classe : suffixes distincts
+        let positions: Vec<PositionImage> = ["a b", "a-b", "a b"]
+            .iter()
+            .map(|nom| PositionImage { chemin: String::new(), nom: nom.to_string(), x: 0, y: 0, largeur: 1, hauteur: 1 })
+            .collect();
+        assert_eq!(classes_sprites(&positions, "icone"), vec!["icone-a-b", "icone-a-b-2", "icone-a-b-3"]);
+        
+        // Planche contact avec légendes : cases de 64 px et 10 px de marge
+        let sortie = dossier.join("planche.jpg").to_string_lossy().to_string();
+        let options = OptionsAssemblage {
+            disposition: DispositionAssemblage::PlancheContact { colonnes: 3, taille_vignette: 64, legendes: true },
+            espacement: None,
+            couleur_fond: None,
+            qualite: Some(90),
+        };
+        let resultat = assembler_images_interne(&chemins, &sortie, &options).await.unwrap();
+        assert_eq!((resultat.largeur, resultat.hauteur), (10 + 3 * 74, 10 + 64 + 10 + 10));
+        
+        // Un fond transparent est refusé en JPEG
+        let options = OptionsAssemblage { couleur_fond: Some("transparent".to_string()), ..options };
+        assert!(assembler_images_interne(&chemins, &sortie, &options).await.is_err());
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[test]
+    fn test_analyser_couleurs() {
+        // Trois quarts de rouge, un quart de bleu, quelques pixels transparents
+        let mut img = image::RgbaImage::from_fn(80, 40, |x, _| {
+            if x < 60 { image::Rgba([220, 20, 30, 255]) } else { image::Rgba([10, 40, 200, 255]) }
+        });
+        img.put_pixel(0, 0, image::Rgba([0, 0, 0, 0]));
+        
+        let analyse = analyser_couleurs(&img, 4);
+        assert_eq!(analyse.palette[0].hex, "#dc141e");
+        assert!((analyse.palette[0].pourcentage - 75.0).abs() < 0.1);
+        assert_eq!(analyse.palette[1].rgb, [10, 40, 200]);
+        assert_eq!(analyse.palette.len(), 2);
+        
+        assert!(analyse.alpha_utilise);
+        assert!(!analyse.niveaux_de_gris);
+        assert_eq!(analyse.histogrammes.rouge[220], 60 * 40 - 1);
+        assert_eq!(analyse.histogrammes.alpha[0], 1);
+        assert_eq!(analyse.couleur_fond_probable.as_deref(), Some("#dc141e"));
+        
+        let gris = image::RgbaImage::from_fn(32, 32, |x, y| {
+            let v = (x * 8) as u8;
+            image::Rgba([v, v + (y % 3) as u8, v, 255])
+        });
+        let analyse = analyser_couleurs(&gris, 8);
+        assert!(analyse.niveaux_de_gris);
+        assert!(!analyse.alpha_utilise);
+        assert!((analyse.luminosite_moyenne - 124.6).abs() < 1.0);
+    }
+    
+    #[tokio::test]
+    async fn test_rechercher_doublons() {
+        let dossier = std::env::temp_dir().join(format!("macgyver_doublons_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        let motif = |decalage: u32| {
+            image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(256, 192, |x, y| {
+                let valeur = ((x * 3 + y * 2 + decalage) % 256) as u8;
+                if (x / 32 + y / 48 + decalage) & 1 == 0 { image::Rgb([valeur, 40, 200]) } else { image::Rgb([20, valeur, 60]) }
+            }))
+        };
+        let originale = motif(0);
+        std::fs::write(dossier.join("originale.png"), encoder_image(&originale, ImageFormat::Png, 100).unwrap()).unwrap();
+        let reduite = originale.resize(128, 96, image::imageops::FilterType::Lanczos3);
+        std::fs::write(dossier.join("copie.jpg"), encoder_image(&reduite, ImageFormat::Jpeg, 70).unwrap()).unwrap();
+        std::fs::write(dossier.join("autre.png"), encoder_image(&motif(1).fliph(), ImageFormat::Png, 100).unwrap()).unwrap();
+        
+        let options = OptionsDoublons {
+            recursif: false,
+            algorithme: None,
+            seuil_distance: None,
+            concurrence_max: Some(2),
+            chemin_cache: Some(dossier.join("cache").join("empreintes.json").to_string_lossy().to_string()),
+        };
+        let entrees = vec![dossier.to_string_lossy().to_string()];
+        
+        let resultat = rechercher_doublons_interne(&entrees, &options).await.unwrap();
+        assert_eq!(resultat.images_analysees, 3);
+        assert_eq!(resultat.images_en_cache, 0);
+        assert_eq!(resultat.groupes.len(), 1);
+        let images = &resultat.groupes[0].images;
+        assert_eq!(images.len(), 2);
+        // La plus grande résolution est proposée en premier
+        assert!(images[0].chemin.ends_with("originale.png"));
+        assert_eq!((images[1].largeur, images[1].hauteur), (128, 96));
+        
+        // Le second passage reprend toutes les empreintes du cache
+        let resultat = rechercher_doublons_interne(&entrees, &options).await.unwrap();
+        assert_eq!(resultat.images_en_cache, 3);
+        assert_eq!(resultat.groupes.len(), 1);
+        
+        // Une image supprimée disparaît du cache
+        let chemin_cache = dossier.join("cache").join("empreintes.json");
+        std::fs::remove_file(dossier.join("autre.png")).unwrap();
+        rechercher_doublons_interne(&entrees, &options).await.unwrap();
+        let cache: HashMap<String, EmpreintesImage> = serde_json::from_slice(&std::fs::read(&chemin_cache).unwrap()).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.keys().all(|chemin| !chemin.ends_with("autre.png")));
+        
+        // Un cache corrompu est reconstruit
+        std::fs::write(&chemin_cache, b"{ tronque").unwrap();
+        let resultat = rechercher_doublons_interne(&entrees, &options).await.unwrap();
+        assert_eq!((resultat.images_analysees, resultat.images_en_cache), (2, 0));
+        assert_eq!(serde_json::from_slice::<HashMap<String, EmpreintesImage>>(&std::fs::read(&chemin_cache).unwrap()).unwrap().len(), 2);
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    #[test]
+    fn test_detecter_format_depuis_contenu() {
+        let dossier = std::env::temp_dir().join("macgyver_test_format");
+        std::fs::create_dir_all(&dossier).unwrap();
+        
+        // Un PNG enregistré avec une extension JPEG, puis sans extension
+        let faux_jpeg = dossier.join("photo.jpg");
+        image::RgbImage::new(4, 4).save_with_format(&faux_jpeg, ImageFormat::Png).unwrap();
+        let sans_extension = dossier.join("photo");
+        std::fs::copy(&faux_jpeg, &sans_extension).unwrap();
+        
+        assert_eq!(detecter_format_image(&faux_jpeg).unwrap(), "PNG");
+        assert_eq!(detecter_format_image(&sans_extension).unwrap(), "PNG");
+        assert_eq!(charger_image(&faux_jpeg).unwrap().width(), 4);
+        
+        std::fs::remove_dir_all(&dossier).unwrap();
+    }
 }
\ No newline at end of file
@@ -31,9 +31,26 @@ pub mod productivite;
 /// Module contenant les commandes de conversion d'images
 /// 
 /// Ce module fournit les fonctionnalités de traitement d'images :
-/// - Conversion entre formats (JPG, PNG, WebP, BMP, TIFF, GIF)
+/// - Conversion entre formats (JPG, PNG, WebP, BMP, TIFF, GIF, ICO, et AVIF en sortie)
+/// - Génération des icônes d'un site ou d'une application (favicon, Apple, manifeste)
+/// - GIF et WebP animés : extraction, création et conversion des animations
+/// - Analyse des couleurs : palette dominante, histogrammes, luminosité, transparence
+/// - Planches contact, collages et planches de sprites CSS
+/// - Comparaison pixel à pixel (image des différences, PSNR, SSIM)
+/// - Recherche de doublons et quasi-doublons par empreinte perceptuelle
 /// - Redimensionnement et optimisation
+/// - Optimisation web : PNG sans perte ou quantifié, JPEG transcodé sans perte (progressif au besoin)
+/// - Conversion par lot en parallèle avec modèle de nommage
+/// - Respect de l'orientation EXIF et redressement automatique
+/// - Recadrage, rotation, miroir, marges et coins arrondis
+/// - Réglages de couleur et filtres (luminosité, netteté, niveaux…)
+/// - Filigrane texte ou logo, seul ou en mosaïque
+/// - Suppression de fond globale ou par remplissage depuis les bords
+/// - Incrustation sur fond vert ou bleu (chroma key)
+/// - Export détouré en PNG, WebP, AVIF, TIFF ou GIF, ou composé sur un fond
 /// - Analyse des métadonnées d'images
+/// - Détection du format réel et correction des extensions
+/// - Lecture et nettoyage des métadonnées EXIF, XMP et IPTC
 pub mod images;
 
 /// Module contenant les commandes de manipulation de PDF
@@ -42,6 +59,11 @@ pub mod images;
 /// - Fusion de plusieurs PDFs en un seul
 /// - Division d'un PDF en pages individuelles
 /// - Extraction d'informations et métadonnées
+/// - Inspection approfondie (polices, images, espace occupé)
+/// - Pré-vérification de conformité PDF/A
+/// - Lecture et édition de l'arbre des signets
+/// - Insertion et détection de pages blanches
+/// - Traitement par lot d'un dossier (compression, filigrane, métadonnées, chiffrement AES-256…)
 /// - Rotation et manipulation de pages
 pub mod pdf;
 
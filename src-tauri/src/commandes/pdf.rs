@@ -15,9 +15,12 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use log::{info, warn, error};
 use std::path::Path;
-use lopdf::{Document, Object};
+use lopdf::{dictionary, Document, Object};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::time::Duration;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use crate::utilitaires::fichiers::{formater_taille_fichier, lister_fichiers_par_extension};
 
 // === Types de données pour les commandes PDF ===
 
@@ -126,6 +129,333 @@ pub struct OptionsDivisionPdf {
     pub prefixe_nom: String,
 }
 
+/**
+ * Rapport d'inspection approfondie d'un fichier PDF
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RapportInspectionPdf {
+    /// Chemin du fichier PDF
+    pub chemin_fichier: String,
+    
+    /// Taille du fichier en octets
+    pub taille_octets: u64,
+    
+    /// Version PDF (ex: "1.4", "1.7")
+    pub version: String,
+    
+    /// Nombre total de pages
+    pub nombre_pages: u32,
+    
+    /// Polices utilisées par le document
+    pub polices: Vec<InfoPolicePdf>,
+    
+    /// Images intégrées au document
+    pub images: Vec<InfoImagePdf>,
+    
+    /// Dimensions et rotation de chaque page
+    pub pages: Vec<InfoPagePdf>,
+    
+    /// Le fichier est linéarisé (affichage web rapide)
+    pub linearise: bool,
+    
+    /// Le document est balisé (Tagged PDF)
+    pub balise: bool,
+    
+    /// Le document contient du JavaScript
+    pub contient_javascript: bool,
+    
+    /// Répartition de l'espace occupé par catégorie d'objets
+    pub repartition_espace: Vec<RepartitionEspacePdf>,
+    
+    /// Horodatage de l'analyse
+    pub horodatage: DateTime<Utc>,
+}
+
+/**
+ * Informations sur une police utilisée dans un PDF
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InfoPolicePdf {
+    /// Référence de l'objet (ex: "12 0 R")
+    pub objet: String,
+    
+    /// Nom de la police (BaseFont)
+    pub nom: String,
+    
+    /// Sous-type (Type1, TrueType, Type0, Type3...)
+    pub sous_type: String,
+    
+    /// Le programme de la police est incorporé au fichier
+    pub incorporee: bool,
+    
+    /// La police est un sous-ensemble (préfixe "ABCDEF+")
+    pub sous_ensemble: bool,
+}
+
+/**
+ * Informations sur une image intégrée dans un PDF
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InfoImagePdf {
+    /// Référence de l'objet (ex: "12 0 R")
+    pub objet: String,
+    
+    /// Largeur en pixels
+    pub largeur: u32,
+    
+    /// Hauteur en pixels
+    pub hauteur: u32,
+    
+    /// Filtres de compression (DCTDecode, FlateDecode...)
+    pub filtres: Vec<String>,
+    
+    /// Espace colorimétrique (DeviceRGB, ICCBased...)
+    pub espace_couleur: String,
+    
+    /// Nombre de bits par composante
+    pub bits_par_composante: Option<u32>,
+    
+    /// Taille des données de l'image dans le fichier
+    pub taille_octets: u64,
+}
+
+/**
+ * Dimensions et rotation d'une page
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InfoPagePdf {
+    /// Numéro de la page (à partir de 1)
+    pub numero: u32,
+    
+    /// Largeur en points (1/72 de pouce)
+    pub largeur_points: f32,
+    
+    /// Hauteur en points (1/72 de pouce)
+    pub hauteur_points: f32,
+    
+    /// Rotation en degrés (0, 90, 180 ou 270)
+    pub rotation: i64,
+}
+
+/**
+ * Espace occupé par une catégorie d'objets PDF
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepartitionEspacePdf {
+    /// Catégorie d'objets (Images, Polices, Contenu des pages...)
+    pub categorie: String,
+    
+    /// Nombre d'objets dans la catégorie
+    pub nombre_objets: u32,
+    
+    /// Taille cumulée en octets
+    pub taille_octets: u64,
+    
+    /// Part de la taille totale du fichier
+    pub pourcentage: f32,
+}
+
+/**
+ * Rapport de pré-vérification de conformité PDF/A
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RapportConformitePdfa {
+    /// Chemin du fichier PDF
+    pub chemin_fichier: String,
+    
+    /// Niveau vérifié ("1b" ou "2b")
+    pub niveau: String,
+    
+    /// Aucune violation n'a été détectée
+    pub conforme: bool,
+    
+    /// Liste des règles non respectées
+    pub violations: Vec<ViolationPdfa>,
+    
+    /// Horodatage de la vérification
+    pub horodatage: DateTime<Utc>,
+}
+
+/**
+ * Violation d'une règle PDF/A
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ViolationPdfa {
+    /// Identifiant de la règle (ex: "polices_incorporees")
+    pub regle: String,
+    
+    /// Description lisible de la violation
+    pub description: String,
+    
+    /// Objets concernés (ex: ["12 0 R"])
+    pub objets: Vec<String>,
+}
+
+/**
+ * Entrée de l'arbre des signets (outline) d'un PDF
+ */
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SignetPdf {
+    /// Titre affiché dans le panneau des signets
+    pub titre: String,
+    
+    /// Page cible (à partir de 1), absente pour un simple regroupement
+    pub page: Option<u32>,
+    
+    /// Signets enfants
+    pub enfants: Vec<SignetPdf>,
+}
+
+/**
+ * Paire (titre, page) pour la génération automatique de signets
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntreeSignet {
+    /// Titre du signet
+    pub titre: String,
+    
+    /// Page cible (à partir de 1)
+    pub page: u32,
+}
+
+/**
+ * Options pour l'insertion de pages blanches
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsInsertionPagesBlanches {
+    /// Mode d'insertion : "apres_impaires" (impression recto-verso) ou "positions"
+    pub mode: String,
+    
+    /// Pages après lesquelles insérer une page blanche (mode "positions", 0 = en tête)
+    pub positions: Option<Vec<u32>>,
+}
+
+/**
+ * Options pour la détection de pages blanches dans un PDF numérisé
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsDetectionPagesBlanches {
+    /// Taux d'encre (en %) sous lequel une page est considérée comme blanche
+    pub seuil_encre_pourcent: f32,
+    
+    /// Supprimer les pages blanches détectées
+    pub supprimer: bool,
+    
+    /// Chemin du PDF nettoyé (obligatoire si `supprimer` est activé)
+    pub chemin_sortie: Option<String>,
+}
+
+/**
+ * Résultat de l'analyse d'une page pour la détection de pages blanches
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysePageBlanche {
+    /// Numéro de la page (à partir de 1)
+    pub numero: u32,
+    
+    /// Taux d'encre mesuré en %, absent si la page n'a pas pu être analysée
+    pub taux_encre_pourcent: Option<f32>,
+    
+    /// La page est considérée comme blanche
+    pub blanche: bool,
+    
+    /// Raison pour laquelle la page n'a pas pu être analysée
+    pub remarque: Option<String>,
+}
+
+/**
+ * Rapport de détection des pages blanches
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RapportPagesBlanches {
+    /// Chemin du fichier PDF analysé
+    pub chemin_fichier: String,
+    
+    /// Analyse de chaque page
+    pub pages: Vec<AnalysePageBlanche>,
+    
+    /// Numéros des pages détectées comme blanches
+    pub pages_blanches: Vec<u32>,
+    
+    /// PDF nettoyé, si la suppression a été demandée
+    pub fichier_sortie: Option<String>,
+    
+    /// Temps de traitement en millisecondes
+    pub temps_traitement_ms: u64,
+    
+    /// Horodatage de l'analyse
+    pub horodatage: DateTime<Utc>,
+}
+
+/**
+ * Options pour le traitement par lot des PDFs d'un dossier
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OptionsLotPdf {
+    /// Opération : "info", "compression", "suppression_metadonnees", "filigrane" ou "chiffrement"
+    pub operation: String,
+    
+    /// Parcourir également les sous-dossiers
+    pub recursif: bool,
+    
+    /// Nombre maximal de fichiers traités simultanément (4 par défaut)
+    pub concurrence_max: Option<usize>,
+    
+    /// Dossier de sortie, obligatoire sauf pour "info" (l'arborescence est reproduite)
+    pub dossier_sortie: Option<String>,
+    
+    /// Texte du filigrane (opération "filigrane")
+    pub texte_filigrane: Option<String>,
+    
+    /// Opacité du filigrane entre 0 et 1 (0.3 par défaut)
+    pub opacite_filigrane: Option<f32>,
+    
+    /// Mot de passe demandé à l'ouverture (opération "chiffrement", vide par défaut)
+    pub mot_de_passe_utilisateur: Option<String>,
+    
+    /// Mot de passe propriétaire, obligatoire pour l'opération "chiffrement"
+    pub mot_de_passe_proprietaire: Option<String>,
+    
+    /// Autoriser un dossier de sortie identique au dossier source (les originaux sont remplacés)
+    pub ecraser: Option<bool>,
+}
+
+/**
+ * Fichier n'ayant pas pu être traité lors d'un traitement par lot
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EchecLotPdf {
+    /// Chemin du fichier en échec
+    pub fichier: String,
+    
+    /// Message d'erreur
+    pub erreur: String,
+}
+
+/**
+ * Synthèse d'un traitement par lot
+ */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultatLotPdf {
+    /// Opération appliquée
+    pub operation: String,
+    
+    /// Nombre de PDFs trouvés dans le dossier
+    pub fichiers_trouves: u32,
+    
+    /// Résultat de chaque fichier traité avec succès
+    pub resultats: Vec<ResultatOperationPdf>,
+    
+    /// Fichiers en échec
+    pub echecs: Vec<EchecLotPdf>,
+    
+    /// Temps total du traitement en millisecondes
+    pub temps_total_ms: u64,
+    
+    /// Horodatage de la fin du traitement
+    pub horodatage: DateTime<Utc>,
+}
+
 // === Commandes Tauri publiques ===
 
 /**
@@ -411,70 +741,2325 @@ pub async fn diviser_pdf(
     Ok(resultat)
 }
 
-// === Fonctions utilitaires privées ===
-
 /**
- * Extrait les métadonnées d'un document PDF
+ * Commande pour inspecter en profondeur un fichier PDF
+ * 
+ * Cette commande liste les polices (incorporées ou non), les images,
+ * les dimensions des pages, et indique où part l'espace du fichier.
+ * Elle signale aussi la linéarisation, le balisage et la présence de JavaScript.
+ * 
+ * # Arguments
+ * * `chemin_fichier` - Chemin vers le fichier PDF à inspecter
+ * 
+ * # Retour
+ * * `Result<RapportInspectionPdf, String>` - Rapport d'inspection ou message d'erreur
  */
-fn extraire_metadonnees_pdf(document: &Document) -> MetadonneesPdf {
-    let mut metadonnees = MetadonneesPdf {
-        titre: None,
-        auteur: None,
-        sujet: None,
-        createur: None,
-        producteur: None,
-        date_creation: None,
-        date_modification: None,
+#[tauri::command]
+pub async fn inspecter_pdf(chemin_fichier: String) -> Result<RapportInspectionPdf, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("🔬 Inspection du PDF : {}", chemin_fichier);
+    
+    if !Path::new(&chemin_fichier).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_fichier);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let taille_octets = match std::fs::metadata(&chemin_fichier) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            let erreur = format!("Impossible de lire les métadonnées du fichier : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
     };
     
-    // Essayer d'obtenir les métadonnées depuis le dictionnaire Info
-    if let Ok(info_dict) = document.trailer.get(b"Info") {
-        if let Object::Reference(info_ref) = info_dict {
-            if let Ok(Object::Dictionary(info)) = document.get_object(*info_ref) {
-                // Extraire chaque métadonnée
-                if let Ok(Object::String(titre, _)) = info.get(b"Title") {
-                    metadonnees.titre = Some(String::from_utf8_lossy(titre).to_string());
-                }
-                
-                if let Ok(Object::String(auteur, _)) = info.get(b"Author") {
-                    metadonnees.auteur = Some(String::from_utf8_lossy(auteur).to_string());
-                }
-                
-                if let Ok(Object::String(sujet, _)) = info.get(b"Subject") {
-                    metadonnees.sujet = Some(String::from_utf8_lossy(sujet).to_string());
-                }
-                
-                if let Ok(Object::String(createur, _)) = info.get(b"Creator") {
-                    metadonnees.createur = Some(String::from_utf8_lossy(createur).to_string());
-                }
-                
-                if let Ok(Object::String(producteur, _)) = info.get(b"Producer") {
-                    metadonnees.producteur = Some(String::from_utf8_lossy(producteur).to_string());
-                }
-                
-                if let Ok(Object::String(date_creation, _)) = info.get(b"CreationDate") {
-                    metadonnees.date_creation = Some(String::from_utf8_lossy(date_creation).to_string());
-                }
-                
-                if let Ok(Object::String(date_modif, _)) = info.get(b"ModDate") {
-                    metadonnees.date_modification = Some(String::from_utf8_lossy(date_modif).to_string());
-                }
-            }
+    let document = match Document::load(&chemin_fichier) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
         }
-    }
+    };
     
-    metadonnees
+    let rapport = construire_rapport_inspection(&document, &chemin_fichier, taille_octets);
+    
+    info!("✅ PDF inspecté : {} polices, {} images, {} pages en {}ms",
+          rapport.polices.len(), rapport.images.len(), rapport.nombre_pages,
+          debut_traitement.elapsed().as_millis());
+    
+    Ok(rapport)
 }
 
-// === Tests unitaires ===
-#[cfg(test)]
-mod tests {
-    use super::*;
+/**
+ * Commande pour vérifier la conformité d'un PDF aux règles PDF/A-1b ou PDF/A-2b
+ * 
+ * Cette pré-vérification hors ligne couvre les règles inspectables sans
+ * rendu : polices incorporées, absence de chiffrement et de JavaScript,
+ * présence d'un OutputIntent, cohérence XMP / Info et, pour le niveau 1b,
+ * absence de transparence. Elle ne remplace pas un validateur complet.
+ * 
+ * # Arguments
+ * * `chemin_fichier` - Chemin vers le fichier PDF à vérifier
+ * * `niveau` - Niveau de conformité visé : "1b" ou "2b"
+ * 
+ * # Retour
+ * * `Result<RapportConformitePdfa, String>` - Liste des violations ou message d'erreur
+ */
+#[tauri::command]
+pub async fn verifier_pdfa(chemin_fichier: String, niveau: String) -> Result<RapportConformitePdfa, String> {
+    info!("🏛️  Vérification PDF/A-{} : {}", niveau, chemin_fichier);
     
-    /**
-     * Test de base pour vérifier que le module peut être importé
-     */
-    #[test]
+    let niveau = niveau.trim().to_lowercase();
+    if niveau != "1b" && niveau != "2b" {
+        let erreur = format!("Niveau PDF/A non supporté : {} (attendu : 1b ou 2b)", niveau);
+        warn!("⚠️  {}", erreur);
+        return Err(erreur);
+    }
+    
+    if !Path::new(&chemin_fichier).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_fichier);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let document = match Document::load(&chemin_fichier) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+    };
+    
+    let mut violations = Vec::new();
+    
+    // Le chiffrement est interdit ; lopdf déchiffre au chargement quand le mot de passe vide suffit
+    if let Ok(reference) = document.trailer.get(b"Encrypt") {
+        violations.push(ViolationPdfa {
+            regle: "chiffrement".to_string(),
+            description: "Le document est chiffré, ce qui est interdit en PDF/A".to_string(),
+            objets: reference.as_reference().map(reference_objet).into_iter().collect(),
+        });
+        
+        if document.encryption_state.is_none() {
+            warn!("⚠️  Document chiffré non lisible, vérification partielle");
+        }
+    }
+    
+    violations.extend(verifier_regles_pdfa(&document, &niveau));
+    
+    let conforme = violations.is_empty();
+    
+    if conforme {
+        info!("✅ Aucune violation PDF/A-{} détectée", niveau);
+    } else {
+        info!("⚠️  {} violation(s) PDF/A-{} détectée(s)", violations.len(), niveau);
+    }
+    
+    Ok(RapportConformitePdfa {
+        chemin_fichier,
+        niveau,
+        conforme,
+        violations,
+        horodatage: Utc::now(),
+    })
+}
+
+/**
+ * Commande pour lire l'arbre des signets d'un PDF
+ * 
+ * # Arguments
+ * * `chemin_fichier` - Chemin vers le fichier PDF
+ * 
+ * # Retour
+ * * `Result<Vec<SignetPdf>, String>` - Signets de premier niveau avec leurs enfants
+ */
+#[tauri::command]
+pub async fn lire_signets_pdf(chemin_fichier: String) -> Result<Vec<SignetPdf>, String> {
+    info!("🔖 Lecture des signets : {}", chemin_fichier);
+    
+    if !Path::new(&chemin_fichier).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_fichier);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let document = match Document::load(&chemin_fichier) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+    };
+    
+    let signets = extraire_signets_pdf(&document);
+    
+    info!("✅ {} signet(s) de premier niveau lus", signets.len());
+    Ok(signets)
+}
+
+/**
+ * Commande pour remplacer l'arbre des signets d'un PDF
+ * 
+ * L'arbre fourni remplace entièrement le dictionnaire /Outlines existant.
+ * Une liste vide supprime les signets du document.
+ * 
+ * # Arguments
+ * * `chemin_entree` - Chemin vers le PDF source
+ * * `chemin_sortie` - Chemin du PDF à écrire
+ * * `signets` - Nouvel arbre des signets
+ * 
+ * # Retour
+ * * `Result<ResultatOperationPdf, String>` - Résultat de l'opération
+ */
+#[tauri::command]
+pub async fn ecrire_signets_pdf(
+    chemin_entree: String,
+    chemin_sortie: String,
+    signets: Vec<SignetPdf>,
+) -> Result<ResultatOperationPdf, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("🔖 Écriture des signets : {} -> {}", chemin_entree, chemin_sortie);
+    
+    if !Path::new(&chemin_entree).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_entree);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let mut document = match Document::load(&chemin_entree) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+    };
+    
+    let nombre_signets = ecrire_arbre_signets(&mut document, &signets).map_err(|erreur| {
+        error!("❌ {}", erreur);
+        erreur
+    })?;
+    
+    if let Err(e) = document.save(&chemin_sortie) {
+        let erreur = format!("Impossible de sauvegarder le PDF : {}", e);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let temps_traitement_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!("✅ {} signet(s) écrits en {}ms", nombre_signets, temps_traitement_ms);
+    
+    Ok(ResultatOperationPdf {
+        succes: true,
+        message: format!("{} signet(s) écrits", nombre_signets),
+        fichiers_sortie: vec![chemin_sortie],
+        pages_traitees: document.get_pages().len() as u32,
+        temps_traitement_ms,
+        horodatage: Utc::now(),
+    })
+}
+
+/**
+ * Commande pour générer des signets à partir d'une liste (titre, page)
+ * 
+ * Les signets sont créés à plat, dans l'ordre fourni, et remplacent
+ * les signets existants.
+ * 
+ * # Arguments
+ * * `chemin_entree` - Chemin vers le PDF source
+ * * `chemin_sortie` - Chemin du PDF à écrire
+ * * `entrees` - Liste des paires (titre, page)
+ * 
+ * # Retour
+ * * `Result<ResultatOperationPdf, String>` - Résultat de l'opération
+ */
+#[tauri::command]
+pub async fn generer_signets_pdf(
+    chemin_entree: String,
+    chemin_sortie: String,
+    entrees: Vec<EntreeSignet>,
+) -> Result<ResultatOperationPdf, String> {
+    let signets = entrees
+        .into_iter()
+        .map(|entree| SignetPdf {
+            titre: entree.titre,
+            page: Some(entree.page),
+            enfants: Vec::new(),
+        })
+        .collect();
+    
+    ecrire_signets_pdf(chemin_entree, chemin_sortie, signets).await
+}
+
+/**
+ * Commande pour insérer des pages blanches dans un PDF
+ * 
+ * Les pages blanches reprennent le format et la rotation de la page
+ * qui les précède, ce qui convient à l'impression recto-verso.
+ * 
+ * # Arguments
+ * * `chemin_entree` - Chemin vers le PDF source
+ * * `chemin_sortie` - Chemin du PDF à écrire
+ * * `options` - Mode et positions d'insertion
+ * 
+ * # Retour
+ * * `Result<ResultatOperationPdf, String>` - Résultat de l'opération
+ */
+#[tauri::command]
+pub async fn inserer_pages_blanches(
+    chemin_entree: String,
+    chemin_sortie: String,
+    options: OptionsInsertionPagesBlanches,
+) -> Result<ResultatOperationPdf, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("📃 Insertion de pages blanches : {} -> {}", chemin_entree, chemin_sortie);
+    
+    if !Path::new(&chemin_entree).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_entree);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let mut document = match Document::load(&chemin_entree) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+    };
+    
+    let pages = document.get_pages();
+    let ordre = calculer_ordre_insertion(pages.len() as u32, &options).map_err(|erreur| {
+        warn!("⚠️  {}", erreur);
+        erreur
+    })?;
+    
+    // Chaque page blanche copie le format de la dernière page d'origine rencontrée
+    let mut nouvel_ordre = Vec::with_capacity(ordre.len());
+    let mut reference = pages.values().next().copied();
+    
+    for position in &ordre {
+        match position {
+            Some(numero) => {
+                reference = pages.get(numero).copied();
+                nouvel_ordre.extend(reference);
+            }
+            None => nouvel_ordre.push(creer_page_blanche(&mut document, reference)),
+        }
+    }
+    
+    let pages_inserees = ordre.iter().filter(|position| position.is_none()).count();
+    
+    reconstruire_arbre_pages(&mut document, &nouvel_ordre)?;
+    
+    if let Err(e) = document.save(&chemin_sortie) {
+        let erreur = format!("Impossible de sauvegarder le PDF : {}", e);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let temps_traitement_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!("✅ {} page(s) blanche(s) insérée(s) en {}ms", pages_inserees, temps_traitement_ms);
+    
+    Ok(ResultatOperationPdf {
+        succes: true,
+        message: format!("{} page(s) blanche(s) insérée(s), {} pages au total", pages_inserees, nouvel_ordre.len()),
+        fichiers_sortie: vec![chemin_sortie],
+        pages_traitees: nouvel_ordre.len() as u32,
+        temps_traitement_ms,
+        horodatage: Utc::now(),
+    })
+}
+
+/**
+ * Commande pour détecter (et éventuellement supprimer) les pages blanches d'un PDF numérisé
+ * 
+ * Chaque page doit contenir une image unique couvrant la page, comme
+ * le produisent les scanners. L'image est décodée et son taux d'encre
+ * (proportion de pixels sombres) est comparé au seuil.
+ * 
+ * # Arguments
+ * * `chemin_fichier` - Chemin vers le PDF numérisé
+ * * `options` - Seuil de détection et suppression éventuelle
+ * 
+ * # Retour
+ * * `Result<RapportPagesBlanches, String>` - Analyse page par page
+ */
+#[tauri::command]
+pub async fn detecter_pages_blanches(
+    chemin_fichier: String,
+    options: OptionsDetectionPagesBlanches,
+) -> Result<RapportPagesBlanches, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("🔍 Détection des pages blanches : {} (seuil {}%)", chemin_fichier, options.seuil_encre_pourcent);
+    
+    if options.supprimer && options.chemin_sortie.is_none() {
+        let erreur = "Un chemin de sortie est requis pour supprimer les pages blanches".to_string();
+        warn!("⚠️  {}", erreur);
+        return Err(erreur);
+    }
+    
+    if !Path::new(&chemin_fichier).exists() {
+        let erreur = format!("Le fichier PDF n'existe pas : {}", chemin_fichier);
+        error!("❌ {}", erreur);
+        return Err(erreur);
+    }
+    
+    let mut document = match Document::load(&chemin_fichier) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let erreur = format!("Impossible de charger le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+    };
+    
+    let pages = document.get_pages();
+    let mut analyses = Vec::with_capacity(pages.len());
+    
+    for (numero, page_id) in &pages {
+        let analyse = match mesurer_encre_page(&document, *page_id) {
+            Ok(taux) => AnalysePageBlanche {
+                numero: *numero,
+                taux_encre_pourcent: Some(taux),
+                blanche: taux <= options.seuil_encre_pourcent,
+                remarque: None,
+            },
+            Err(remarque) => AnalysePageBlanche {
+                numero: *numero,
+                taux_encre_pourcent: None,
+                blanche: false,
+                remarque: Some(remarque),
+            },
+        };
+        analyses.push(analyse);
+    }
+    
+    let pages_blanches: Vec<u32> = analyses.iter().filter(|analyse| analyse.blanche).map(|analyse| analyse.numero).collect();
+    
+    let mut fichier_sortie = None;
+    
+    if options.supprimer {
+        let chemin_sortie = options.chemin_sortie.clone().unwrap_or_default();
+        
+        let pages_conservees: Vec<lopdf::ObjectId> = pages
+            .iter()
+            .filter(|(numero, _)| !pages_blanches.contains(numero))
+            .map(|(_, id)| *id)
+            .collect();
+        
+        if pages_conservees.is_empty() {
+            let erreur = "Toutes les pages sont blanches : le document serait vide".to_string();
+            warn!("⚠️  {}", erreur);
+            return Err(erreur);
+        }
+        
+        reconstruire_arbre_pages(&mut document, &pages_conservees)?;
+        
+        if let Err(e) = document.save(&chemin_sortie) {
+            let erreur = format!("Impossible de sauvegarder le PDF : {}", e);
+            error!("❌ {}", erreur);
+            return Err(erreur);
+        }
+        
+        fichier_sortie = Some(chemin_sortie);
+    }
+    
+    let temps_traitement_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!("✅ {} page(s) blanche(s) sur {} en {}ms", pages_blanches.len(), pages.len(), temps_traitement_ms);
+    
+    Ok(RapportPagesBlanches {
+        chemin_fichier,
+        pages: analyses,
+        pages_blanches,
+        fichier_sortie,
+        temps_traitement_ms,
+        horodatage: Utc::now(),
+    })
+}
+
+/**
+ * Commande pour appliquer une opération à tous les PDFs d'un dossier
+ * 
+ * Les fichiers sont traités en parallèle dans la limite de `concurrence_max`.
+ * Un fichier en échec n'interrompt pas le lot : il est reporté dans `echecs`.
+ * 
+ * # Arguments
+ * * `dossier` - Dossier contenant les PDFs
+ * * `options` - Opération à appliquer et paramètres associés
+ * 
+ * # Retour
+ * * `Result<ResultatLotPdf, String>` - Synthèse du lot
+ */
+#[tauri::command]
+pub async fn traiter_lot_pdf(dossier: String, options: OptionsLotPdf) -> Result<ResultatLotPdf, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    info!("📚 Traitement par lot '{}' du dossier : {}", options.operation, dossier);
+    
+    verifier_options_lot(&dossier, &options).map_err(|erreur| {
+        warn!("⚠️  {}", erreur);
+        erreur
+    })?;
+    
+    let fichiers = lister_fichiers_par_extension(&dossier, &["pdf"], options.recursif)
+        .await
+        .map_err(|e| {
+            let erreur = e.to_string();
+            error!("❌ {}", erreur);
+            erreur
+        })?;
+    
+    let semaphore = Arc::new(Semaphore::new(options.concurrence_max.unwrap_or(4).max(1)));
+    let options = Arc::new(options);
+    let mut taches = Vec::with_capacity(fichiers.len());
+    
+    for fichier in &fichiers {
+        let semaphore = Arc::clone(&semaphore);
+        let options = Arc::clone(&options);
+        let entree = fichier.clone();
+        let sortie = options
+            .dossier_sortie
+            .as_ref()
+            .map(|dossier_sortie| Path::new(dossier_sortie).join(entree.strip_prefix(&dossier).unwrap_or(&entree)));
+        
+        taches.push(tokio::spawn(async move {
+            let _permis = semaphore.acquire_owned().await.map_err(|e| e.to_string())?;
+            tokio::task::spawn_blocking(move || traiter_fichier_lot(&entree, sortie.as_deref(), &options))
+                .await
+                .map_err(|e| format!("Tâche interrompue : {}", e))?
+        }));
+    }
+    
+    let mut resultats = Vec::new();
+    let mut echecs = Vec::new();
+    
+    for (fichier, tache) in fichiers.iter().zip(taches) {
+        let resultat = tache.await.unwrap_or_else(|e| Err(format!("Tâche interrompue : {}", e)));
+        
+        match resultat {
+            Ok(resultat) => resultats.push(resultat),
+            Err(erreur) => {
+                warn!("⚠️  Échec pour {} : {}", fichier.display(), erreur);
+                echecs.push(EchecLotPdf {
+                    fichier: fichier.to_string_lossy().to_string(),
+                    erreur,
+                });
+            }
+        }
+    }
+    
+    let temps_total_ms = debut_traitement.elapsed().as_millis() as u64;
+    
+    info!("✅ Lot terminé : {} succès, {} échec(s) en {}ms", resultats.len(), echecs.len(), temps_total_ms);
+    
+    Ok(ResultatLotPdf {
+        operation: options.operation.clone(),
+        fichiers_trouves: fichiers.len() as u32,
+        resultats,
+        echecs,
+        temps_total_ms,
+        horodatage: Utc::now(),
+    })
+}
+
+// === Fonctions utilitaires privées ===
+
+/**
+ * Extrait les métadonnées d'un document PDF
+ */
+fn extraire_metadonnees_pdf(document: &Document) -> MetadonneesPdf {
+    let mut metadonnees = MetadonneesPdf {
+        titre: None,
+        auteur: None,
+        sujet: None,
+        createur: None,
+        producteur: None,
+        date_creation: None,
+        date_modification: None,
+    };
+    
+    // Essayer d'obtenir les métadonnées depuis le dictionnaire Info
+    if let Ok(info_dict) = document.trailer.get(b"Info") {
+        if let Object::Reference(info_ref) = info_dict {
+            if let Ok(Object::Dictionary(info)) = document.get_object(*info_ref) {
+                // Extraire chaque métadonnée
+                if let Ok(Object::String(titre, _)) = info.get(b"Title") {
+                    metadonnees.titre = Some(String::from_utf8_lossy(titre).to_string());
+                }
+                
+                if let Ok(Object::String(auteur, _)) = info.get(b"Author") {
+                    metadonnees.auteur = Some(String::from_utf8_lossy(auteur).to_string());
+                }
+                
+                if let Ok(Object::String(sujet, _)) = info.get(b"Subject") {
+                    metadonnees.sujet = Some(String::from_utf8_lossy(sujet).to_string());
+                }
+                
+                if let Ok(Object::String(createur, _)) = info.get(b"Creator") {
+                    metadonnees.createur = Some(String::from_utf8_lossy(createur).to_string());
+                }
+                
+                if let Ok(Object::String(producteur, _)) = info.get(b"Producer") {
+                    metadonnees.producteur = Some(String::from_utf8_lossy(producteur).to_string());
+                }
+                
+                if let Ok(Object::String(date_creation, _)) = info.get(b"CreationDate") {
+                    metadonnees.date_creation = Some(String::from_utf8_lossy(date_creation).to_string());
+                }
+                
+                if let Ok(Object::String(date_modif, _)) = info.get(b"ModDate") {
+                    metadonnees.date_modification = Some(String::from_utf8_lossy(date_modif).to_string());
+                }
+            }
+        }
+    }
+    
+    metadonnees
+}
+
+/**
+ * Extrait l'arbre des signets (/Outlines) d'un document PDF
+ */
+fn extraire_signets_pdf(document: &Document) -> Vec<SignetPdf> {
+    let pages: std::collections::HashMap<lopdf::ObjectId, u32> = document
+        .get_pages()
+        .into_iter()
+        .map(|(numero, id)| (id, numero))
+        .collect();
+    
+    let premier = document
+        .catalog()
+        .ok()
+        .and_then(|catalogue| dictionnaire_deref(document, catalogue, b"Outlines"))
+        .and_then(|racine| racine.get(b"First").and_then(Object::as_reference).ok());
+    
+    let mut visites = std::collections::HashSet::new();
+    lire_niveau_signets(document, premier, &pages, &mut visites)
+}
+
+/**
+ * Lit une liste de signets frères à partir du premier, puis leurs enfants
+ */
+fn lire_niveau_signets(
+    document: &Document,
+    premier: Option<lopdf::ObjectId>,
+    pages: &std::collections::HashMap<lopdf::ObjectId, u32>,
+    visites: &mut std::collections::HashSet<lopdf::ObjectId>,
+) -> Vec<SignetPdf> {
+    let mut signets = Vec::new();
+    let mut courant = premier;
+    
+    // Le jeu des objets visités protège contre les listes chaînées cycliques
+    while let Some(id) = courant.filter(|id| visites.insert(*id)) {
+        let element = match document.get_dictionary(id) {
+            Ok(element) => element,
+            Err(_) => break,
+        };
+        
+        let titre = element
+            .get(b"Title")
+            .ok()
+            .and_then(|titre| document.dereference(titre).ok())
+            .and_then(|(_, titre)| titre.as_str().ok())
+            .map(decoder_chaine_pdf)
+            .unwrap_or_default();
+        
+        let destination = element.get(b"Dest").ok().or_else(|| {
+            dictionnaire_deref(document, element, b"A")
+                .filter(|action| nom_dans_dictionnaire(action, b"S") == Some("GoTo"))
+                .and_then(|action| action.get(b"D").ok())
+        });
+        
+        let page = destination.and_then(|destination| resoudre_destination(document, destination, pages));
+        
+        let premier_enfant = element.get(b"First").and_then(Object::as_reference).ok();
+        let enfants = lire_niveau_signets(document, premier_enfant, pages, visites);
+        
+        signets.push(SignetPdf { titre, page, enfants });
+        
+        courant = element.get(b"Next").and_then(Object::as_reference).ok();
+    }
+    
+    signets
+}
+
+/**
+ * Résout une destination (tableau explicite ou destination nommée) en numéro de page
+ */
+fn resoudre_destination(
+    document: &Document,
+    destination: &Object,
+    pages: &std::collections::HashMap<lopdf::ObjectId, u32>,
+) -> Option<u32> {
+    let (_, destination) = document.dereference(destination).ok()?;
+    
+    match destination {
+        Object::Array(tableau) => tableau
+            .first()
+            .and_then(|cible| cible.as_reference().ok())
+            .and_then(|page_id| pages.get(&page_id).copied()),
+        // Forme { /D [...] } utilisée dans les dictionnaires de destinations
+        Object::Dictionary(dict) => dict
+            .get(b"D")
+            .ok()
+            .and_then(|cible| resoudre_destination(document, cible, pages)),
+        Object::Name(nom) | Object::String(nom, _) => {
+            let catalogue = document.catalog().ok()?;
+            
+            // Destinations nommées PDF 1.1 (/Dests) puis arbre de noms PDF 1.2+
+            let cible = dictionnaire_deref(document, catalogue, b"Dests")
+                .and_then(|dests| dests.get(nom).ok())
+                .or_else(|| {
+                    dictionnaire_deref(document, catalogue, b"Names")
+                        .and_then(|noms| dictionnaire_deref(document, noms, b"Dests"))
+                        .and_then(|arbre| chercher_arbre_noms(document, arbre, nom, 0))
+                })?;
+            
+            match cible {
+                Object::Name(_) | Object::String(_, _) => None,
+                _ => resoudre_destination(document, cible, pages),
+            }
+        }
+        _ => None,
+    }
+}
+
+/**
+ * Recherche une clé dans un arbre de noms PDF (/Names et /Kids)
+ */
+fn chercher_arbre_noms<'a>(document: &'a Document, noeud: &'a lopdf::Dictionary, cle: &[u8], profondeur: u32) -> Option<&'a Object> {
+    if profondeur > 32 {
+        return None;
+    }
+    
+    if let Ok(noms) = noeud.get(b"Names").and_then(Object::as_array) {
+        for paire in noms.chunks_exact(2) {
+            if paire[0].as_str().ok() == Some(cle) {
+                return Some(&paire[1]);
+            }
+        }
+    }
+    
+    let enfants = noeud.get(b"Kids").and_then(Object::as_array).ok()?;
+    enfants
+        .iter()
+        .filter_map(|enfant| document.dereference(enfant).ok())
+        .filter_map(|(_, enfant)| enfant.as_dict().ok())
+        .find_map(|enfant| chercher_arbre_noms(document, enfant, cle, profondeur + 1))
+}
+
+/**
+ * Remplace l'arbre des signets d'un document
+ * 
+ * # Retour
+ * * `Result<usize, String>` - Nombre total de signets écrits
+ */
+fn ecrire_arbre_signets(document: &mut Document, signets: &[SignetPdf]) -> Result<usize, String> {
+    let pages = document.get_pages();
+    
+    verifier_pages_signets(signets, pages.len() as u32)?;
+    
+    // L'ancien arbre devient orphelin et sera supprimé par le nettoyage final
+    document.catalog_mut().map_err(|e| format!("Catalogue PDF introuvable : {}", e))?.remove(b"Outlines");
+    
+    let nombre_signets = if signets.is_empty() {
+        0
+    } else {
+        let racine_id = document.new_object_id();
+        let (premier, dernier, total) = creer_niveau_signets(document, racine_id, signets, &pages);
+        
+        document.objects.insert(racine_id, Object::Dictionary(dictionary! {
+            "Type" => "Outlines",
+            "First" => premier,
+            "Last" => dernier,
+            "Count" => total as i64,
+        }));
+        
+        let catalogue = document.catalog_mut().map_err(|e| format!("Catalogue PDF introuvable : {}", e))?;
+        catalogue.set("Outlines", racine_id);
+        catalogue.set("PageMode", "UseOutlines");
+        
+        total
+    };
+    
+    document.prune_objects();
+    Ok(nombre_signets)
+}
+
+/**
+ * Vérifie que toutes les pages cibles existent dans le document
+ */
+fn verifier_pages_signets(signets: &[SignetPdf], nombre_pages: u32) -> Result<(), String> {
+    for signet in signets {
+        if let Some(page) = signet.page {
+            if page == 0 || page > nombre_pages {
+                return Err(format!(
+                    "Le signet \"{}\" cible la page {} alors que le document en compte {}",
+                    signet.titre, page, nombre_pages
+                ));
+            }
+        }
+        verifier_pages_signets(&signet.enfants, nombre_pages)?;
+    }
+    Ok(())
+}
+
+/**
+ * Crée les objets d'un niveau de signets et de leurs descendants
+ * 
+ * # Retour
+ * * `(premier, dernier, total)` - Premier et dernier signet du niveau, nombre de descendants
+ */
+fn creer_niveau_signets(
+    document: &mut Document,
+    parent_id: lopdf::ObjectId,
+    signets: &[SignetPdf],
+    pages: &std::collections::BTreeMap<u32, lopdf::ObjectId>,
+) -> (lopdf::ObjectId, lopdf::ObjectId, usize) {
+    let identifiants: Vec<lopdf::ObjectId> = signets.iter().map(|_| document.new_object_id()).collect();
+    let mut total = 0;
+    
+    for (index, signet) in signets.iter().enumerate() {
+        let mut element = dictionary! {
+            "Title" => encoder_chaine_pdf(&signet.titre),
+            "Parent" => parent_id,
+        };
+        
+        if index > 0 {
+            element.set("Prev", identifiants[index - 1]);
+        }
+        if index + 1 < identifiants.len() {
+            element.set("Next", identifiants[index + 1]);
+        }
+        
+        if let Some(page_id) = signet.page.and_then(|page| pages.get(&page)) {
+            element.set("Dest", vec![(*page_id).into(), "Fit".into()]);
+        }
+        
+        if !signet.enfants.is_empty() {
+            let (premier, dernier, descendants) = creer_niveau_signets(document, identifiants[index], &signet.enfants, pages);
+            element.set("First", premier);
+            element.set("Last", dernier);
+            // Un compteur positif affiche les enfants dépliés à l'ouverture
+            element.set("Count", descendants as i64);
+            total += descendants;
+        }
+        
+        document.objects.insert(identifiants[index], Object::Dictionary(element));
+        total += 1;
+    }
+    
+    (identifiants[0], identifiants[identifiants.len() - 1], total)
+}
+
+/**
+ * Encode un texte en chaîne PDF (littérale si ASCII, UTF-16BE avec BOM sinon)
+ */
+fn encoder_chaine_pdf(texte: &str) -> Object {
+    if texte.is_ascii() {
+        return Object::string_literal(texte);
+    }
+    
+    let mut octets = vec![0xFE, 0xFF];
+    for unite in texte.encode_utf16() {
+        octets.extend_from_slice(&unite.to_be_bytes());
+    }
+    
+    Object::String(octets, lopdf::StringFormat::Hexadecimal)
+}
+
+/**
+ * Calcule l'ordre des pages après insertion des pages blanches
+ * 
+ * # Retour
+ * * `Vec<Option<u32>>` - `Some(n)` pour la page d'origine n, `None` pour une page blanche
+ */
+fn calculer_ordre_insertion(nombre_pages: u32, options: &OptionsInsertionPagesBlanches) -> Result<Vec<Option<u32>>, String> {
+    let mut positions: Vec<u32> = match options.mode.as_str() {
+        "apres_impaires" => (1..=nombre_pages).filter(|page| page % 2 == 1).collect(),
+        "positions" => options.positions.clone().unwrap_or_default(),
+        autre => return Err(format!("Mode d'insertion inconnu : {} (attendu : apres_impaires ou positions)", autre)),
+    };
+    
+    if let Some(position) = positions.iter().find(|position| **position > nombre_pages) {
+        return Err(format!("Position {} invalide : le document compte {} pages", position, nombre_pages));
+    }
+    
+    positions.sort_unstable();
+    
+    let mut ordre = Vec::with_capacity(nombre_pages as usize + positions.len());
+    let mut suivantes = positions.iter().peekable();
+    
+    for page in 0..=nombre_pages {
+        if page > 0 {
+            ordre.push(Some(page));
+        }
+        while suivantes.next_if(|position| **position == page).is_some() {
+            ordre.push(None);
+        }
+    }
+    
+    Ok(ordre)
+}
+
+/**
+ * Crée une page blanche au format de la page de référence
+ */
+fn creer_page_blanche(document: &mut Document, reference: Option<lopdf::ObjectId>) -> lopdf::ObjectId {
+    let (largeur, hauteur) = reference
+        .map(|page_id| obtenir_dimensions_page(document, page_id))
+        .unwrap_or((595.0, 842.0));
+    let rotation = reference
+        .and_then(|page_id| obtenir_attribut_page(document, page_id, b"Rotate"))
+        .and_then(|rotation| rotation.as_i64().ok())
+        .unwrap_or(0);
+    
+    document.add_object(dictionary! {
+        "Type" => "Page",
+        "MediaBox" => vec![0.into(), 0.into(), largeur.into(), hauteur.into()],
+        "Rotate" => rotation,
+        "Resources" => dictionary! {},
+    })
+}
+
+/**
+ * Reconstruit l'arbre des pages à plat dans l'ordre donné
+ * 
+ * Les attributs hérités (MediaBox, CropBox, Resources, Rotate) sont
+ * recopiés sur chaque page avant que les nœuds intermédiaires ne soient
+ * supprimés, pour que l'apparence des pages reste identique. Les signets et
+ * liens qui visaient une page retirée sont redirigés vers la page conservée
+ * suivante (ou la dernière conservée).
+ */
+fn reconstruire_arbre_pages(document: &mut Document, ordre: &[lopdf::ObjectId]) -> Result<(), String> {
+    let racine_id = document
+        .catalog()
+        .and_then(|catalogue| catalogue.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|e| format!("Arbre des pages introuvable : {}", e))?;
+    
+    let pages_origine: Vec<lopdf::ObjectId> = document.get_pages().into_values().collect();
+    let mut remplacements = std::collections::HashMap::new();
+    for (position, page_id) in pages_origine.iter().enumerate() {
+        if ordre.contains(page_id) {
+            continue;
+        }
+        let remplacement = pages_origine[position + 1..]
+            .iter()
+            .find(|suivante| ordre.contains(suivante))
+            .or_else(|| ordre.last());
+        if let Some(remplacement) = remplacement {
+            remplacements.insert(*page_id, *remplacement);
+        }
+    }
+    if !remplacements.is_empty() {
+        for objet in document.objects.values_mut() {
+            rediriger_destinations(objet, &remplacements);
+        }
+    }
+    
+    for page_id in ordre {
+        let herites: Vec<(&[u8], Object)> = [&b"MediaBox"[..], b"CropBox", b"Resources", b"Rotate"]
+            .into_iter()
+            .filter_map(|cle| obtenir_attribut_page(document, *page_id, cle).map(|valeur| (cle, valeur.clone())))
+            .collect();
+        
+        let page = document
+            .get_dictionary_mut(*page_id)
+            .map_err(|e| format!("Page {} introuvable : {}", reference_objet(*page_id), e))?;
+        
+        for (cle, valeur) in herites {
+            if !page.has(cle) {
+                page.set(cle, valeur);
+            }
+        }
+        page.set("Parent", racine_id);
+    }
+    
+    let racine = document
+        .get_dictionary_mut(racine_id)
+        .map_err(|e| format!("Arbre des pages introuvable : {}", e))?;
+    racine.set("Kids", ordre.iter().map(|id| Object::Reference(*id)).collect::<Vec<Object>>());
+    racine.set("Count", ordre.len() as i64);
+    racine.remove(b"Parent");
+    
+    // Les nœuds intermédiaires et les pages retirées deviennent orphelins
+    document.prune_objects();
+    Ok(())
+}
+
+/**
+ * Redirige les destinations explicites ([page /XYZ …], [page /Fit]…) visant une page remplacée
+ * 
+ * Parcourt récursivement l'objet : signets, annotations de lien, actions GoTo
+ * et destinations nommées partagent cette forme de tableau.
+ */
+fn rediriger_destinations(objet: &mut Object, remplacements: &std::collections::HashMap<lopdf::ObjectId, lopdf::ObjectId>) {
+    const TYPES_DESTINATION: [&str; 8] = ["XYZ", "Fit", "FitH", "FitV", "FitR", "FitB", "FitBH", "FitBV"];
+    
+    match objet {
+        Object::Array(elements) => {
+            let destination = match elements.as_slice() {
+                [Object::Reference(page_id), Object::Name(type_destination), ..]
+                    if TYPES_DESTINATION.iter().any(|nom| nom.as_bytes() == type_destination.as_slice()) =>
+                {
+                    remplacements.get(page_id).copied()
+                }
+                _ => None,
+            };
+            
+            match destination {
+                // Les coordonnées visaient l'ancienne page : on affiche la nouvelle en entier
+                Some(nouvelle_page) => *elements = vec![Object::Reference(nouvelle_page), Object::Name(b"Fit".to_vec())],
+                None => elements.iter_mut().for_each(|element| rediriger_destinations(element, remplacements)),
+            }
+        }
+        Object::Dictionary(dictionnaire) => {
+            for (_, valeur) in dictionnaire.iter_mut() {
+                rediriger_destinations(valeur, remplacements);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, valeur) in stream.dict.iter_mut() {
+                rediriger_destinations(valeur, remplacements);
+            }
+        }
+        _ => {}
+    }
+}
+
+/**
+ * Mesure le taux d'encre d'une page numérisée à partir de son image pleine page
+ * 
+ * # Retour
+ * * `Result<f32, String>` - Pourcentage de pixels sombres, ou raison de l'échec
+ */
+fn mesurer_encre_page(document: &Document, page_id: lopdf::ObjectId) -> Result<f32, String> {
+    let images: Vec<&lopdf::Stream> = obtenir_attribut_page(document, page_id, b"Resources")
+        .and_then(|ressources| ressources.as_dict().ok())
+        .and_then(|ressources| dictionnaire_deref(document, ressources, b"XObject"))
+        .map(|xobjets| {
+            xobjets
+                .iter()
+                .filter_map(|(_, xobjet)| document.dereference(xobjet).ok())
+                .filter_map(|(_, xobjet)| xobjet.as_stream().ok())
+                .filter(|stream| nom_dans_dictionnaire(&stream.dict, b"Subtype") == Some("Image"))
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    match images.len() {
+        1 => {
+            let image = decoder_image_pdf_en_gris(images[0])?;
+            Ok(calculer_taux_encre(&image))
+        }
+        0 => {
+            // Une page sans image ni contenu est blanche par construction
+            let contenu = document.get_page_content(page_id).unwrap_or_default();
+            if contenu.iter().all(|octet| octet.is_ascii_whitespace()) {
+                Ok(0.0)
+            } else {
+                Err("Page sans image numérisée".to_string())
+            }
+        }
+        nombre => Err(format!("La page contient {} images au lieu d'une seule image pleine page", nombre)),
+    }
+}
+
+/**
+ * Décode un flux image PDF en niveaux de gris
+ * 
+ * Formats pris en charge : JPEG (DCTDecode) et données brutes éventuellement
+ * compressées en FlateDecode, en DeviceGray, DeviceRGB ou DeviceCMYK sur
+ * 1, 8 ou 16 bits. Les filtres CCITT, JBIG2 et JPEG 2000 ne sont pas décodés.
+ */
+fn decoder_image_pdf_en_gris(stream: &lopdf::Stream) -> Result<image::GrayImage, String> {
+    let filtres = stream.filters().unwrap_or_default();
+    let entier = |cle: &[u8]| stream.dict.get(cle).and_then(Object::as_i64).ok();
+    
+    let largeur = entier(b"Width").unwrap_or(0).max(0) as u32;
+    let hauteur = entier(b"Height").unwrap_or(0).max(0) as u32;
+    
+    if filtres.iter().any(|filtre| *filtre == b"DCTDecode") {
+        return image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg)
+            .map(|image| image.to_luma8())
+            .map_err(|e| format!("Image JPEG illisible : {}", e));
+    }
+    
+    if let Some(filtre) = filtres.iter().find(|filtre| **filtre != b"FlateDecode") {
+        return Err(format!("Filtre d'image non pris en charge : {}", String::from_utf8_lossy(filtre)));
+    }
+    
+    let masque = stream.dict.get(b"ImageMask").and_then(Object::as_bool).unwrap_or(false);
+    let bits = if masque { 1 } else { entier(b"BitsPerComponent").unwrap_or(8) as usize };
+    let composantes = if masque {
+        1
+    } else {
+        match stream.dict.get(b"ColorSpace").and_then(Object::as_name) {
+            Ok(b"DeviceGray") | Ok(b"CalGray") => 1,
+            Ok(b"DeviceRGB") | Ok(b"CalRGB") => 3,
+            Ok(b"DeviceCMYK") => 4,
+            Ok(autre) => return Err(format!("Espace colorimétrique non pris en charge : {}", String::from_utf8_lossy(autre))),
+            Err(_) => return Err("Espace colorimétrique indirect non pris en charge".to_string()),
+        }
+    };
+    
+    if !matches!(bits, 1 | 8 | 16) {
+        return Err(format!("Profondeur de {} bits non prise en charge", bits));
+    }
+    
+    let octets_par_ligne = (largeur as usize * composantes * bits).div_ceil(8);
+    
+    let mut donnees = stream.content.clone();
+    if !filtres.is_empty() {
+        use std::io::Read;
+        let mut decompresse = Vec::new();
+        flate2::read::ZlibDecoder::new(donnees.as_slice())
+            .read_to_end(&mut decompresse)
+            .map_err(|e| format!("Données d'image corrompues : {}", e))?;
+        donnees = decompresse;
+        
+        let predicteur = stream
+            .dict
+            .get(b"DecodeParms")
+            .and_then(Object::as_dict)
+            .and_then(|parametres| parametres.get(b"Predictor"))
+            .and_then(Object::as_i64)
+            .unwrap_or(1);
+        
+        if predicteur >= 10 {
+            let octets_par_pixel = (composantes * bits / 8).max(1);
+            donnees = lopdf::filters::png::decode_frame(&donnees, octets_par_pixel, octets_par_ligne / octets_par_pixel)
+                .map_err(|e| format!("Prédicteur PNG invalide : {}", e))?;
+        }
+    }
+    
+    if donnees.len() < octets_par_ligne * hauteur as usize {
+        return Err("Données d'image tronquées".to_string());
+    }
+    
+    // Un tableau /Decode [1 0] inverse l'interprétation des échantillons
+    let inverse = stream
+        .dict
+        .get(b"Decode")
+        .and_then(Object::as_array)
+        .ok()
+        .and_then(|decode| decode.first())
+        .and_then(|premier| premier.as_float().ok())
+        .map(|premier| premier > 0.5)
+        .unwrap_or(false);
+    
+    let mut image = image::GrayImage::new(largeur, hauteur);
+    
+    for (y, ligne) in donnees.chunks_exact(octets_par_ligne).take(hauteur as usize).enumerate() {
+        for x in 0..largeur as usize {
+            let echantillon = |index: usize| -> u8 {
+                match bits {
+                    1 => {
+                        let bit = (ligne[index / 8] >> (7 - index % 8)) & 1;
+                        if bit == 1 { 255 } else { 0 }
+                    }
+                    8 => ligne[index],
+                    _ => ligne[index * 2],
+                }
+            };
+            
+            let base = x * composantes;
+            let mut gris = match composantes {
+                1 => echantillon(base),
+                3 => {
+                    let (r, g, b) = (echantillon(base) as f32, echantillon(base + 1) as f32, echantillon(base + 2) as f32);
+                    (0.299 * r + 0.587 * g + 0.114 * b) as u8
+                }
+                _ => {
+                    let encre = 0.3 * echantillon(base) as f32
+                        + 0.59 * echantillon(base + 1) as f32
+                        + 0.11 * echantillon(base + 2) as f32
+                        + echantillon(base + 3) as f32;
+                    255 - encre.min(255.0) as u8
+                }
+            };
+            
+            // Pour un masque, un bit à 0 marque déjà la zone peinte : il est lu comme noir
+            if inverse {
+                gris = 255 - gris;
+            }
+            
+            image.put_pixel(x as u32, y as u32, image::Luma([gris]));
+        }
+    }
+    
+    Ok(image)
+}
+
+/**
+ * Calcule le pourcentage de pixels sombres d'une image en niveaux de gris
+ * 
+ * Une marge de 2% est ignorée sur chaque bord pour ne pas compter les
+ * ombres laissées par le scanner autour de la feuille.
+ */
+fn calculer_taux_encre(image: &image::GrayImage) -> f32 {
+    const SEUIL_SOMBRE: u8 = 128;
+    
+    let (largeur, hauteur) = image.dimensions();
+    let marge_x = largeur / 50;
+    let marge_y = hauteur / 50;
+    
+    let mut total = 0u64;
+    let mut sombres = 0u64;
+    
+    for y in marge_y..hauteur.saturating_sub(marge_y) {
+        for x in marge_x..largeur.saturating_sub(marge_x) {
+            total += 1;
+            if image.get_pixel(x, y).0[0] < SEUIL_SOMBRE {
+                sombres += 1;
+            }
+        }
+    }
+    
+    if total == 0 {
+        0.0
+    } else {
+        sombres as f32 / total as f32 * 100.0
+    }
+}
+
+/**
+ * Vérifie que les options d'un lot sont cohérentes avant de lancer le traitement
+ */
+fn verifier_options_lot(dossier: &str, options: &OptionsLotPdf) -> Result<(), String> {
+    match options.operation.as_str() {
+        "info" => return Ok(()),
+        "compression" | "suppression_metadonnees" => {}
+        "filigrane" => match options.texte_filigrane.as_deref().filter(|texte| !texte.trim().is_empty()) {
+            Some(texte) => verifier_texte_winansi(texte)?,
+            None => return Err("Le texte du filigrane est obligatoire".to_string()),
+        },
+        "chiffrement" => {
+            let proprietaire = options.mot_de_passe_proprietaire.as_deref().unwrap_or_default();
+            let utilisateur = options.mot_de_passe_utilisateur.as_deref().unwrap_or_default();
+            
+            if proprietaire.is_empty() {
+                return Err("Le mot de passe propriétaire est obligatoire".to_string());
+            }
+            if proprietaire == utilisateur {
+                return Err("Le mot de passe propriétaire doit différer du mot de passe utilisateur".to_string());
+            }
+            // Limite du gestionnaire de sécurité AES-256 (révision 6)
+            if proprietaire.len() > 127 || utilisateur.len() > 127 {
+                return Err("Les mots de passe sont limités à 127 octets".to_string());
+            }
+        }
+        autre => return Err(format!("Opération de lot inconnue : {}", autre)),
+    }
+    
+    let Some(dossier_sortie) = &options.dossier_sortie else {
+        return Err(format!("Un dossier de sortie est requis pour l'opération '{}'", options.operation));
+    };
+    
+    // Un dossier de sortie encore inexistant ne peut pas être le dossier source
+    let meme_dossier = match (Path::new(dossier).canonicalize(), Path::new(dossier_sortie).canonicalize()) {
+        (Ok(source), Ok(sortie)) => source == sortie,
+        _ => Path::new(dossier) == Path::new(dossier_sortie),
+    };
+    if meme_dossier && !options.ecraser.unwrap_or(false) {
+        return Err("Le dossier de sortie est le dossier source : les originaux seraient remplacés (activez « ecraser » pour le confirmer)".to_string());
+    }
+    
+    Ok(())
+}
+
+/**
+ * Code WinAnsiEncoding d'un caractère, ou None s'il n'y figure pas
+ */
+fn octet_winansi(caractere: char) -> Option<u8> {
+    // Caractères placés par WinAnsi entre 0x80 et 0x9F, hors du Latin-1
+    const SUPPLEMENT_WINANSI: [(char, u8); 27] = [
+        ('€', 0x80), ('‚', 0x82), ('ƒ', 0x83), ('„', 0x84), ('…', 0x85), ('†', 0x86), ('‡', 0x87),
+        ('ˆ', 0x88), ('‰', 0x89), ('Š', 0x8A), ('‹', 0x8B), ('Œ', 0x8C), ('Ž', 0x8E), ('‘', 0x91),
+        ('’', 0x92), ('“', 0x93), ('”', 0x94), ('•', 0x95), ('–', 0x96), ('—', 0x97), ('˜', 0x98),
+        ('™', 0x99), ('š', 0x9A), ('›', 0x9B), ('œ', 0x9C), ('ž', 0x9E), ('Ÿ', 0x9F),
+    ];
+    
+    match caractere as u32 {
+        code @ (0x20..=0x7E | 0xA0..=0xFF) => Some(code as u8),
+        _ => SUPPLEMENT_WINANSI
+            .iter()
+            .find(|(candidat, _)| *candidat == caractere)
+            .map(|(_, octet)| *octet),
+    }
+}
+
+/**
+ * Vérifie qu'un texte s'écrit entièrement en WinAnsiEncoding, le codage de la police standard du filigrane
+ */
+fn verifier_texte_winansi(texte: &str) -> Result<(), String> {
+    let refuses: String = texte
+        .chars()
+        .filter(|&caractere| octet_winansi(caractere).is_none())
+        .collect();
+    
+    if refuses.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Caractères non pris en charge par la police du filigrane (WinAnsi) : {}", refuses))
+    }
+}
+
+/**
+ * Applique l'opération d'un lot à un fichier (exécuté hors du runtime asynchrone)
+ */
+fn traiter_fichier_lot(entree: &Path, sortie: Option<&Path>, options: &OptionsLotPdf) -> Result<ResultatOperationPdf, String> {
+    let debut_traitement = std::time::Instant::now();
+    
+    let mut document = Document::load(entree).map_err(|e| format!("Impossible de charger le PDF : {}", e))?;
+    
+    // Une copie réécrite perdrait la protection du document : on refuse plutôt que de la retirer
+    if document.is_encrypted() {
+        return Err("PDF chiffré : traitement refusé pour ne pas retirer sa protection".to_string());
+    }
+    
+    let nombre_pages = document.get_pages().len() as u32;
+    
+    let message = match options.operation.as_str() {
+        "info" => {
+            let taille_octets = std::fs::metadata(entree).map(|metadata| metadata.len()).unwrap_or(0);
+            let titre = extraire_metadonnees_pdf(&document).titre.unwrap_or_else(|| "sans titre".to_string());
+            format!(
+                "{} pages, PDF {}, {} ({})",
+                nombre_pages,
+                document.version,
+                formater_taille_fichier(taille_octets),
+                titre
+            )
+        }
+        "compression" => {
+            compresser_document(&mut document);
+            "Document compressé".to_string()
+        }
+        "suppression_metadonnees" => {
+            let supprimees = supprimer_metadonnees_document(&mut document);
+            format!("{} entrée(s) de métadonnées supprimée(s)", supprimees)
+        }
+        "filigrane" => {
+            let texte = options.texte_filigrane.as_deref().unwrap_or_default();
+            ajouter_filigrane_document(&mut document, texte, options.opacite_filigrane.unwrap_or(0.3))?;
+            format!("Filigrane « {} » ajouté", texte)
+        }
+        "chiffrement" => {
+            chiffrer_document(
+                &mut document,
+                options.mot_de_passe_utilisateur.as_deref().unwrap_or_default(),
+                options.mot_de_passe_proprietaire.as_deref().unwrap_or_default(),
+            )?;
+            "Document chiffré (AES-256)".to_string()
+        }
+        autre => return Err(format!("Opération de lot inconnue : {}", autre)),
+    };
+    
+    let mut fichiers_sortie = Vec::new();
+    
+    if let Some(sortie) = sortie.filter(|_| options.operation != "info") {
+        if let Some(parent) = sortie.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {} : {}", parent.display(), e))?;
+        }
+        
+        document.save(sortie).map_err(|e| format!("Impossible de sauvegarder le PDF : {}", e))?;
+        fichiers_sortie.push(sortie.to_string_lossy().to_string());
+    }
+    
+    Ok(ResultatOperationPdf {
+        succes: true,
+        message: format!("{} : {}", entree.display(), message),
+        fichiers_sortie,
+        pages_traitees: nombre_pages,
+        temps_traitement_ms: debut_traitement.elapsed().as_millis() as u64,
+        horodatage: Utc::now(),
+    })
+}
+
+/**
+ * Chiffre un document en AES-256 (gestionnaire de sécurité standard, révision 6)
+ * 
+ * Sans le mot de passe propriétaire, seules l'impression et l'extraction
+ * pour l'accessibilité restent autorisées.
+ */
+fn chiffrer_document(document: &mut Document, mot_de_passe_utilisateur: &str, mot_de_passe_proprietaire: &str) -> Result<(), String> {
+    use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+    use lopdf::{EncryptionState, EncryptionVersion, Permissions};
+    use std::collections::BTreeMap;
+    
+    let mut cle = [0u8; 32];
+    getrandom::fill(&mut cle).map_err(|e| format!("Impossible de générer la clé de chiffrement : {}", e))?;
+    
+    let filtre: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    let version = EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filtre)]),
+        file_encryption_key: &cle,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: mot_de_passe_proprietaire,
+        user_password: mot_de_passe_utilisateur,
+        permissions: Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY | Permissions::COPYABLE_FOR_ACCESSIBILITY,
+    };
+    
+    let etat = EncryptionState::try_from(version).map_err(|e| format!("Paramètres de chiffrement invalides : {}", e))?;
+    document.encrypt(&etat).map_err(|e| format!("Impossible de chiffrer le PDF : {}", e))
+}
+
+/**
+ * Réduit la taille d'un document : objets orphelins, flux vides et compression Flate
+ */
+fn compresser_document(document: &mut Document) {
+    document.delete_zero_length_streams();
+    document.prune_objects();
+    document.compress();
+}
+
+/**
+ * Supprime le dictionnaire Info, les flux XMP et les données privées des applications
+ * 
+ * # Retour
+ * * `usize` - Nombre d'entrées supprimées
+ */
+fn supprimer_metadonnees_document(document: &mut Document) -> usize {
+    let mut supprimees = 0;
+    
+    if document.trailer.remove(b"Info").is_some() {
+        supprimees += 1;
+    }
+    
+    for objet in document.objects.values_mut() {
+        let dictionnaire = match objet {
+            Object::Dictionary(dictionnaire) => dictionnaire,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        
+        for cle in [b"Metadata".as_slice(), b"PieceInfo".as_slice()] {
+            if dictionnaire.remove(cle).is_some() {
+                supprimees += 1;
+            }
+        }
+    }
+    
+    document.prune_objects();
+    supprimees
+}
+
+/**
+ * Ajoute un filigrane texte en diagonale sur chaque page
+ * 
+ * Le contenu existant de la page est encadré par `q`/`Q` pour que
+ * le filigrane ne dépende pas de l'état graphique laissé par la page.
+ */
+fn ajouter_filigrane_document(document: &mut Document, texte: &str, opacite: f32) -> Result<(), String> {
+    use lopdf::content::{Content, Operation};
+    
+    verifier_texte_winansi(texte)?;
+    
+    let police_id = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica-Bold",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let etat_id = document.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => opacite.clamp(0.0, 1.0),
+        "CA" => opacite.clamp(0.0, 1.0),
+    });
+    let texte_encode: Vec<u8> = texte.chars().filter_map(octet_winansi).collect();
+    
+    let debut_id = document.add_object(lopdf::Stream::new(lopdf::Dictionary::new(), b"q\n".to_vec()));
+    
+    for page_id in document.get_pages().into_values() {
+        let (largeur, hauteur) = obtenir_dimensions_page(document, page_id);
+        
+        // Largeur moyenne d'un caractère Helvetica Bold : environ 0,6 em
+        let diagonale = largeur.hypot(hauteur);
+        let taille = (diagonale * 0.7 / (texte.chars().count().max(1) as f32 * 0.6)).min(120.0);
+        let demi_longueur = texte.chars().count() as f32 * 0.6 * taille / 2.0;
+        let angle = hauteur.atan2(largeur);
+        let (sinus, cosinus) = angle.sin_cos();
+        let x = largeur / 2.0 - demi_longueur * cosinus + taille / 3.0 * sinus;
+        let y = hauteur / 2.0 - demi_longueur * sinus - taille / 3.0 * cosinus;
+        
+        let contenu = Content {
+            operations: vec![
+                Operation::new("Q", vec![]),
+                Operation::new("q", vec![]),
+                Operation::new("gs", vec!["GSFiligrane".into()]),
+                Operation::new("g", vec![0.5.into()]),
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["FFiligrane".into(), taille.into()]),
+                Operation::new("Tm", vec![cosinus.into(), sinus.into(), (-sinus).into(), cosinus.into(), x.into(), y.into()]),
+                Operation::new("Tj", vec![Object::String(texte_encode.clone(), lopdf::StringFormat::Literal)]),
+                Operation::new("ET", vec![]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let flux = contenu.encode().map_err(|e| format!("Impossible d'encoder le filigrane : {}", e))?;
+        let filigrane_id = document.add_object(lopdf::Stream::new(lopdf::Dictionary::new(), flux));
+        
+        ajouter_ressource_page(document, page_id, b"Font", "FFiligrane", police_id)?;
+        ajouter_ressource_page(document, page_id, b"ExtGState", "GSFiligrane", etat_id)?;
+        
+        // /Contents peut être un flux, un tableau de flux ou une référence vers un tel tableau
+        let contenus_actuels = document
+            .get_dictionary(page_id)
+            .map_err(|e| format!("Page {} illisible : {}", reference_objet(page_id), e))?
+            .get(b"Contents")
+            .ok();
+        let mut contenus = match contenus_actuels.map(|contenu| (contenu, document.dereference(contenu))) {
+            Some((_, Ok((_, Object::Array(elements))))) => elements.clone(),
+            Some((contenu, _)) => vec![contenu.clone()],
+            None => vec![],
+        };
+        contenus.insert(0, debut_id.into());
+        contenus.push(filigrane_id.into());
+        document
+            .get_dictionary_mut(page_id)
+            .map_err(|e| format!("Page {} illisible : {}", reference_objet(page_id), e))?
+            .set("Contents", contenus);
+    }
+    
+    Ok(())
+}
+
+/**
+ * Déclare une ressource nommée dans le dictionnaire Resources propre à une page
+ * 
+ * Les ressources héritées sont d'abord recopiées sur la page, et les
+ * sous-dictionnaires référencés (Font, ExtGState…) sont dupliqués en
+ * direct pour ne pas modifier ceux partagés avec d'autres pages.
+ */
+fn ajouter_ressource_page(
+    document: &mut Document,
+    page_id: lopdf::ObjectId,
+    categorie: &[u8],
+    nom: &str,
+    ressource_id: lopdf::ObjectId,
+) -> Result<(), String> {
+    let mut ressources = match obtenir_attribut_page(document, page_id, b"Resources") {
+        Some(Object::Reference(id)) => document.get_dictionary(*id).cloned().unwrap_or_default(),
+        Some(Object::Dictionary(dictionnaire)) => dictionnaire.clone(),
+        _ => lopdf::Dictionary::new(),
+    };
+    
+    let mut sous_dictionnaire = match ressources.get(categorie) {
+        Ok(Object::Reference(id)) => document.get_dictionary(*id).cloned().unwrap_or_default(),
+        Ok(Object::Dictionary(dictionnaire)) => dictionnaire.clone(),
+        _ => lopdf::Dictionary::new(),
+    };
+    sous_dictionnaire.set(nom, ressource_id);
+    ressources.set(categorie.to_vec(), sous_dictionnaire);
+    
+    document
+        .get_dictionary_mut(page_id)
+        .map_err(|e| format!("Page {} illisible : {}", reference_objet(page_id), e))?
+        .set("Resources", ressources);
+    
+    Ok(())
+}
+
+/**
+ * Construit le rapport d'inspection d'un document PDF déjà chargé
+ */
+fn construire_rapport_inspection(document: &Document, chemin_fichier: &str, taille_octets: u64) -> RapportInspectionPdf {
+    let pages_document = document.get_pages();
+    
+    let pages = pages_document
+        .iter()
+        .map(|(numero, page_id)| {
+            let (largeur_points, hauteur_points) = obtenir_dimensions_page(document, *page_id);
+            let rotation = obtenir_attribut_page(document, *page_id, b"Rotate")
+                .and_then(|rotation| rotation.as_i64().ok())
+                .unwrap_or(0)
+                .rem_euclid(360);
+            
+            InfoPagePdf {
+                numero: *numero,
+                largeur_points,
+                hauteur_points,
+                rotation,
+            }
+        })
+        .collect();
+    
+    let mut polices = Vec::new();
+    let mut images = Vec::new();
+    let mut linearise = false;
+    
+    for (id, objet) in &document.objects {
+        let dictionnaire = match objet {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => continue,
+        };
+        
+        if dictionnaire.has(b"Linearized") {
+            linearise = true;
+        }
+        
+        if let Some(police) = analyser_police_pdf(document, *id, dictionnaire) {
+            polices.push(police);
+        }
+        
+        if let Object::Stream(stream) = objet {
+            if nom_dans_dictionnaire(&stream.dict, b"Subtype") == Some("Image") {
+                images.push(analyser_image_pdf(document, *id, stream));
+            }
+        }
+    }
+    
+    let balise = document.catalog().ok()
+        .and_then(|catalogue| dictionnaire_deref(document, catalogue, b"MarkInfo"))
+        .and_then(|mark_info| mark_info.get(b"Marked").ok())
+        .and_then(|marque| marque.as_bool().ok())
+        .unwrap_or(false);
+    
+    RapportInspectionPdf {
+        chemin_fichier: chemin_fichier.to_string(),
+        taille_octets,
+        version: document.version.clone(),
+        nombre_pages: pages_document.len() as u32,
+        polices,
+        images,
+        pages,
+        linearise,
+        balise,
+        contient_javascript: !rechercher_objets_javascript(document).is_empty(),
+        repartition_espace: calculer_repartition_espace(document, taille_octets),
+        horodatage: Utc::now(),
+    }
+}
+
+/**
+ * Analyse un dictionnaire de police et détermine si son programme est incorporé
+ * 
+ * Les polices descendantes (CIDFontType0/2) sont ignorées : elles sont
+ * décrites à travers la police Type0 qui les référence.
+ */
+fn analyser_police_pdf(document: &Document, id: lopdf::ObjectId, dictionnaire: &lopdf::Dictionary) -> Option<InfoPolicePdf> {
+    if nom_dans_dictionnaire(dictionnaire, b"Type") != Some("Font") {
+        return None;
+    }
+    
+    let sous_type = nom_dans_dictionnaire(dictionnaire, b"Subtype").unwrap_or("Inconnu").to_string();
+    if sous_type.starts_with("CIDFontType") {
+        return None;
+    }
+    
+    let nom = nom_dans_dictionnaire(dictionnaire, b"BaseFont").unwrap_or("(sans nom)").to_string();
+    
+    let incorporee = match sous_type.as_str() {
+        // Les glyphes Type3 sont décrits directement dans le PDF
+        "Type3" => true,
+        "Type0" => dictionnaire
+            .get(b"DescendantFonts")
+            .ok()
+            .and_then(|descendants| document.dereference(descendants).ok())
+            .and_then(|(_, descendants)| descendants.as_array().ok())
+            .and_then(|descendants| descendants.first())
+            .and_then(|descendante| document.dereference(descendante).ok())
+            .and_then(|(_, descendante)| descendante.as_dict().ok())
+            .map(|descendante| descripteur_police_incorpore(document, descendante))
+            .unwrap_or(false),
+        _ => descripteur_police_incorpore(document, dictionnaire),
+    };
+    
+    Some(InfoPolicePdf {
+        objet: reference_objet(id),
+        sous_ensemble: est_police_sous_ensemble(&nom),
+        nom,
+        sous_type,
+        incorporee,
+    })
+}
+
+/**
+ * Vérifie si le descripteur d'une police référence un programme de police
+ */
+fn descripteur_police_incorpore(document: &Document, police: &lopdf::Dictionary) -> bool {
+    dictionnaire_deref(document, police, b"FontDescriptor")
+        .map(|descripteur| {
+            descripteur.has(b"FontFile") || descripteur.has(b"FontFile2") || descripteur.has(b"FontFile3")
+        })
+        .unwrap_or(false)
+}
+
+/**
+ * Indique si un nom de police correspond à un sous-ensemble ("ABCDEF+Nom")
+ */
+fn est_police_sous_ensemble(nom: &str) -> bool {
+    let octets = nom.as_bytes();
+    octets.len() > 7 && octets[6] == b'+' && octets[..6].iter().all(|c| c.is_ascii_uppercase())
+}
+
+/**
+ * Extrait les caractéristiques d'un flux image
+ */
+fn analyser_image_pdf(document: &Document, id: lopdf::ObjectId, stream: &lopdf::Stream) -> InfoImagePdf {
+    let entier = |cle: &[u8]| {
+        stream.dict
+            .get(cle)
+            .ok()
+            .and_then(|valeur| document.dereference(valeur).ok())
+            .and_then(|(_, valeur)| valeur.as_i64().ok())
+    };
+    
+    let espace_couleur = match stream.dict.get(b"ColorSpace").map(|valeur| document.dereference(valeur)) {
+        Ok(Ok((_, Object::Name(nom)))) => String::from_utf8_lossy(nom).to_string(),
+        Ok(Ok((_, Object::Array(tableau)))) => tableau
+            .first()
+            .and_then(|premier| premier.as_name().ok())
+            .map(|nom| String::from_utf8_lossy(nom).to_string())
+            .unwrap_or_else(|| "Inconnu".to_string()),
+        _ if stream.dict.has(b"ImageMask") => "Masque".to_string(),
+        _ => "Inconnu".to_string(),
+    };
+    
+    InfoImagePdf {
+        objet: reference_objet(id),
+        largeur: entier(b"Width").unwrap_or(0).max(0) as u32,
+        hauteur: entier(b"Height").unwrap_or(0).max(0) as u32,
+        filtres: stream
+            .filters()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|filtre| String::from_utf8_lossy(filtre).into_owned())
+            .collect(),
+        espace_couleur,
+        bits_par_composante: entier(b"BitsPerComponent").map(|bits| bits.max(0) as u32),
+        taille_octets: stream.content.len() as u64,
+    }
+}
+
+/**
+ * Répartit la taille du fichier entre les grandes catégories d'objets
+ * 
+ * Les flux sont mesurés par la taille de leurs données stockées ; tout le
+ * reste (dictionnaires, table de références) est regroupé dans "Structure".
+ */
+fn calculer_repartition_espace(document: &Document, taille_octets: u64) -> Vec<RepartitionEspacePdf> {
+    use std::collections::{BTreeMap, HashSet};
+    
+    // Identifier les flux référencés par leur rôle plutôt que par leur dictionnaire
+    let mut flux_polices = HashSet::new();
+    let mut flux_icc = HashSet::new();
+    let mut flux_contenu = HashSet::new();
+    
+    for page_id in document.page_iter() {
+        flux_contenu.extend(document.get_page_contents(page_id));
+    }
+    
+    for objet in document.objects.values() {
+        let dictionnaire = match objet {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => continue,
+        };
+        
+        for cle in [&b"FontFile"[..], b"FontFile2", b"FontFile3"] {
+            if let Ok(reference) = dictionnaire.get(cle).and_then(Object::as_reference) {
+                flux_polices.insert(reference);
+            }
+        }
+        
+        for (_, valeur) in dictionnaire.iter() {
+            if let Ok(tableau) = valeur.as_array() {
+                if tableau.len() == 2 && tableau[0].as_name().ok() == Some(b"ICCBased".as_slice()) {
+                    if let Ok(reference) = tableau[1].as_reference() {
+                        flux_icc.insert(reference);
+                    }
+                }
+            }
+        }
+    }
+    
+    let mut categories: BTreeMap<&str, (u32, u64)> = BTreeMap::new();
+    let mut taille_flux = 0u64;
+    
+    for (id, objet) in &document.objects {
+        let stream = match objet {
+            Object::Stream(stream) => stream,
+            _ => continue,
+        };
+        
+        let categorie = if flux_polices.contains(id) {
+            "Polices"
+        } else if flux_icc.contains(id) {
+            "Profils ICC"
+        } else if flux_contenu.contains(id) {
+            "Contenu des pages"
+        } else {
+            match (nom_dans_dictionnaire(&stream.dict, b"Type"), nom_dans_dictionnaire(&stream.dict, b"Subtype")) {
+                (_, Some("Image")) => "Images",
+                (_, Some("Form")) => "Formulaires XObject",
+                (Some("Metadata"), _) => "Métadonnées XMP",
+                (Some("EmbeddedFile"), _) => "Fichiers joints",
+                _ => "Autres flux",
+            }
+        };
+        
+        let taille = stream.content.len() as u64;
+        let entree = categories.entry(categorie).or_insert((0, 0));
+        entree.0 += 1;
+        entree.1 += taille;
+        taille_flux += taille;
+    }
+    
+    let nombre_dictionnaires = document.objects.values().filter(|objet| !matches!(objet, Object::Stream(_))).count() as u32;
+    categories.insert("Structure et dictionnaires", (nombre_dictionnaires, taille_octets.saturating_sub(taille_flux)));
+    
+    let mut repartition: Vec<RepartitionEspacePdf> = categories
+        .into_iter()
+        .map(|(categorie, (nombre_objets, taille))| RepartitionEspacePdf {
+            categorie: categorie.to_string(),
+            nombre_objets,
+            taille_octets: taille,
+            pourcentage: if taille_octets > 0 {
+                taille as f32 / taille_octets as f32 * 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    
+    repartition.sort_by_key(|entree| std::cmp::Reverse(entree.taille_octets));
+    repartition
+}
+
+/**
+ * Recherche un attribut de page, en remontant l'arbre des pages si besoin
+ * 
+ * MediaBox, CropBox, Resources et Rotate peuvent être hérités d'un nœud parent.
+ */
+fn obtenir_attribut_page<'a>(document: &'a Document, page_id: lopdf::ObjectId, cle: &[u8]) -> Option<&'a Object> {
+    let mut noeud = document.get_dictionary(page_id).ok()?;
+    
+    // Limite de profondeur pour se protéger des arbres de pages cycliques
+    for _ in 0..32 {
+        if let Ok(valeur) = noeud.get(cle) {
+            return document.dereference(valeur).ok().map(|(_, valeur)| valeur);
+        }
+        
+        let parent = noeud.get(b"Parent").and_then(Object::as_reference).ok()?;
+        noeud = document.get_dictionary(parent).ok()?;
+    }
+    
+    None
+}
+
+/**
+ * Calcule la largeur et la hauteur d'une page en points (MediaBox)
+ */
+fn obtenir_dimensions_page(document: &Document, page_id: lopdf::ObjectId) -> (f32, f32) {
+    let boite: Vec<f32> = obtenir_attribut_page(document, page_id, b"MediaBox")
+        .and_then(|boite| boite.as_array().ok())
+        .map(|valeurs| {
+            valeurs
+                .iter()
+                .filter_map(|valeur| document.dereference(valeur).ok())
+                .filter_map(|(_, valeur)| valeur.as_float().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    if boite.len() == 4 {
+        ((boite[2] - boite[0]).abs(), (boite[3] - boite[1]).abs())
+    } else {
+        // Format A4 par défaut si la MediaBox est absente ou invalide
+        (595.0, 842.0)
+    }
+}
+
+/**
+ * Lit un nom PDF dans un dictionnaire (ex: /Type /Font -> "Font")
+ */
+fn nom_dans_dictionnaire<'a>(dictionnaire: &'a lopdf::Dictionary, cle: &[u8]) -> Option<&'a str> {
+    dictionnaire
+        .get(cle)
+        .ok()
+        .and_then(|valeur| valeur.as_name().ok())
+        .and_then(|nom| std::str::from_utf8(nom).ok())
+}
+
+/**
+ * Lit un sous-dictionnaire, qu'il soit direct ou référencé
+ */
+fn dictionnaire_deref<'a>(document: &'a Document, dictionnaire: &'a lopdf::Dictionary, cle: &[u8]) -> Option<&'a lopdf::Dictionary> {
+    let valeur = dictionnaire.get(cle).ok()?;
+    let (_, valeur) = document.dereference(valeur).ok()?;
+    valeur.as_dict().ok()
+}
+
+/**
+ * Formate l'identifiant d'un objet comme une référence PDF ("12 0 R")
+ */
+fn reference_objet(id: lopdf::ObjectId) -> String {
+    format!("{} {} R", id.0, id.1)
+}
+
+/**
+ * Recherche les objets qui déclarent du JavaScript
+ * 
+ * Sont détectés les actions JavaScript (/S /JavaScript ou clé /JS) et
+ * l'arbre de noms /JavaScript du catalogue.
+ */
+fn rechercher_objets_javascript(document: &Document) -> Vec<lopdf::ObjectId> {
+    let mut objets: Vec<lopdf::ObjectId> = document
+        .objects
+        .iter()
+        .filter(|(_, objet)| match objet {
+            Object::Dictionary(dict) => dict.has(b"JS") || nom_dans_dictionnaire(dict, b"S") == Some("JavaScript"),
+            _ => false,
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    
+    if let Ok(catalogue_id) = document.trailer.get(b"Root").and_then(Object::as_reference) {
+        let javascript_nomme = document
+            .get_dictionary(catalogue_id)
+            .ok()
+            .and_then(|catalogue| dictionnaire_deref(document, catalogue, b"Names"))
+            .map(|noms| noms.has(b"JavaScript"))
+            .unwrap_or(false);
+        
+        if javascript_nomme {
+            objets.push(catalogue_id);
+        }
+    }
+    
+    objets
+}
+
+/**
+ * Applique les règles PDF/A inspectables hors ligne (hors chiffrement)
+ */
+fn verifier_regles_pdfa(document: &Document, niveau: &str) -> Vec<ViolationPdfa> {
+    let mut violations = Vec::new();
+    
+    // Règle : toutes les polices doivent être incorporées
+    let polices_manquantes: Vec<String> = document
+        .objects
+        .iter()
+        .filter_map(|(id, objet)| objet.as_dict().ok().and_then(|dict| analyser_police_pdf(document, *id, dict)))
+        .filter(|police| !police.incorporee)
+        .map(|police| format!("{} ({})", police.objet, police.nom))
+        .collect();
+    
+    if !polices_manquantes.is_empty() {
+        violations.push(ViolationPdfa {
+            regle: "polices_incorporees".to_string(),
+            description: format!("{} police(s) ne sont pas incorporées", polices_manquantes.len()),
+            objets: polices_manquantes,
+        });
+    }
+    
+    // Règle : pas de JavaScript ni d'action de lancement
+    let objets_javascript = rechercher_objets_javascript(document);
+    if !objets_javascript.is_empty() {
+        violations.push(ViolationPdfa {
+            regle: "javascript".to_string(),
+            description: "Le document contient du JavaScript".to_string(),
+            objets: objets_javascript.into_iter().map(reference_objet).collect(),
+        });
+    }
+    
+    let actions_interdites: Vec<String> = document
+        .objects
+        .iter()
+        .filter(|(_, objet)| {
+            objet.as_dict().ok()
+                .and_then(|dict| nom_dans_dictionnaire(dict, b"S"))
+                .map(|action| matches!(action, "Launch" | "Sound" | "Movie" | "ResetForm" | "ImportData"))
+                .unwrap_or(false)
+        })
+        .map(|(id, _)| reference_objet(*id))
+        .collect();
+    
+    if !actions_interdites.is_empty() {
+        violations.push(ViolationPdfa {
+            regle: "actions_interdites".to_string(),
+            description: "Le document contient des actions interdites (Launch, Sound, Movie, ResetForm, ImportData)".to_string(),
+            objets: actions_interdites,
+        });
+    }
+    
+    // Règle : la compression LZW est interdite
+    let flux_lzw: Vec<String> = document
+        .objects
+        .iter()
+        .filter(|(_, objet)| {
+            objet.as_stream()
+                .map(|stream| stream.filters().unwrap_or_default().iter().any(|filtre| *filtre == b"LZWDecode"))
+                .unwrap_or(false)
+        })
+        .map(|(id, _)| reference_objet(*id))
+        .collect();
+    
+    if !flux_lzw.is_empty() {
+        violations.push(ViolationPdfa {
+            regle: "filtre_lzw".to_string(),
+            description: "Des flux utilisent la compression LZW, interdite en PDF/A".to_string(),
+            objets: flux_lzw,
+        });
+    }
+    
+    let catalogue_id = document.trailer.get(b"Root").and_then(Object::as_reference).ok();
+    let catalogue = document.catalog().ok();
+    
+    // Règle : un OutputIntent PDF/A avec profil ICC doit être présent
+    let output_intents: Vec<(Option<lopdf::ObjectId>, &lopdf::Dictionary)> = catalogue
+        .and_then(|catalogue| catalogue.get(b"OutputIntents").ok())
+        .and_then(|intents| document.dereference(intents).ok())
+        .and_then(|(_, intents)| intents.as_array().ok())
+        .map(|intents| {
+            intents
+                .iter()
+                .filter_map(|intent| document.dereference(intent).ok())
+                .filter_map(|(id, intent)| intent.as_dict().ok().map(|dict| (id, dict)))
+                .filter(|(_, intent)| nom_dans_dictionnaire(intent, b"S") == Some("GTS_PDFA1"))
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    if output_intents.is_empty() {
+        violations.push(ViolationPdfa {
+            regle: "output_intent".to_string(),
+            description: "Aucun OutputIntent GTS_PDFA1 n'est déclaré dans le catalogue".to_string(),
+            objets: catalogue_id.map(reference_objet).into_iter().collect(),
+        });
+    } else {
+        let sans_profil: Vec<String> = output_intents
+            .iter()
+            .filter(|(_, intent)| !intent.has(b"DestOutputProfile"))
+            .map(|(id, _)| id.map(reference_objet).unwrap_or_else(|| "OutputIntents".to_string()))
+            .collect();
+        
+        if !sans_profil.is_empty() {
+            violations.push(ViolationPdfa {
+                regle: "output_intent".to_string(),
+                description: "L'OutputIntent ne référence pas de profil ICC (DestOutputProfile)".to_string(),
+                objets: sans_profil,
+            });
+        }
+    }
+    
+    // Règle : métadonnées XMP présentes, identifiées PDF/A et cohérentes avec Info
+    violations.extend(verifier_metadonnees_xmp(document, niveau, catalogue_id));
+    
+    if niveau == "1b" {
+        // Règle 1b : aucune transparence
+        let objets_transparents = rechercher_transparence(document);
+        if !objets_transparents.is_empty() {
+            violations.push(ViolationPdfa {
+                regle: "transparence".to_string(),
+                description: "Le document utilise de la transparence, interdite en PDF/A-1".to_string(),
+                objets: objets_transparents.into_iter().map(reference_objet).collect(),
+            });
+        }
+        
+        // Règle 1b : pas de fichiers joints
+        let fichiers_joints = catalogue
+            .and_then(|catalogue| dictionnaire_deref(document, catalogue, b"Names"))
+            .map(|noms| noms.has(b"EmbeddedFiles"))
+            .unwrap_or(false);
+        
+        if fichiers_joints {
+            violations.push(ViolationPdfa {
+                regle: "fichiers_joints".to_string(),
+                description: "Les fichiers joints sont interdits en PDF/A-1".to_string(),
+                objets: catalogue_id.map(reference_objet).into_iter().collect(),
+            });
+        }
+    }
+    
+    violations
+}
+
+/**
+ * Vérifie le flux XMP du catalogue et sa cohérence avec le dictionnaire Info
+ */
+fn verifier_metadonnees_xmp(document: &Document, niveau: &str, catalogue_id: Option<lopdf::ObjectId>) -> Vec<ViolationPdfa> {
+    let mut violations = Vec::new();
+    
+    let metadonnees = document
+        .catalog()
+        .ok()
+        .and_then(|catalogue| catalogue.get(b"Metadata").ok())
+        .and_then(|metadonnees| document.dereference(metadonnees).ok())
+        .and_then(|(id, metadonnees)| metadonnees.as_stream().ok().map(|stream| (id, stream)));
+    
+    let (metadonnees_id, flux) = match metadonnees {
+        Some(metadonnees) => metadonnees,
+        None => {
+            violations.push(ViolationPdfa {
+                regle: "metadonnees_xmp".to_string(),
+                description: "Le catalogue ne contient pas de flux de métadonnées XMP".to_string(),
+                objets: catalogue_id.map(reference_objet).into_iter().collect(),
+            });
+            return violations;
+        }
+    };
+    
+    let objets_xmp: Vec<String> = metadonnees_id.map(reference_objet).into_iter().collect();
+    let contenu = flux.decompressed_content().unwrap_or_else(|_| flux.content.clone());
+    let xmp = String::from_utf8_lossy(&contenu);
+    
+    // Identification PDF/A dans le XMP
+    let partie_attendue = &niveau[..1];
+    let partie = extraire_valeur_xmp(&xmp, "pdfaid:part");
+    let conformite = extraire_valeur_xmp(&xmp, "pdfaid:conformance");
+    
+    if partie.as_deref() != Some(partie_attendue) || !conformite.map(|c| c.eq_ignore_ascii_case("b")).unwrap_or(false) {
+        violations.push(ViolationPdfa {
+            regle: "identification_pdfa".to_string(),
+            description: format!("Le XMP ne déclare pas pdfaid:part={} et pdfaid:conformance=B", partie_attendue),
+            objets: objets_xmp.clone(),
+        });
+    }
+    
+    // Cohérence des entrées du dictionnaire Info avec leurs équivalents XMP
+    let info = document
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|info| document.dereference(info).ok())
+        .and_then(|(id, info)| info.as_dict().ok().map(|dict| (id, dict)));
+    
+    if let Some((info_id, info)) = info {
+        let correspondances: [(&[u8], &str); 7] = [
+            (b"Title", "dc:title"),
+            (b"Author", "dc:creator"),
+            (b"Subject", "dc:description"),
+            (b"Keywords", "pdf:Keywords"),
+            (b"Creator", "xmp:CreatorTool"),
+            (b"Producer", "pdf:Producer"),
+            (b"CreationDate", "xmp:CreateDate"),
+        ];
+        
+        for (cle, propriete) in correspondances {
+            let valeur_info = match info.get(cle).ok().and_then(|valeur| valeur.as_str().ok()) {
+                Some(octets) => decoder_chaine_pdf(octets),
+                None => continue,
+            };
+            
+            let valeur_xmp = extraire_valeur_xmp(&xmp, propriete);
+            
+            let coherent = match (&valeur_xmp, cle) {
+                (Some(xmp_valeur), b"CreationDate") => date_pdf_vers_xmp(&valeur_info)
+                    .map(|date| xmp_valeur.starts_with(&date))
+                    .unwrap_or(false),
+                (Some(xmp_valeur), _) => xmp_valeur.trim() == valeur_info.trim(),
+                (None, _) => false,
+            };
+            
+            if !coherent {
+                let mut objets = objets_xmp.clone();
+                objets.extend(info_id.map(reference_objet));
+                
+                violations.push(ViolationPdfa {
+                    regle: "metadonnees_xmp".to_string(),
+                    description: format!(
+                        "Info /{} (\"{}\") ne correspond pas à {} ({})",
+                        String::from_utf8_lossy(cle),
+                        valeur_info,
+                        propriete,
+                        valeur_xmp.map(|valeur| format!("\"{}\"", valeur)).unwrap_or_else(|| "absent".to_string())
+                    ),
+                    objets,
+                });
+            }
+        }
+    }
+    
+    violations
+}
+
+/**
+ * Recherche les objets qui introduisent de la transparence
+ * 
+ * Sont concernés : les ExtGState avec masque doux, opacité inférieure à 1
+ * ou mode de fusion non normal, les images avec /SMask et les groupes
+ * de transparence des pages et formulaires.
+ */
+fn rechercher_transparence(document: &Document) -> Vec<lopdf::ObjectId> {
+    document
+        .objects
+        .iter()
+        .filter(|(_, objet)| {
+            let dictionnaire = match objet {
+                Object::Dictionary(dict) => dict,
+                Object::Stream(stream) => &stream.dict,
+                _ => return false,
+            };
+            
+            let masque_doux = match dictionnaire.get(b"SMask") {
+                Ok(Object::Name(nom)) => nom != b"None",
+                Ok(_) => true,
+                Err(_) => false,
+            };
+            
+            let opacite_partielle = [&b"CA"[..], b"ca"].iter().any(|cle| {
+                dictionnaire.get(cle).and_then(Object::as_float).map(|opacite| opacite < 1.0).unwrap_or(false)
+            });
+            
+            let fusion = nom_dans_dictionnaire(dictionnaire, b"BM")
+                .map(|mode| mode != "Normal" && mode != "Compatible")
+                .unwrap_or(false);
+            
+            let groupe_transparence = dictionnaire_deref(document, dictionnaire, b"Group")
+                .map(|groupe| nom_dans_dictionnaire(groupe, b"S") == Some("Transparency"))
+                .unwrap_or(false);
+            
+            masque_doux || opacite_partielle || fusion || groupe_transparence
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/**
+ * Extrait la valeur d'une propriété d'un paquet XMP
+ * 
+ * Gère les deux écritures possibles : élément (`<pdf:Producer>x</pdf:Producer>`,
+ * y compris les listes rdf:Alt/Seq/Bag dont on lit le premier élément) et
+ * attribut (`pdf:Producer="x"`).
+ */
+fn extraire_valeur_xmp(xmp: &str, propriete: &str) -> Option<String> {
+    let ouverture = format!("<{}", propriete);
+    let fermeture = format!("</{}>", propriete);
+    
+    let mut recherche = 0;
+    while let Some(position) = xmp[recherche..].find(&ouverture) {
+        let debut = recherche + position + ouverture.len();
+        recherche = debut;
+        
+        // Éviter les préfixes communs (ex: dc:title vs dc:titleAlt)
+        let suivant = xmp[debut..].chars().next()?;
+        if suivant != '>' && suivant != '/' && !suivant.is_whitespace() {
+            continue;
+        }
+        
+        let fin_balise = debut + xmp[debut..].find('>')?;
+        if xmp[..fin_balise].ends_with('/') {
+            continue;
+        }
+        
+        let fin = fin_balise + xmp[fin_balise..].find(&fermeture)?;
+        let mut contenu = &xmp[fin_balise + 1..fin];
+        
+        if let Some(element) = contenu.find("<rdf:li") {
+            let debut_li = element + contenu[element..].find('>')? + 1;
+            let fin_li = debut_li + contenu[debut_li..].find("</rdf:li>")?;
+            contenu = &contenu[debut_li..fin_li];
+        }
+        
+        return Some(decoder_entites_xml(contenu.trim()));
+    }
+    
+    let attribut = format!("{}=", propriete);
+    let position = xmp.find(&attribut)? + attribut.len();
+    let guillemet = xmp[position..].chars().next()?;
+    if guillemet != '"' && guillemet != '\'' {
+        return None;
+    }
+    let debut = position + 1;
+    let fin = debut + xmp[debut..].find(guillemet)?;
+    
+    Some(decoder_entites_xml(&xmp[debut..fin]))
+}
+
+/**
+ * Décode les entités XML prédéfinies
+ */
+fn decoder_entites_xml(texte: &str) -> String {
+    texte
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/**
+ * Décode une chaîne texte PDF (UTF-16BE avec BOM ou PDFDocEncoding)
+ */
+fn decoder_chaine_pdf(octets: &[u8]) -> String {
+    if octets.starts_with(&[0xFE, 0xFF]) {
+        let unites: Vec<u16> = octets[2..]
+            .chunks_exact(2)
+            .map(|paire| u16::from_be_bytes([paire[0], paire[1]]))
+            .collect();
+        return String::from_utf16_lossy(&unites);
+    }
+    
+    if let Some(utf8) = octets.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(utf8).to_string();
+    }
+    
+    // PDFDocEncoding coïncide avec Latin-1 pour les caractères courants
+    octets.iter().map(|&octet| octet as char).collect()
+}
+
+/**
+ * Convertit une date PDF (D:AAAAMMJJHHmmSS) au format XMP (AAAA-MM-JJTHH:mm:SS)
+ * 
+ * Seule la partie présente dans la date PDF est produite, afin de pouvoir
+ * comparer par préfixe avec la date XMP.
+ */
+fn date_pdf_vers_xmp(date: &str) -> Option<String> {
+    let chiffres: String = date
+        .trim_start_matches("D:")
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    
+    if chiffres.len() < 4 {
+        return None;
+    }
+    
+    let separateurs = ["", "-", "-", "T", ":", ":"];
+    let mut resultat = String::new();
+    
+    for (index, separateur) in separateurs.iter().enumerate() {
+        let debut = if index == 0 { 0 } else { 2 + index * 2 };
+        let fin = debut + if index == 0 { 4 } else { 2 };
+        if chiffres.len() < fin {
+            break;
+        }
+        resultat.push_str(separateur);
+        resultat.push_str(&chiffres[debut..fin]);
+    }
+    
+    Some(resultat)
+}
+
+// === Tests unitaires ===
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /**
+     * Test de base pour vérifier que le module peut être importé
+     */
+    #[test]
     fn test_module_pdf() {
         // Vérifie que le module est correctement configuré
         assert!(true, "Le module PDF est correctement configuré");
@@ -501,4 +3086,336 @@ mod tests {
         
         assert_eq!(options_division.mode, "pages", "Le mode de division devrait être configurable");
     }
+    
+    /**
+     * Test de l'inspection d'un document construit en mémoire
+     */
+    #[test]
+    fn test_inspection_pdf() {
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let police_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_id = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Rotate" => -90,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => police_id } },
+        });
+        document.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }));
+        let catalogue_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalogue_id);
+        
+        let rapport = construire_rapport_inspection(&document, "test.pdf", 1000);
+        
+        assert_eq!(rapport.nombre_pages, 1);
+        assert_eq!(rapport.pages[0].largeur_points, 612.0, "La MediaBox doit être héritée du nœud Pages");
+        assert_eq!(rapport.pages[0].rotation, 270);
+        assert_eq!(rapport.polices.len(), 1);
+        assert!(!rapport.polices[0].incorporee, "Helvetica standard n'est pas incorporée");
+        assert!(!rapport.contient_javascript);
+        assert!(est_police_sous_ensemble("ABCDEF+Arial"));
+        assert!(!est_police_sous_ensemble("Arial"));
+    }
+    
+    /**
+     * Test de lecture des propriétés XMP et des dates PDF
+     */
+    #[test]
+    fn test_extraction_xmp() {
+        let xmp = r#"<rdf:Description pdfaid:part="1" pdfaid:conformance="B">
+            <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Rapport &amp; annexes</rdf:li></rdf:Alt></dc:title>
+            <pdf:Producer>MacGyver</pdf:Producer>
+        </rdf:Description>"#;
+        
+        assert_eq!(extraire_valeur_xmp(xmp, "pdfaid:part").as_deref(), Some("1"));
+        assert_eq!(extraire_valeur_xmp(xmp, "dc:title").as_deref(), Some("Rapport & annexes"));
+        assert_eq!(extraire_valeur_xmp(xmp, "pdf:Producer").as_deref(), Some("MacGyver"));
+        assert_eq!(extraire_valeur_xmp(xmp, "xmp:CreatorTool"), None);
+        
+        assert_eq!(date_pdf_vers_xmp("D:20240131120000+01'00'").as_deref(), Some("2024-01-31T12:00:00"));
+        assert_eq!(date_pdf_vers_xmp("D:2024").as_deref(), Some("2024"));
+        assert_eq!(decoder_chaine_pdf(&[0xFE, 0xFF, 0x00, 0xE9]), "é");
+    }
+    
+    /**
+     * Test d'écriture puis de relecture de l'arbre des signets
+     */
+    #[test]
+    fn test_signets_pdf() {
+        let mut document = creer_document_test(3);
+        
+        let signets = vec![
+            SignetPdf {
+                titre: "Introduction".to_string(),
+                page: Some(1),
+                enfants: vec![SignetPdf { titre: "Contexte élargi".to_string(), page: Some(2), enfants: vec![] }],
+            },
+            SignetPdf { titre: "Annexes".to_string(), page: None, enfants: vec![] },
+        ];
+        
+        assert_eq!(ecrire_arbre_signets(&mut document, &signets), Ok(3));
+        assert_eq!(extraire_signets_pdf(&document), signets);
+        
+        let hors_limite = vec![SignetPdf { titre: "Fin".to_string(), page: Some(4), enfants: vec![] }];
+        assert!(ecrire_arbre_signets(&mut document, &hors_limite).is_err());
+    }
+    
+    /**
+     * Test de l'insertion de pages blanches et de la mesure d'encre
+     */
+    #[test]
+    fn test_pages_blanches() {
+        let options = OptionsInsertionPagesBlanches { mode: "apres_impaires".to_string(), positions: None };
+        assert_eq!(
+            calculer_ordre_insertion(3, &options),
+            Ok(vec![Some(1), None, Some(2), Some(3), None])
+        );
+        
+        let options = OptionsInsertionPagesBlanches { mode: "positions".to_string(), positions: Some(vec![2, 0]) };
+        assert_eq!(calculer_ordre_insertion(2, &options), Ok(vec![None, Some(1), Some(2), None]));
+        
+        let options = OptionsInsertionPagesBlanches { mode: "positions".to_string(), positions: Some(vec![5]) };
+        assert!(calculer_ordre_insertion(2, &options).is_err());
+        
+        let mut document = creer_document_test(3);
+        let premiere = document.get_pages()[&1];
+        let blanche = creer_page_blanche(&mut document, Some(premiere));
+        let mut ordre: Vec<lopdf::ObjectId> = document.get_pages().into_values().collect();
+        ordre.insert(1, blanche);
+        reconstruire_arbre_pages(&mut document, &ordre).unwrap();
+        assert_eq!(document.get_pages().len(), 4);
+        assert_eq!(obtenir_dimensions_page(&document, blanche), (595.0, 842.0));
+        
+        let mut feuille = image::GrayImage::from_pixel(100, 100, image::Luma([255]));
+        assert_eq!(calculer_taux_encre(&feuille), 0.0);
+        for x in 10..20 {
+            for y in 10..90 {
+                feuille.put_pixel(x, y, image::Luma([0]));
+            }
+        }
+        assert!(calculer_taux_encre(&feuille) > 5.0);
+    }
+    
+    /**
+     * Test des masques 1 bit (/ImageMask) et de la redirection des signets vers les pages conservées
+     */
+    #[test]
+    fn test_pages_blanches_masque_image() {
+        let mut document = creer_document_test(3);
+        let pages: Vec<lopdf::ObjectId> = document.get_pages().into_values().collect();
+        
+        // Bits à 1 : rien n'est peint ; le second masque peint un bandeau sur la moitié des lignes
+        let blanche: Vec<u8> = vec![0xFF; 8 * 64];
+        let encree: Vec<u8> = (0..64).flat_map(|ligne| vec![if ligne % 2 == 0 { 0x00 } else { 0xFF }; 8]).collect();
+        for (page_id, donnees) in [(pages[0], blanche), (pages[1], encree)] {
+            let image_id = document.add_object(lopdf::Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => 64,
+                    "Height" => 64,
+                    "ImageMask" => true,
+                },
+                donnees,
+            ));
+            let page = document.get_dictionary_mut(page_id).unwrap();
+            page.set("Resources", dictionary! { "XObject" => dictionary! { "Im0" => image_id } });
+        }
+        
+        assert_eq!(mesurer_encre_page(&document, pages[0]), Ok(0.0));
+        let encre = mesurer_encre_page(&document, pages[1]).unwrap();
+        assert!((40.0..60.0).contains(&encre), "taux d'encre : {}", encre);
+        
+        // Un signet et un lien visaient la page blanche retirée
+        let signet_id = document.add_object(dictionary! {
+            "Title" => Object::string_literal("Début"),
+            "Dest" => vec![pages[0].into(), "XYZ".into(), 0.into(), 842.into(), Object::Null],
+        });
+        let lien_id = document.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "A" => dictionary! { "S" => "GoTo", "D" => vec![pages[0].into(), "Fit".into()] },
+        });
+        document.get_dictionary_mut(pages[2]).unwrap().set("Annots", vec![lien_id.into()]);
+        let catalogue_id = document.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+        let plan_id = document.add_object(dictionary! { "Type" => "Outlines", "First" => signet_id, "Last" => signet_id, "Count" => 1 });
+        document.get_dictionary_mut(catalogue_id).unwrap().set("Outlines", plan_id);
+        
+        reconstruire_arbre_pages(&mut document, &pages[1..]).unwrap();
+        assert_eq!(document.get_pages().len(), 2);
+        assert!(document.get_object(pages[0]).is_err());
+        
+        let destination = document.get_dictionary(signet_id).unwrap().get(b"Dest").and_then(Object::as_array).unwrap();
+        assert_eq!(destination[0].as_reference().unwrap(), pages[1]);
+        let action = document.get_dictionary(lien_id).unwrap().get(b"A").and_then(Object::as_dict).unwrap();
+        assert_eq!(action.get(b"D").and_then(Object::as_array).unwrap()[0].as_reference().unwrap(), pages[1]);
+    }
+    
+    /**
+     * Test de la validation des options de lot : dossier de sortie et texte du filigrane
+     */
+    #[test]
+    fn test_options_lot_pdf() {
+        let dossier = std::env::temp_dir().to_string_lossy().to_string();
+        let mut options = OptionsLotPdf {
+            operation: "filigrane".to_string(),
+            recursif: false,
+            concurrence_max: None,
+            dossier_sortie: Some(format!("{}/", dossier)),
+            texte_filigrane: Some("Brouillon – « interne » €".to_string()),
+            opacite_filigrane: None,
+            mot_de_passe_utilisateur: None,
+            mot_de_passe_proprietaire: None,
+            ecraser: None,
+        };
+        
+        assert!(verifier_options_lot(&dossier, &options).unwrap_err().contains("dossier source"));
+        options.ecraser = Some(true);
+        assert_eq!(verifier_options_lot(&dossier, &options), Ok(()));
+        
+        options.dossier_sortie = None;
+        assert!(verifier_options_lot(&dossier, &options).is_err());
+        
+        options.dossier_sortie = Some(format!("{}/sortie_lot", dossier));
+        options.texte_filigrane = Some("Черновик ✓".to_string());
+        assert!(verifier_options_lot(&dossier, &options).unwrap_err().contains("Черновик✓"));
+        
+        options.operation = "chiffrement".to_string();
+        assert!(verifier_options_lot(&dossier, &options).unwrap_err().contains("propriétaire est obligatoire"));
+        options.mot_de_passe_proprietaire = Some("secret".to_string());
+        options.mot_de_passe_utilisateur = Some("secret".to_string());
+        assert!(verifier_options_lot(&dossier, &options).unwrap_err().contains("doit différer"));
+        options.mot_de_passe_utilisateur = None;
+        assert_eq!(verifier_options_lot(&dossier, &options), Ok(()));
+    }
+    
+    /**
+     * Test du chiffrement par lot : relecture avec le mot de passe, puis refus de retraiter le fichier chiffré
+     */
+    #[test]
+    fn test_chiffrement_lot() {
+        let dossier = std::env::temp_dir().join(format!("test_chiffrement_lot_{}", std::process::id()));
+        std::fs::create_dir_all(&dossier).unwrap();
+        let entree = dossier.join("source.pdf");
+        let sortie = dossier.join("chiffre.pdf");
+        
+        let mut document = creer_document_test(2);
+        let info_id = document.add_object(dictionary! { "Title" => Object::string_literal("Rapport") });
+        document.trailer.set("Info", info_id);
+        document.save(&entree).unwrap();
+        
+        let mut options = OptionsLotPdf {
+            operation: "chiffrement".to_string(),
+            recursif: false,
+            concurrence_max: None,
+            dossier_sortie: Some(dossier.to_string_lossy().to_string()),
+            texte_filigrane: None,
+            opacite_filigrane: None,
+            mot_de_passe_utilisateur: Some("lecture".to_string()),
+            mot_de_passe_proprietaire: Some("proprietaire".to_string()),
+            ecraser: None,
+        };
+        traiter_fichier_lot(&entree, Some(&sortie), &options).expect("Le PDF devrait être chiffré");
+        
+        // Le titre ne doit plus apparaître en clair dans le fichier
+        let octets = std::fs::read(&sortie).unwrap();
+        assert!(octets.windows(7).all(|fenetre| fenetre != b"Rapport"));
+        
+        let relu = Document::load(&sortie).unwrap();
+        assert!(relu.is_encrypted());
+        assert!(relu.authenticate_user_password("mauvais").is_err());
+        assert!(relu.authenticate_user_password("lecture").is_ok());
+        assert!(relu.authenticate_owner_password("proprietaire").is_ok());
+        
+        // Sans mot de passe utilisateur, lopdf déchiffre au chargement : le contenu doit revenir intact
+        options.mot_de_passe_utilisateur = None;
+        traiter_fichier_lot(&entree, Some(&sortie), &options).expect("Le PDF devrait être chiffré");
+        let relu = Document::load(&sortie).unwrap();
+        assert!(relu.is_encrypted());
+        assert_eq!(relu.get_pages().len(), 2);
+        assert_eq!(extraire_metadonnees_pdf(&relu).titre.as_deref(), Some("Rapport"));
+        
+        // Un PDF chiffré n'est jamais réécrit sans sa protection
+        options.operation = "compression".to_string();
+        let erreur = traiter_fichier_lot(&sortie, Some(&dossier.join("copie.pdf")), &options).unwrap_err();
+        assert!(erreur.contains("PDF chiffré"), "{}", erreur);
+        assert!(!dossier.join("copie.pdf").exists());
+        
+        std::fs::remove_dir_all(&dossier).ok();
+    }
+    
+    /**
+     * Test du filigrane, relu après sauvegarde
+     */
+    #[test]
+    fn test_filigrane_document() {
+        let mut document = creer_document_test(2);
+        let info_id = document.add_object(dictionary! { "Title" => Object::string_literal("Rapport") });
+        document.trailer.set("Info", info_id);
+        
+        // Seconde page : /Contents référence un tableau de flux
+        let flux_id = document.add_object(lopdf::Stream::new(lopdf::Dictionary::new(), b"0 0 m 10 10 l S".to_vec()));
+        let tableau_id = document.add_object(vec![Object::Reference(flux_id)]);
+        let seconde_id = document.get_pages()[&2];
+        document.get_dictionary_mut(seconde_id).unwrap().set("Contents", tableau_id);
+        
+        ajouter_filigrane_document(&mut document, "CONFIDENTIEL", 0.3).expect("Le filigrane devrait être ajouté");
+        
+        let contenus = document.get_dictionary(seconde_id).unwrap().get(b"Contents").and_then(Object::as_array).unwrap();
+        assert_eq!(contenus.len(), 3);
+        assert_eq!(contenus[1].as_reference().unwrap(), flux_id);
+        assert!(contenus.iter().all(|contenu| document.get_object(contenu.as_reference().unwrap()).and_then(Object::as_stream).is_ok()));
+        
+        let mut tampon = Vec::new();
+        document.save_to(&mut tampon).expect("Le document devrait être sauvegardé");
+        let relu = Document::load_mem(&tampon).expect("Le document devrait être relisible");
+        
+        let page_id = relu.get_pages()[&1];
+        let contenu = String::from_utf8_lossy(&relu.get_page_content(page_id).unwrap()).to_string();
+        assert!(contenu.contains("(CONFIDENTIEL) Tj"), "Le filigrane doit être présent : {}", contenu);
+        assert_eq!(extraire_metadonnees_pdf(&relu).titre.as_deref(), Some("Rapport"));
+    }
+    
+    /**
+     * Crée un document minimal avec des pages vides au format A4
+     */
+    fn creer_document_test(nombre_pages: usize) -> Document {
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        
+        let kids: Vec<Object> = (0..nombre_pages)
+            .map(|_| {
+                document.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                }).into()
+            })
+            .collect();
+        
+        document.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => nombre_pages as i64,
+        }));
+        let catalogue_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalogue_id);
+        
+        document
+    }
 }
\ No newline at end of file
@@ -74,14 +74,38 @@ fn main() {
             
             // Commandes de conversion d'images
             commandes::images::obtenir_info_image,
+            commandes::images::analyser_couleurs_image,
             commandes::images::convertir_image,
+            commandes::images::convertir_images_lot,
+            commandes::images::redresser_images_lot,
+            commandes::images::rechercher_doublons_images,
+            commandes::images::transformer_image,
+            commandes::images::ajuster_image,
+            commandes::images::ajouter_filigrane_image,
+            commandes::images::optimiser_image,
             commandes::images::obtenir_formats_supportes,
+            commandes::images::generer_icones,
+            commandes::images::extraire_trames_animation,
+            commandes::images::creer_animation,
+            commandes::images::convertir_animation,
+            commandes::images::assembler_images,
+            commandes::images::comparer_images,
             commandes::images::supprimer_fond_image,
+            commandes::images::corriger_extensions_images,
+            commandes::images::nettoyer_metadonnees_image,
             
             // Commandes de manipulation de PDF
             commandes::pdf::obtenir_info_pdf,
             commandes::pdf::fusionner_pdfs,
             commandes::pdf::diviser_pdf,
+            commandes::pdf::inspecter_pdf,
+            commandes::pdf::verifier_pdfa,
+            commandes::pdf::lire_signets_pdf,
+            commandes::pdf::ecrire_signets_pdf,
+            commandes::pdf::generer_signets_pdf,
+            commandes::pdf::inserer_pages_blanches,
+            commandes::pdf::detecter_pages_blanches,
+            commandes::pdf::traiter_lot_pdf,
         ])
         
         // === Configuration des plugins ===
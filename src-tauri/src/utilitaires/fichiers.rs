@@ -3,7 +3,7 @@
 // Toutes les fonctions ont été supprimées car elles n'étaient pas utilisées.
 // Ce fichier est conservé pour les futures fonctions de gestion de fichiers.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use log::{info, warn, debug};
 use crate::erreurs::{ErreurUtilitaire, ResultatUtilitaire};
@@ -56,6 +56,64 @@ pub async fn creer_dossier_recursif(chemin: &str) -> ResultatUtilitaire<()> {
     Ok(())
 }
 
+/**
+ * Liste les fichiers d'un dossier dont l'extension fait partie de la liste
+ * 
+ * La comparaison des extensions ignore la casse. Les liens symboliques
+ * vers des dossiers ne sont pas suivis pour éviter les boucles.
+ * 
+ * # Arguments
+ * * `dossier` - Le dossier à parcourir
+ * * `extensions` - Les extensions acceptées, sans le point (ex: `["pdf"]`)
+ * * `recursif` - Parcourir également les sous-dossiers
+ * 
+ * # Retour
+ * * `ResultatUtilitaire<Vec<PathBuf>>` - Fichiers trouvés, triés par chemin
+ */
+pub async fn lister_fichiers_par_extension(dossier: &str, extensions: &[&str], recursif: bool) -> ResultatUtilitaire<Vec<PathBuf>> {
+    debug!("📂 Parcours du dossier : {} (récursif : {})", dossier, recursif);
+    
+    let mut fichiers = Vec::new();
+    let mut a_parcourir = vec![PathBuf::from(dossier)];
+    
+    while let Some(courant) = a_parcourir.pop() {
+        let mut entrees = fs::read_dir(&courant).await.map_err(|e| {
+            warn!("❌ Erreur lors de la lecture du dossier {} : {}", courant.display(), e);
+            ErreurUtilitaire::Validation {
+                message: format!("Impossible de lire le dossier {} : {}", courant.display(), e),
+            }
+        })?;
+        
+        while let Ok(Some(entree)) = entrees.next_entry().await {
+            let Ok(type_entree) = entree.file_type().await else {
+                continue;
+            };
+            let chemin = entree.path();
+            
+            if type_entree.is_dir() {
+                if recursif {
+                    a_parcourir.push(chemin);
+                }
+            } else if type_entree.is_file() {
+                let extension_acceptee = chemin
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| extensions.iter().any(|acceptee| acceptee.eq_ignore_ascii_case(extension)))
+                    .unwrap_or(false);
+                
+                if extension_acceptee {
+                    fichiers.push(chemin);
+                }
+            }
+        }
+    }
+    
+    fichiers.sort();
+    
+    debug!("✅ {} fichier(s) trouvé(s) dans {}", fichiers.len(), dossier);
+    Ok(fichiers)
+}
+
 /**
  * Formate la taille d'un fichier en format lisible
  * 
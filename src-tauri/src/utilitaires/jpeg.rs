@@ -0,0 +1,810 @@
+/**
+ * Codec entropique JPEG pour la transcodification sans perte
+ * 
+ * Décode un JPEG séquentiel jusqu'à ses coefficients DCT quantifiés, puis les
+ * réécrit avec des tables de Huffman optimales, en séquentiel ou en progressif,
+ * à la manière de jpegtran : les pixels ne sont jamais recalculés.
+ */
+
+/**
+ * Coefficients DCT quantifiés d'une composante JPEG, bloc par bloc dans l'ordre zigzag
+ */
+struct ComposanteJpeg {
+    identifiant: u8,
+    facteur_h: usize,
+    facteur_v: usize,
+    /// Largeur de la grille de blocs, complétée jusqu'à un nombre entier de MCU
+    blocs_par_ligne: usize,
+    /// Blocs couverts par l'image, seuls parcourus par un balayage non entrelacé
+    blocs_utiles: (usize, usize),
+    blocs: Vec<[i16; 64]>,
+}
+
+/**
+ * JPEG séquentiel décodé jusqu'aux coefficients, sans repasser par les pixels
+ */
+pub(crate) struct ImageJpegDct {
+    /// Segments recopiés avant la trame : tables de quantification, APPn, commentaires
+    segments: Vec<u8>,
+    /// Segment SOF d'origine, marqueur compris
+    trame: Vec<u8>,
+    composantes: Vec<ComposanteJpeg>,
+    /// Nombre de MCU en largeur et en hauteur
+    mcu: (usize, usize),
+}
+
+/**
+ * Balayage JPEG : composantes concernées et bande spectrale (zigzag) codée
+ */
+struct BalayageJpeg {
+    composantes: Vec<usize>,
+    debut: usize,
+    fin: usize,
+}
+
+#[derive(Clone)]
+struct TableHuffman {
+    /// Nombre de codes de chaque longueur, de 1 à 16 bits
+    longueurs: [u8; 16],
+    symboles: Vec<u8>,
+}
+
+impl TableHuffman {
+    fn lire(contenu: &[u8]) -> Option<Self> {
+        let longueurs: [u8; 16] = contenu.get(..16)?.try_into().ok()?;
+        let nombre = longueurs.iter().map(|&n| n as usize).sum::<usize>();
+        Some(TableHuffman { longueurs, symboles: contenu.get(16..16 + nombre)?.to_vec() })
+    }
+    
+    /**
+     * Table optimale pour des fréquences de symboles (JPEG annexe K.2, codes limités à 16 bits)
+     */
+    fn optimale(frequences: &[u32; 256]) -> Self {
+        // Un symbole fictif de fréquence 1 garantit qu'aucun code n'est fait que de 1
+        let mut poids: Vec<u64> = frequences.iter().map(|&n| n as u64).chain([1]).collect();
+        let mut tailles = [0usize; 257];
+        let mut suivants = [usize::MAX; 257];
+        
+        loop {
+            // Les deux plus petits poids non nuls, le plus grand indice l'emportant à égalité
+            let plus_petit = |exclu: Option<usize>| {
+                (0..257)
+                    .filter(|&i| poids[i] > 0 && Some(i) != exclu)
+                    .min_by_key(|&i| (poids[i], std::cmp::Reverse(i)))
+            };
+            let Some(mut c1) = plus_petit(None) else { break };
+            let Some(mut c2) = plus_petit(Some(c1)) else { break };
+            
+            poids[c1] += poids[c2];
+            poids[c2] = 0;
+            tailles[c1] += 1;
+            while suivants[c1] != usize::MAX {
+                c1 = suivants[c1];
+                tailles[c1] += 1;
+            }
+            suivants[c1] = c2;
+            tailles[c2] += 1;
+            while suivants[c2] != usize::MAX {
+                c2 = suivants[c2];
+                tailles[c2] += 1;
+            }
+        }
+        
+        let mut nombres = [0usize; 258];
+        for &taille in tailles.iter().filter(|&&taille| taille > 0) {
+            nombres[taille] += 1;
+        }
+        for i in (17..nombres.len()).rev() {
+            while nombres[i] > 0 {
+                let mut j = i - 2;
+                while nombres[j] == 0 {
+                    j -= 1;
+                }
+                nombres[i] -= 2;
+                nombres[i - 1] += 1;
+                nombres[j + 1] += 2;
+                nombres[j] -= 1;
+            }
+        }
+        // Le symbole fictif occupe l'un des codes les plus longs
+        if let Some(taille) = (1..=16).rev().find(|&taille| nombres[taille] > 0) {
+            nombres[taille] -= 1;
+        }
+        
+        let mut longueurs = [0u8; 16];
+        for (longueur, nombre) in longueurs.iter_mut().zip(&nombres[1..=16]) {
+            *longueur = *nombre as u8;
+        }
+        let symboles = (1..=256)
+            .flat_map(|taille| (0..256).filter(move |&symbole| tailles[symbole] == taille))
+            .map(|symbole| symbole as u8)
+            .collect();
+        TableHuffman { longueurs, symboles }
+    }
+    
+    /// Code canonique (valeur, longueur) de chaque symbole
+    fn codes(&self) -> [(u32, u8); 256] {
+        let mut codes = [(0, 0); 256];
+        let mut code = 0u32;
+        let mut symboles = self.symboles.iter();
+        for (longueur, &nombre) in self.longueurs.iter().enumerate() {
+            for symbole in symboles.by_ref().take(nombre as usize) {
+                codes[*symbole as usize] = (code, longueur as u8 + 1);
+                code += 1;
+            }
+            code <<= 1;
+        }
+        codes
+    }
+    
+    fn decoder(&self, lecteur: &mut LecteurBitsJpeg) -> Option<u8> {
+        let (mut code, mut premier, mut indice) = (0usize, 0usize, 0usize);
+        for &nombre in &self.longueurs {
+            code |= lecteur.bit()? as usize;
+            let nombre = nombre as usize;
+            if code - premier < nombre {
+                return self.symboles.get(indice + code - premier).copied();
+            }
+            indice += nombre;
+            premier = (premier + nombre) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+struct LecteurBitsJpeg<'a> {
+    donnees: &'a [u8],
+    position: usize,
+    octet: u8,
+    restants: u8,
+}
+
+impl LecteurBitsJpeg<'_> {
+    /// Bit suivant des données entropiques ; None sur un marqueur ou en fin de fichier
+    fn bit(&mut self) -> Option<u8> {
+        if self.restants == 0 {
+            let octet = *self.donnees.get(self.position)?;
+            if octet == 0xFF {
+                if *self.donnees.get(self.position + 1)? != 0x00 {
+                    return None;
+                }
+                self.position += 1;
+            }
+            self.position += 1;
+            self.octet = octet;
+            self.restants = 8;
+        }
+        self.restants -= 1;
+        Some((self.octet >> self.restants) & 1)
+    }
+    
+    /// Amplitude signée codée sur `taille` bits (JPEG F.2.2.1)
+    fn amplitude(&mut self, taille: u8) -> Option<i32> {
+        if taille > 15 {
+            return None;
+        }
+        let mut valeur = 0i32;
+        for _ in 0..taille {
+            valeur = (valeur << 1) | self.bit()? as i32;
+        }
+        if taille > 0 && valeur < 1 << (taille - 1) {
+            valeur -= (1 << taille) - 1;
+        }
+        Some(valeur)
+    }
+    
+    /// Passe le marqueur RSTn qui clôt un intervalle de reprise
+    fn reprendre(&mut self) -> Option<()> {
+        self.restants = 0;
+        // Des octets 0xFF de remplissage peuvent précéder le marqueur
+        while self.donnees.get(self.position..self.position + 2) == Some(&[0xFF, 0xFF]) {
+            self.position += 1;
+        }
+        match self.donnees.get(self.position..self.position + 2)? {
+            [0xFF, 0xD0..=0xD7] => {
+                self.position += 2;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct EcrivainBitsJpeg {
+    sortie: Vec<u8>,
+    tampon: u32,
+    nombre: u8,
+}
+
+impl EcrivainBitsJpeg {
+    fn ecrire(&mut self, valeur: u32, taille: u8) {
+        for decalage in (0..taille).rev() {
+            self.tampon = (self.tampon << 1) | ((valeur >> decalage) & 1);
+            self.nombre += 1;
+            if self.nombre == 8 {
+                let octet = self.tampon as u8;
+                self.sortie.push(octet);
+                if octet == 0xFF {
+                    self.sortie.push(0x00);
+                }
+                self.tampon = 0;
+                self.nombre = 0;
+            }
+        }
+    }
+    
+    /// Complète le dernier octet avec des 1
+    fn terminer(mut self) -> Vec<u8> {
+        if self.nombre > 0 {
+            self.ecrire(0xFF, 8 - self.nombre);
+        }
+        self.sortie
+    }
+}
+
+/**
+ * Destination d'un balayage encodé : comptage des symboles ou écriture des codes
+ * 
+ * Les tables sont numérotées 0 et 1 pour les coefficients DC, 2 et 3 pour les AC.
+ */
+trait SortieEntropique {
+    fn symbole(&mut self, table: usize, symbole: u8);
+    fn bits(&mut self, valeur: u32, taille: u8);
+}
+
+struct FrequencesHuffman([[u32; 256]; 4]);
+
+impl SortieEntropique for FrequencesHuffman {
+    fn symbole(&mut self, table: usize, symbole: u8) {
+        self.0[table][symbole as usize] += 1;
+    }
+    
+    fn bits(&mut self, _valeur: u32, _taille: u8) {}
+}
+
+struct CodageHuffman {
+    codes: [[(u32, u8); 256]; 4],
+    ecrivain: EcrivainBitsJpeg,
+}
+
+impl SortieEntropique for CodageHuffman {
+    fn symbole(&mut self, table: usize, symbole: u8) {
+        let (code, longueur) = self.codes[table][symbole as usize];
+        self.ecrivain.ecrire(code, longueur);
+    }
+    
+    fn bits(&mut self, valeur: u32, taille: u8) {
+        self.ecrivain.ecrire(valeur, taille);
+    }
+}
+
+/**
+ * Lit les coefficients DCT d'un JPEG séquentiel à codage de Huffman
+ * 
+ * Tables de quantification, APP0 (JFIF), profil ICC et APP14 (Adobe, qui fixe
+ * l'espace de couleurs) sont toujours conservés ; les autres segments APPn et les
+ * commentaires sont confiés à `metadonnee`, qui ajoute aux segments recopiés ce
+ * qu'il faut en garder.
+ * 
+ * # Retour
+ * * `Option<ImageJpegDct>` - None pour un JPEG progressif, arithmétique ou illisible
+ */
+pub(crate) fn lire_coefficients_jpeg(jpeg: &[u8], mut metadonnee: impl FnMut(&[u8], &mut Vec<u8>)) -> Option<ImageJpegDct> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    
+    let mut segments = Vec::new();
+    let mut image: Option<ImageJpegDct> = None;
+    let mut tables: Vec<Option<TableHuffman>> = vec![None; 8];
+    let mut intervalle_reprise = 0;
+    let mut position = 2;
+    
+    loop {
+        while jpeg.get(position..position + 2) == Some(&[0xFF, 0xFF]) {
+            position += 1;
+        }
+        let marqueur = match jpeg.get(position..position + 2)? {
+            [0xFF, marqueur] => *marqueur,
+            _ => return None,
+        };
+        if marqueur == 0xD9 {
+            break;
+        }
+        
+        let longueur = u16::from_be_bytes([*jpeg.get(position + 2)?, *jpeg.get(position + 3)?]) as usize;
+        // La longueur inclut ses deux octets : en dessous, le segment est corrompu
+        if longueur < 2 {
+            return None;
+        }
+        let segment = jpeg.get(position..position + 2 + longueur)?;
+        let contenu = &segment[4..];
+        position += 2 + longueur;
+        
+        match marqueur {
+            0xC0 | 0xC1 => image = Some(lire_trame_jpeg(segment)?),
+            0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return None,
+            0xC4 => {
+                let mut curseur = 0;
+                while curseur < contenu.len() {
+                    let (classe, identifiant) = ((contenu[curseur] >> 4) as usize, (contenu[curseur] & 0x0F) as usize);
+                    let table = TableHuffman::lire(&contenu[curseur + 1..])?;
+                    curseur += 17 + table.symboles.len();
+                    *tables.get_mut(classe * 4 + identifiant)? = Some(table);
+                }
+            }
+            0xDD => intervalle_reprise = u16::from_be_bytes([*contenu.first()?, *contenu.get(1)?]) as usize,
+            0xDA => position = decoder_balayage_jpeg(jpeg, position, contenu, image.as_mut()?, &tables, intervalle_reprise)?,
+            0xDB => segments.extend_from_slice(segment),
+            0xE0..=0xEF | 0xFE => {
+                let indispensable = marqueur == 0xE0
+                    || marqueur == 0xEE
+                    || (marqueur == 0xE2 && contenu.starts_with(b"ICC_PROFILE\0"));
+                if indispensable {
+                    segments.extend_from_slice(segment);
+                } else {
+                    metadonnee(segment, &mut segments);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    let mut image = image?;
+    image.segments = segments;
+    Some(image)
+}
+
+/**
+ * Prépare les grilles de coefficients d'après le segment SOF
+ */
+fn lire_trame_jpeg(segment: &[u8]) -> Option<ImageJpegDct> {
+    let contenu = segment.get(4..)?;
+    let hauteur = u16::from_be_bytes([*contenu.get(1)?, *contenu.get(2)?]) as usize;
+    let largeur = u16::from_be_bytes([*contenu.get(3)?, *contenu.get(4)?]) as usize;
+    let nombre = *contenu.get(5)? as usize;
+    if largeur == 0 || hauteur == 0 || !(1..=4).contains(&nombre) {
+        return None;
+    }
+    
+    let facteurs: Vec<(u8, usize, usize)> = contenu.get(6..6 + 3 * nombre)?
+        .chunks(3)
+        .map(|composante| (composante[0], (composante[1] >> 4) as usize, (composante[1] & 0x0F) as usize))
+        .collect();
+    if facteurs.iter().any(|&(_, h, v)| !(1..=4).contains(&h) || !(1..=4).contains(&v)) {
+        return None;
+    }
+    let h_max = facteurs.iter().map(|&(_, h, _)| h).max()?;
+    let v_max = facteurs.iter().map(|&(_, _, v)| v).max()?;
+    let mcu = (largeur.div_ceil(8 * h_max), hauteur.div_ceil(8 * v_max));
+    
+    let composantes = facteurs.into_iter()
+        .map(|(identifiant, facteur_h, facteur_v)| ComposanteJpeg {
+            identifiant,
+            facteur_h,
+            facteur_v,
+            blocs_par_ligne: mcu.0 * facteur_h,
+            blocs_utiles: (
+                (largeur * facteur_h).div_ceil(h_max).div_ceil(8),
+                (hauteur * facteur_v).div_ceil(v_max).div_ceil(8),
+            ),
+            blocs: vec![[0; 64]; mcu.0 * facteur_h * mcu.1 * facteur_v],
+        })
+        .collect();
+    
+    Some(ImageJpegDct { segments: Vec::new(), trame: segment.to_vec(), composantes, mcu })
+}
+
+/**
+ * Blocs d'un balayage dans l'ordre de codage : (composante, indice du bloc)
+ * 
+ * Un balayage entrelacé parcourt les MCU entières, un balayage d'une seule
+ * composante ses seuls blocs utiles, ligne par ligne.
+ */
+fn blocs_balayage(image: &ImageJpegDct, composantes: &[usize]) -> Vec<(usize, usize)> {
+    let mut blocs = Vec::new();
+    
+    if let [composante] = *composantes {
+        let grille = &image.composantes[composante];
+        for y in 0..grille.blocs_utiles.1 {
+            for x in 0..grille.blocs_utiles.0 {
+                blocs.push((composante, y * grille.blocs_par_ligne + x));
+            }
+        }
+    } else {
+        for mcu_y in 0..image.mcu.1 {
+            for mcu_x in 0..image.mcu.0 {
+                for &composante in composantes {
+                    let grille = &image.composantes[composante];
+                    for y in 0..grille.facteur_v {
+                        for x in 0..grille.facteur_h {
+                            let ligne = mcu_y * grille.facteur_v + y;
+                            blocs.push((composante, ligne * grille.blocs_par_ligne + mcu_x * grille.facteur_h + x));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    blocs
+}
+
+/**
+ * Décode un balayage séquentiel et renvoie la position du marqueur suivant
+ */
+fn decoder_balayage_jpeg(
+    jpeg: &[u8],
+    position: usize,
+    entete: &[u8],
+    image: &mut ImageJpegDct,
+    tables: &[Option<TableHuffman>],
+    intervalle_reprise: usize
+) -> Option<usize> {
+    let nombre = *entete.first()? as usize;
+    let mut composantes = Vec::new();
+    let mut tables_composantes = Vec::new();
+    for selecteur in entete.get(1..1 + 2 * nombre)?.chunks(2) {
+        composantes.push(image.composantes.iter().position(|composante| composante.identifiant == selecteur[0])?);
+        let dc = tables.get((selecteur[1] >> 4) as usize)?.as_ref()?;
+        let ac = tables.get(4 + (selecteur[1] & 0x0F) as usize)?.as_ref()?;
+        tables_composantes.push((dc, ac));
+    }
+    if entete.get(1 + 2 * nombre..4 + 2 * nombre)? != [0, 63, 0] {
+        return None;
+    }
+    
+    let blocs_par_mcu = if nombre == 1 {
+        1
+    } else {
+        composantes.iter().map(|&c| image.composantes[c].facteur_h * image.composantes[c].facteur_v).sum()
+    };
+    let blocs = blocs_balayage(image, &composantes);
+    let mut lecteur = LecteurBitsJpeg { donnees: jpeg, position, octet: 0, restants: 0 };
+    let mut predictions = [0i32; 4];
+    let mut avant_reprise = intervalle_reprise;
+    
+    for mcu in blocs.chunks(blocs_par_mcu) {
+        if intervalle_reprise > 0 {
+            if avant_reprise == 0 {
+                lecteur.reprendre()?;
+                predictions = [0; 4];
+                avant_reprise = intervalle_reprise;
+            }
+            avant_reprise -= 1;
+        }
+        
+        for &(composante, indice) in mcu {
+            let (dc, ac) = tables_composantes[composantes.iter().position(|&c| c == composante)?];
+            let bloc = &mut image.composantes[composante].blocs[indice];
+            
+            let taille = dc.decoder(&mut lecteur)?;
+            predictions[composante] += lecteur.amplitude(taille)?;
+            bloc[0] = i16::try_from(predictions[composante]).ok()?;
+            
+            let mut k = 1;
+            while k < 64 {
+                let symbole = ac.decoder(&mut lecteur)?;
+                let (zeros, taille) = ((symbole >> 4) as usize, symbole & 0x0F);
+                if taille == 0 {
+                    if zeros != 15 {
+                        break;
+                    }
+                    k += 16;
+                    continue;
+                }
+                k += zeros;
+                *bloc.get_mut(k)? = lecteur.amplitude(taille)? as i16;
+                k += 1;
+            }
+        }
+    }
+    
+    // Octets de bourrage éventuels jusqu'au marqueur suivant
+    let mut position = lecteur.position;
+    while position + 1 < jpeg.len() && (jpeg[position] != 0xFF || matches!(jpeg[position + 1], 0x00 | 0xD0..=0xD7 | 0xFF)) {
+        position += 1;
+    }
+    Some(position)
+}
+
+/// Catégorie d'une amplitude (nombre de bits) et ses bits de complément (JPEG F.1.2.1)
+fn coder_amplitude(valeur: i32) -> (u8, u32) {
+    let taille = (32 - valeur.unsigned_abs().leading_zeros()) as u8;
+    let bits = if valeur < 0 { valeur + (1 << taille) - 1 } else { valeur };
+    (taille, bits as u32)
+}
+
+/**
+ * Code les blocs d'un balayage : séquentiel, DC progressif ou première passe AC progressive
+ */
+fn encoder_balayage_jpeg(image: &ImageJpegDct, balayage: &BalayageJpeg, sortie: &mut impl SortieEntropique) {
+    let mut predictions = [0i32; 4];
+    let mut fins_de_bande = 0u32;
+    
+    fn vider_fins_de_bande(fins_de_bande: &mut u32, table: usize, sortie: &mut impl SortieEntropique) {
+        if *fins_de_bande > 0 {
+            let taille = 31 - fins_de_bande.leading_zeros();
+            sortie.symbole(table, (taille << 4) as u8);
+            sortie.bits(*fins_de_bande & ((1 << taille) - 1), taille as u8);
+            *fins_de_bande = 0;
+        }
+    }
+    
+    for (composante, indice) in blocs_balayage(image, &balayage.composantes) {
+        let bloc = &image.composantes[composante].blocs[indice];
+        let table = (composante > 0) as usize;
+        
+        if balayage.debut == 0 {
+            let difference = bloc[0] as i32 - predictions[composante];
+            predictions[composante] = bloc[0] as i32;
+            let (taille, bits) = coder_amplitude(difference);
+            sortie.symbole(table, taille);
+            sortie.bits(bits, taille);
+        }
+        if balayage.fin == 0 {
+            continue;
+        }
+        
+        let mut zeros = 0;
+        for &coefficient in &bloc[balayage.debut.max(1)..=balayage.fin] {
+            if coefficient == 0 {
+                zeros += 1;
+                continue;
+            }
+            vider_fins_de_bande(&mut fins_de_bande, 2 + table, sortie);
+            while zeros > 15 {
+                sortie.symbole(2 + table, 0xF0);
+                zeros -= 16;
+            }
+            let (taille, bits) = coder_amplitude(coefficient as i32);
+            sortie.symbole(2 + table, (zeros << 4) as u8 | taille);
+            sortie.bits(bits, taille);
+            zeros = 0;
+        }
+        
+        if zeros > 0 {
+            if balayage.debut == 0 {
+                sortie.symbole(2 + table, 0x00);
+            } else {
+                fins_de_bande += 1;
+                if fins_de_bande == 0x7FFF {
+                    vider_fins_de_bande(&mut fins_de_bande, 2 + table, sortie);
+                }
+            }
+        }
+    }
+    
+    let table = (balayage.composantes[0] > 0) as usize;
+    vider_fins_de_bande(&mut fins_de_bande, 2 + table, sortie);
+}
+
+/**
+ * Écrit un JPEG à partir de ses coefficients, avec des tables de Huffman optimales par balayage
+ * 
+ * En progressif, les DC sont suivis des bandes AC 1-5 puis 6-63 de chaque composante.
+ */
+pub(crate) fn ecrire_jpeg_dct(image: &ImageJpegDct, progressif: bool) -> Vec<u8> {
+    let nombre = image.composantes.len();
+    // Une MCU entrelacée compte au plus 10 blocs
+    let entrelacable = image.composantes.iter().map(|c| c.facteur_h * c.facteur_v).sum::<usize>() <= 10;
+    let groupes: Vec<Vec<usize>> = if entrelacable {
+        vec![(0..nombre).collect()]
+    } else {
+        (0..nombre).map(|composante| vec![composante]).collect()
+    };
+    
+    let mut balayages = Vec::new();
+    if progressif {
+        for composantes in groupes {
+            balayages.push(BalayageJpeg { composantes, debut: 0, fin: 0 });
+        }
+        for (debut, fin) in [(1, 5), (6, 63)] {
+            for composante in 0..nombre {
+                balayages.push(BalayageJpeg { composantes: vec![composante], debut, fin });
+            }
+        }
+    } else {
+        for composantes in groupes {
+            balayages.push(BalayageJpeg { composantes, debut: 0, fin: 63 });
+        }
+    }
+    
+    let mut sortie = vec![0xFF, 0xD8];
+    sortie.extend_from_slice(&image.segments);
+    let mut trame = image.trame.clone();
+    if progressif {
+        trame[1] = 0xC2;
+    }
+    sortie.extend_from_slice(&trame);
+    
+    for balayage in &balayages {
+        let mut frequences = FrequencesHuffman([[0; 256]; 4]);
+        encoder_balayage_jpeg(image, balayage, &mut frequences);
+        
+        let mut dht = Vec::new();
+        let mut codes = [[(0, 0); 256]; 4];
+        for (indice, frequences) in frequences.0.iter().enumerate() {
+            if frequences.iter().all(|&n| n == 0) {
+                continue;
+            }
+            let table = TableHuffman::optimale(frequences);
+            codes[indice] = table.codes();
+            dht.push((((indice / 2) << 4) | (indice % 2)) as u8);
+            dht.extend_from_slice(&table.longueurs);
+            dht.extend_from_slice(&table.symboles);
+        }
+        sortie.extend_from_slice(&[0xFF, 0xC4]);
+        sortie.extend_from_slice(&((dht.len() + 2) as u16).to_be_bytes());
+        sortie.extend_from_slice(&dht);
+        
+        let mut sos = vec![balayage.composantes.len() as u8];
+        for &composante in &balayage.composantes {
+            let table = (composante > 0) as u8;
+            sos.extend_from_slice(&[image.composantes[composante].identifiant, table << 4 | table]);
+        }
+        sos.extend_from_slice(&[balayage.debut as u8, balayage.fin as u8, 0]);
+        sortie.extend_from_slice(&[0xFF, 0xDA]);
+        sortie.extend_from_slice(&((sos.len() + 2) as u16).to_be_bytes());
+        sortie.extend_from_slice(&sos);
+        
+        let mut codage = CodageHuffman { codes, ecrivain: EcrivainBitsJpeg::default() };
+        encoder_balayage_jpeg(image, balayage, &mut codage);
+        sortie.extend_from_slice(&codage.ecrivain.terminer());
+    }
+    
+    sortie.extend_from_slice(&[0xFF, 0xD9]);
+    sortie
+}
+
+// === Tests du module ===
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Encode un dégradé aux dimensions non multiples de 16
+    fn encoder_test(echantillonnage: jpeg_encoder::SamplingFactor, intervalle_reprise: u16) -> Vec<u8> {
+        let degrade = image::RgbImage::from_fn(37, 21, |x, y| image::Rgb([(x * 6) as u8, (y * 11) as u8, ((x ^ y) * 9) as u8]));
+        let mut jpeg = Vec::new();
+        let mut encodeur = jpeg_encoder::Encoder::new(&mut jpeg, 85);
+        encodeur.set_sampling_factor(echantillonnage);
+        if intervalle_reprise > 0 {
+            encodeur.set_restart_interval(intervalle_reprise);
+        }
+        encodeur.encode(degrade.as_raw(), 37, 21, jpeg_encoder::ColorType::Rgb).unwrap();
+        jpeg
+    }
+    
+    /// Insère un segment juste après SOI
+    fn inserer_segment(jpeg: &mut Vec<u8>, marqueur: u8, contenu: &[u8]) {
+        let mut segment = vec![0xFF, marqueur];
+        segment.extend_from_slice(&((contenu.len() + 2) as u16).to_be_bytes());
+        segment.extend_from_slice(contenu);
+        jpeg.splice(2..2, segment);
+    }
+    
+    /// Transcode sans perte en séquentiel puis en progressif et vérifie les pixels
+    fn verifier_transcodage(jpeg: &[u8]) {
+        let pixels = image::load_from_memory(jpeg).unwrap().to_rgb8();
+        for progressif in [false, true] {
+            let image = lire_coefficients_jpeg(jpeg, |_, _| {}).expect("Le JPEG devrait être lisible");
+            let sortie = ecrire_jpeg_dct(&image, progressif);
+            assert_eq!(image::load_from_memory(&sortie).unwrap().to_rgb8(), pixels, "Pixels modifiés (progressif : {})", progressif);
+        }
+    }
+    
+    #[test]
+    fn test_sous_echantillonnages() {
+        for echantillonnage in [jpeg_encoder::SamplingFactor::R_4_2_2, jpeg_encoder::SamplingFactor::R_4_4_4] {
+            let jpeg = encoder_test(echantillonnage, 0);
+            let trame = lire_coefficients_jpeg(&jpeg, |_, _| {}).unwrap();
+            let facteur_h = if echantillonnage == jpeg_encoder::SamplingFactor::R_4_2_2 { 2 } else { 1 };
+            assert_eq!((trame.composantes[0].facteur_h, trame.composantes[0].facteur_v), (facteur_h, 1));
+            verifier_transcodage(&jpeg);
+        }
+    }
+    
+    #[test]
+    fn test_intervalle_reprise_avec_remplissage() {
+        let jpeg = encoder_test(jpeg_encoder::SamplingFactor::R_4_2_2, 2);
+        
+        // Octets 0xFF de remplissage devant chaque RSTn et devant EOI
+        let debut_sos = jpeg.windows(2).position(|marqueur| marqueur == [0xFF, 0xDA]).unwrap();
+        let debut_donnees = debut_sos + 2 + u16::from_be_bytes([jpeg[debut_sos + 2], jpeg[debut_sos + 3]]) as usize;
+        let mut remplie = jpeg[..debut_donnees].to_vec();
+        let mut reprises = 0;
+        for (indice, &octet) in jpeg.iter().enumerate().skip(debut_donnees) {
+            if octet == 0xFF && matches!(jpeg.get(indice + 1), Some(0xD0..=0xD7 | 0xD9)) {
+                remplie.extend_from_slice(&[0xFF, 0xFF]);
+                reprises += 1;
+            }
+            remplie.push(octet);
+        }
+        assert!(reprises > 2, "Le JPEG de test doit contenir des marqueurs de reprise");
+        
+        assert_eq!(image::load_from_memory(&remplie).unwrap().to_rgb8(), image::load_from_memory(&jpeg).unwrap().to_rgb8());
+        verifier_transcodage(&remplie);
+    }
+    
+    #[test]
+    fn test_segments_tronques_ou_impairs() {
+        let mut jpeg = encoder_test(jpeg_encoder::SamplingFactor::R_4_2_0, 0);
+        
+        // Segments de longueur impaire, recopiés à l'octet près
+        inserer_segment(&mut jpeg, 0xFE, b"abc");
+        inserer_segment(&mut jpeg, 0xE5, b"impair");
+        verifier_transcodage(&jpeg);
+        let image = lire_coefficients_jpeg(&jpeg, |segment, conserves| conserves.extend_from_slice(segment)).unwrap();
+        let sortie = ecrire_jpeg_dct(&image, false);
+        assert!(sortie.windows(7).any(|fenetre| fenetre == [0xFF, 0xFE, 0x00, 0x05, b'a', b'b', b'c']));
+        assert!(sortie.windows(6).any(|fenetre| fenetre == b"impair"));
+        
+        // Toute troncature est refusée sans paniquer
+        for longueur in 0..jpeg.len() {
+            assert!(lire_coefficients_jpeg(&jpeg[..longueur], |_, _| {}).is_none(), "Troncature à {} octets acceptée", longueur);
+        }
+        
+        // Longueur de segment inférieure à ses propres deux octets
+        for longueur in [0u8, 1] {
+            let mut corrompu = jpeg.clone();
+            corrompu.splice(2..2, [0xFF, 0xE1, 0x00, longueur]);
+            assert!(lire_coefficients_jpeg(&corrompu, |_, _| {}).is_none());
+        }
+    }
+    
+    #[test]
+    fn test_photo_appareil() {
+        // Disposition d'un boîtier : 4:2:2, intervalles de reprise, EXIF avec vignette JPEG, MPF, IPTC et commentaire
+        let mut jpeg = encoder_test(jpeg_encoder::SamplingFactor::R_4_2_2, 4);
+        
+        let mut vignette = Vec::new();
+        jpeg_encoder::Encoder::new(&mut vignette, 70)
+            .encode(&[128; 8 * 8 * 3], 8, 8, jpeg_encoder::ColorType::Rgb)
+            .unwrap();
+        
+        // IFD0 en 8 (orientation), IFD1 en 26 (vignette), vignette en 56
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        let entree = |tiff: &mut Vec<u8>, balise: u16, type_valeur: u16, valeur: u32| {
+            tiff.extend_from_slice(&balise.to_le_bytes());
+            tiff.extend_from_slice(&type_valeur.to_le_bytes());
+            tiff.extend_from_slice(&1u32.to_le_bytes());
+            tiff.extend_from_slice(&valeur.to_le_bytes());
+        };
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        entree(&mut tiff, 0x0112, 3, 6);
+        tiff.extend_from_slice(&26u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        entree(&mut tiff, 0x0201, 4, 56);
+        entree(&mut tiff, 0x0202, 4, vignette.len() as u32);
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(&vignette);
+        let mut exif = b"Exif\0\0".to_vec();
+        exif.extend_from_slice(&tiff);
+        
+        inserer_segment(&mut jpeg, 0xFE, b"Appareil photo");
+        inserer_segment(&mut jpeg, 0xED, b"Photoshop 3.0\08BIM\x04\x04\0\0\0\0\0\0");
+        inserer_segment(&mut jpeg, 0xE2, b"MPF\0II*\0\x08\0\0\0");
+        inserer_segment(&mut jpeg, 0xE1, &exif);
+        verifier_transcodage(&jpeg);
+        
+        // Tout conservé : l'EXIF et sa vignette restent lisibles
+        let image = lire_coefficients_jpeg(&jpeg, |segment, conserves| conserves.extend_from_slice(segment)).unwrap();
+        let sortie = ecrire_jpeg_dct(&image, true);
+        let relu = exif::Reader::new().read_from_container(&mut std::io::Cursor::new(&sortie)).unwrap();
+        let orientation = relu.get_field(exif::Tag::Orientation, exif::In::PRIMARY).and_then(|champ| champ.value.get_uint(0));
+        assert_eq!(orientation, Some(6));
+        assert!(sortie.windows(vignette.len()).any(|fenetre| fenetre == vignette.as_slice()), "La vignette doit être intacte");
+        for signature in [&b"MPF\0"[..], b"Photoshop 3.0", b"Appareil photo"] {
+            assert!(sortie.windows(signature.len()).any(|fenetre| fenetre == signature));
+        }
+        
+        // Rien conservé : seuls les segments nécessaires au rendu subsistent
+        let image = lire_coefficients_jpeg(&jpeg, |_, _| {}).unwrap();
+        let sortie = ecrire_jpeg_dct(&image, false);
+        assert!(!sortie.windows(4).any(|fenetre| fenetre == b"Exif" || fenetre == b"MPF\0"));
+        assert_eq!(sortie[2..4], [0xFF, 0xE0], "L'APP0 JFIF doit rester en tête");
+    }
+}
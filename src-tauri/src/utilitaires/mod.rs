@@ -44,6 +44,12 @@ pub mod progression;
 /// courantes sur les fichiers et dossiers.
 pub mod fichiers;
 
+/// Module contenant le codec entropique JPEG
+/// 
+/// Ce module lit et réécrit les coefficients DCT d'un JPEG pour
+/// l'optimiser sans perte, sans repasser par les pixels.
+pub mod jpeg;
+
 // === Futurs modules à ajouter ===
 // Décommentez et ajoutez selon les besoins de votre application
 